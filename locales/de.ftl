@@ -0,0 +1,66 @@
+app-greeting = Hallo Welt
+
+## hud
+
+hud-captures = { $count ->
+    [0] Noch keine Figur geschlagen
+    [one] Eine Figur geschlagen
+   *[other] { $count } Figuren geschlagen
+}
+
+hud-moves = Züge
+
+## debug window
+
+ui-resources = Ressourcen
+ui-frame-time = Framezeit: { $milliseconds }
+ui-mouse-position = Mausposition: ({ $x },{ $y })
+ui-highlighted-squares = { $count ->
+    [0] Keine Felder markiert
+    [one] Ein Feld markiert
+   *[other] { $count } Felder markiert
+}
+ui-language = Sprache
+ui-lighting = Beleuchtung
+ui-controls = Steuerung
+ui-controls-listening = Taste drücken, Escape zum Abbrechen
+ui-controls-save = Speichern
+
+## lighting moods
+
+mood-day = Tag
+mood-dusk = Dämmerung
+mood-night = Nacht
+
+## actions
+
+action-exit = Beenden
+action-pan-up = Nach oben schwenken
+action-pan-down = Nach unten schwenken
+action-pan-left = Nach links schwenken
+action-pan-right = Nach rechts schwenken
+
+## pieces and moves
+
+piece-pawn = Bauer
+piece-knight = Springer
+piece-bishop = Läufer
+piece-rook = Turm
+piece-queen = Dame
+piece-king = König
+
+move = { $piece } von { $from } nach { $to }
+move-capture = { $piece } schlägt auf { $to }
+
+## game results
+
+result-checkmate = Schachmatt, { $winner ->
+    [white] Weiß
+   *[black] Schwarz
+} gewinnt
+result-resignation = { $winner ->
+    [white] Schwarz gibt auf, Weiß gewinnt
+   *[black] Weiß gibt auf, Schwarz gewinnt
+}
+result-stalemate = Patt, die Partie endet remis
+result-draw = Remis vereinbart
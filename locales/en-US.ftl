@@ -0,0 +1,66 @@
+app-greeting = Hello World
+
+## hud
+
+hud-captures = { $count ->
+    [0] No captures yet
+    [one] One capture
+   *[other] { $count } captures
+}
+
+hud-moves = Moves
+
+## debug window
+
+ui-resources = Resources
+ui-frame-time = Frametime: { $milliseconds }
+ui-mouse-position = Mouse Position: ({ $x },{ $y })
+ui-highlighted-squares = { $count ->
+    [0] No squares highlighted
+    [one] One square highlighted
+   *[other] { $count } squares highlighted
+}
+ui-language = Language
+ui-lighting = Lighting
+ui-controls = Controls
+ui-controls-listening = Press a key or button, Escape to cancel
+ui-controls-save = Save
+
+## lighting moods
+
+mood-day = Day
+mood-dusk = Dusk
+mood-night = Night
+
+## actions
+
+action-exit = Quit
+action-pan-up = Pan up
+action-pan-down = Pan down
+action-pan-left = Pan left
+action-pan-right = Pan right
+
+## pieces and moves
+
+piece-pawn = Pawn
+piece-knight = Knight
+piece-bishop = Bishop
+piece-rook = Rook
+piece-queen = Queen
+piece-king = King
+
+move = { $piece } from { $from } to { $to }
+move-capture = { $piece } takes on { $to }
+
+## game results
+
+result-checkmate = Checkmate, { $winner ->
+    [white] White
+   *[black] Black
+} wins
+result-resignation = { $winner ->
+    [white] Black resigns, White wins
+   *[black] White resigns, Black wins
+}
+result-stalemate = Stalemate, the game is drawn
+result-draw = Draw agreed
@@ -0,0 +1,66 @@
+app-greeting = Hola mundo
+
+## hud
+
+hud-captures = { $count ->
+    [0] Ninguna captura
+    [one] Una captura
+   *[other] { $count } capturas
+}
+
+hud-moves = Jugadas
+
+## debug window
+
+ui-resources = Recursos
+ui-frame-time = Tiempo de fotograma: { $milliseconds }
+ui-mouse-position = Posición del ratón: ({ $x },{ $y })
+ui-highlighted-squares = { $count ->
+    [0] Ninguna casilla resaltada
+    [one] Una casilla resaltada
+   *[other] { $count } casillas resaltadas
+}
+ui-language = Idioma
+ui-lighting = Iluminación
+ui-controls = Controles
+ui-controls-listening = Pulsa una tecla o botón, Escape para cancelar
+ui-controls-save = Guardar
+
+## lighting moods
+
+mood-day = Día
+mood-dusk = Atardecer
+mood-night = Noche
+
+## actions
+
+action-exit = Salir
+action-pan-up = Desplazar arriba
+action-pan-down = Desplazar abajo
+action-pan-left = Desplazar a la izquierda
+action-pan-right = Desplazar a la derecha
+
+## pieces and moves
+
+piece-pawn = Peón
+piece-knight = Caballo
+piece-bishop = Alfil
+piece-rook = Torre
+piece-queen = Dama
+piece-king = Rey
+
+move = { $piece } de { $from } a { $to }
+move-capture = { $piece } captura en { $to }
+
+## game results
+
+result-checkmate = Jaque mate, { $winner ->
+    [white] ganan las blancas
+   *[black] ganan las negras
+}
+result-resignation = { $winner ->
+    [white] Las negras abandonan, ganan las blancas
+   *[black] Las blancas abandonan, ganan las negras
+}
+result-stalemate = Ahogado, la partida es tablas
+result-draw = Tablas por acuerdo
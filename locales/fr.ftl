@@ -0,0 +1,66 @@
+app-greeting = Bonjour le monde
+
+## hud
+
+hud-captures = { $count ->
+    [0] Aucune prise
+    [one] Une prise
+   *[other] { $count } prises
+}
+
+hud-moves = Coups
+
+## debug window
+
+ui-resources = Ressources
+ui-frame-time = Temps d'image : { $milliseconds }
+ui-mouse-position = Position de la souris : ({ $x },{ $y })
+ui-highlighted-squares = { $count ->
+    [0] Aucune case surlignée
+    [one] Une case surlignée
+   *[other] { $count } cases surlignées
+}
+ui-language = Langue
+ui-lighting = Éclairage
+ui-controls = Commandes
+ui-controls-listening = Appuyez sur une touche, Échap pour annuler
+ui-controls-save = Enregistrer
+
+## lighting moods
+
+mood-day = Jour
+mood-dusk = Crépuscule
+mood-night = Nuit
+
+## actions
+
+action-exit = Quitter
+action-pan-up = Défiler vers le haut
+action-pan-down = Défiler vers le bas
+action-pan-left = Défiler vers la gauche
+action-pan-right = Défiler vers la droite
+
+## pieces and moves
+
+piece-pawn = Pion
+piece-knight = Cavalier
+piece-bishop = Fou
+piece-rook = Tour
+piece-queen = Dame
+piece-king = Roi
+
+move = { $piece } de { $from } à { $to }
+move-capture = { $piece } prend en { $to }
+
+## game results
+
+result-checkmate = Échec et mat, { $winner ->
+    [white] les Blancs gagnent
+   *[black] les Noirs gagnent
+}
+result-resignation = { $winner ->
+    [white] Les Noirs abandonnent, les Blancs gagnent
+   *[black] Les Blancs abandonnent, les Noirs gagnent
+}
+result-stalemate = Pat, la partie est nulle
+result-draw = Nulle par accord mutuel
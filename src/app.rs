@@ -1,12 +1,12 @@
-use std::time::{Instant, Duration};
+use std::{path::PathBuf, time::{Instant, Duration}};
 
 use bevy_ecs::prelude::*;
-use winit::{event_loop::ControlFlow, event::{WindowEvent, KeyboardInput, ElementState, VirtualKeyCode, Event}};
+use winit::{dpi::PhysicalSize, event_loop::ControlFlow, event::{WindowEvent, VirtualKeyCode, Event}};
 
 #[derive(Debug)]
 struct Update;
 
-use crate::{core::{WindowSystem, EventSystem}, graphics::{Renderer, RenderContext}, two_dimensional::{text::{TextPass, TextBox}, sprite::{SpritePass, Sprite}, Camera2d, CameraController2dPan}, ui::UI, Board};
+use crate::{core::{WindowSystem, EventSystem, Recorder, RecordingHeader, Replay, ActionState, AxisDirection, Binding, Gamepads, InputMap, KeyBinding}, graphics::{Renderer, RenderContext}, two_dimensional::{text::{TextPass, TextBox, FontRegistry, FontHandle}, tilemap::TilemapPass, particles::{ParticlePass, ParticleEmitter, ParticleRng}, lighting::LightingPass, gizmos::{GizmoPass, Gizmos}, path::PathPass, sprite::{SpritePass, Sprite, SpriteAnimator, AnimationFinished, Fade, NineSlice, SliceMode}, Camera2d, CameraBounds, CameraController2dPan, CameraFollow, CameraShake, CameraTransition, Picking, PickingEvent, RenderLayers, Transform2d, GlobalTransform2d}, ui::UI, localization::{Localization, LocalizedText}, board::{CaptureCounter, MoveLog}, Board};
 
 //maybe some way to improve rendering performace, but it seems like we're just running into issues with high resolutions
 //and this integrated rendering, but idk performance is fucking terrible so im probably doing something wrong
@@ -14,12 +14,170 @@ pub struct App;
 
 pub struct FrameTime(pub Duration);
 
+//fonts we look for next to the executable, text falls back to the embedded font when they are missing
+const TITLE_FONT: &str = "serif";
+//has the chess figurine glyphs used in move notation
+pub(crate) const FIGURINE_FONT: &str = "figurines";
+const FONTS: [(&str, &str); 2] = [(TITLE_FONT, "fonts/serif.ttf"), (FIGURINE_FONT, "fonts/figurines.ttf")];
+
+//screen text on this layer is drawn by the hud camera, over the world and untouched by its lighting
+const HUD_LAYER: u8 = 1;
+
+//the frame behind hud panels, its borders in texture pixels, left, right, top, bottom
+const PANEL_TEXTURE: &str = "panel.png";
+const PANEL_INSETS: [f32; 4] = [5f32, 5f32, 5f32, 5f32];
+//how many screen pixels each of the frame's pixels covers
+const PANEL_PIXEL_SCALE: f32 = 3f32;
+
+//every string the player sees is looked up in these, english fills in for anything a translation is missing
+const FALLBACK_LOCALE: &str = "en-US";
+const LOCALES: [(&str, &str); 4] = [("en-US", "locales/en-US.ftl"), ("de", "locales/de.ftl"), ("fr", "locales/fr.ftl"), ("es", "locales/es.ftl")];
+
+//the bindings of every action, the controls window saves rebinds back to it
+pub(crate) const INPUT_MAP: &str = "config/input.json";
+const EXIT_ACTION: &str = "exit";
+
+//what every frame takes with --fixed-timestep, whatever the clock says
+const FIXED_TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+//command line options, mostly for recording sessions and playing them back
+//--record <file> writes every event to a file, --replay <file> plays one back in place of the window's input
+//--headless runs a replay in a hidden window without drawing and quits once it's over, it still needs a display to open the window
+//--fixed-timestep gives every frame the same frame time, so a recording doesn't depend on how fast the machine was
+#[derive(Default)]
+struct Options {
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+    headless: bool,
+    fixed_timestep: bool,
+}
+
+impl Options {
+    fn from_args() -> Self {
+        let mut options = Self::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" => options.record = args.next().map(PathBuf::from),
+                "--replay" => options.replay = args.next().map(PathBuf::from),
+                "--headless" => options.headless = true,
+                "--fixed-timestep" => options.fixed_timestep = true,
+                _ => eprintln!("Ignoring unknown argument {}", arg),
+            }
+        }
+        options
+    }
+}
+
 impl App {
+    fn load_locales(world: &mut World) {
+        let mut localization = Localization::new(FALLBACK_LOCALE).expect("The fallback locale should be valid");
+        for (locale, path) in LOCALES {
+            if let Err(error) = localization.load(locale, path) {
+                eprintln!("Couldn't load the {} translation: {}", locale, error);
+            }
+        }
+        world.insert_resource(localization);
+    }
+
+    //the bindings we ship with, for when the input map is missing or leaves out an action
+    fn default_input_map() -> InputMap {
+        let pan_bindings = [
+            ("pan_up", [Binding::key(VirtualKeyCode::W), Binding::key(VirtualKeyCode::Up), Binding::Gamepad(gilrs::Button::DPadUp), Binding::GamepadAxis((gilrs::Axis::LeftStickY, AxisDirection::Positive))]),
+            ("pan_down", [Binding::key(VirtualKeyCode::S), Binding::key(VirtualKeyCode::Down), Binding::Gamepad(gilrs::Button::DPadDown), Binding::GamepadAxis((gilrs::Axis::LeftStickY, AxisDirection::Negative))]),
+            ("pan_left", [Binding::key(VirtualKeyCode::A), Binding::key(VirtualKeyCode::Left), Binding::Gamepad(gilrs::Button::DPadLeft), Binding::GamepadAxis((gilrs::Axis::LeftStickX, AxisDirection::Negative))]),
+            ("pan_right", [Binding::key(VirtualKeyCode::D), Binding::key(VirtualKeyCode::Right), Binding::Gamepad(gilrs::Button::DPadRight), Binding::GamepadAxis((gilrs::Axis::LeftStickX, AxisDirection::Positive))]),
+        ];
+
+        let mut input_map = InputMap::new()
+            .with_binding(EXIT_ACTION, Binding::key(VirtualKeyCode::Escape))
+            .with_binding(EXIT_ACTION, Binding::Key(KeyBinding::new(VirtualKeyCode::Q).with_ctrl()))
+            .with_binding(EXIT_ACTION, Binding::Gamepad(gilrs::Button::Select));
+        //the actions CameraController2dPan reads by default
+        for (action, bindings) in pan_bindings {
+            for binding in bindings {
+                input_map.bind(action, binding);
+            }
+        }
+        input_map
+    }
+
+    fn load_input_map(world: &mut World) {
+        let defaults = Self::default_input_map();
+        let input_map = match InputMap::load(INPUT_MAP) {
+            Ok(input_map) => input_map.with_defaults(&defaults),
+            Err(error) => {
+                eprintln!("Couldn't load the input map, using the default bindings: {}", error);
+                defaults
+            }
+        };
+        world.insert_resource(input_map);
+        world.insert_resource(ActionState::new());
+    }
+
+    //a replay opens the window at the size it was recorded at, otherwise the cursor lands on different things
+    //and seeds the particles the way they were, a fresh session gets a new seed that a recording keeps
+    //the loaded options are updated to match what actually started
+    fn start_replay_and_recording(world: &mut World, event_system: &mut EventSystem, options: &mut Options) {
+        let window = world.get_resource::<WindowSystem>().expect("Window does not exist?").window();
+        let size = window.inner_size();
+        let mut window_size = (size.width, size.height);
+        let mut seed = rand::random::<u64>();
+
+        if let Some(path) = options.replay.as_ref() {
+            match Replay::load(path) {
+                Ok(replay) => {
+                    window_size = replay.header().window_size;
+                    seed = replay.header().seed;
+                    window.set_inner_size(PhysicalSize::new(window_size.0, window_size.1));
+                    event_system.start_replay(replay);
+                }
+                Err(error) => {
+                    eprintln!("Couldn't load the replay: {}", error);
+                    options.replay = None;
+                }
+            }
+        }
+
+        //with nothing to replay there'd be no way to stop a headless run
+        if options.headless && options.replay.is_none() {
+            eprintln!("--headless only works with --replay, opening the window");
+            options.headless = false;
+        }
+        window.set_visible(!options.headless);
+
+        if let Some(path) = options.record.as_ref() {
+            //the window may not have been resized to the replay's size yet
+            match Recorder::create(path, RecordingHeader { window_size, seed }) {
+                Ok(recorder) => event_system.start_recording(recorder),
+                Err(error) => eprintln!("Couldn't start recording: {}", error),
+            }
+        }
+        world.insert_resource(ParticleRng::seeded(seed));
+    }
+
+    fn load_fonts(world: &mut World) {
+        let mut font_registry = world.get_resource_mut::<FontRegistry>().expect("Text pass has not been initialized");
+        for (name, path) in FONTS {
+            if let Err(error) = font_registry.load(name, path) {
+                eprintln!("Couldn't load the {} font: {}", name, error);
+            }
+        }
+
+        //the figurines fill in for the chess symbols the other fonts don't have
+        if let Some(figurines) = font_registry.get(FIGURINE_FONT) {
+            let fonts: Vec<FontHandle> = [FontHandle::DEFAULT].into_iter().chain(font_registry.get(TITLE_FONT)).collect();
+            for font in fonts {
+                font_registry.set_fallbacks(font, &[figurines]);
+            }
+        }
+    }
 
     //for now we're doing event based updates, when there are no more events we draw to the screen
     pub async fn run() {
 
         env_logger::init();
+        let mut options = Options::from_args();
         let event_loop: winit::event_loop::EventLoop<Update> = winit::event_loop::EventLoop::with_user_event();
         //create a proxy, and start another thread
         let update_proxy = event_loop.create_proxy();
@@ -39,47 +197,117 @@ impl App {
         WindowSystem::register_system(&mut world, "Worlds", &event_loop);
 
         let mut renderer = Renderer::new();
+        renderer.add_pass::<TilemapPass>();
+        //the sprite pass was switched off until sprites could be placed with Transform2d, the board and its pieces need it on
+        renderer.add_pass::<SpritePass>();
+        //particles go after lighting so sparks still glow in the dark
+        renderer.add_pass::<LightingPass>();
+        renderer.add_pass::<ParticlePass>();
+        renderer.add_pass::<PathPass>();
+        renderer.add_pass::<GizmoPass>();
         renderer.add_pass::<TextPass>();
-        //renderer.add_pass::<SpritePass>();
         renderer.init(&mut world).await;
 
         let mut ui = UI::new(&mut world);
         let mut event_system = EventSystem::new();
         event_system.init(&mut world);
+        Self::start_replay_and_recording(&mut world, &mut event_system, &mut options);
+        Gamepads::init(&mut world);
+        Self::load_input_map(&mut world);
+
+        SpriteAnimator::init(&mut world);
+        Gizmos::init(&mut world);
+        Picking::init(&mut world);
 
         //init our shit
         world.insert_resource(Instant::now());
-        world.spawn().insert(TextBox { text: String::from("Hello World"), position: (30f32, 30f32), color: [0f32, 0f32, 0.5f32, 1f32], scale: 40f32 });
+        Self::load_fonts(&mut world);
+        Self::load_locales(&mut world);
+        world.spawn().insert(LocalizedText::new("app-greeting")).insert(
+            TextBox::new("", (30f32, 30f32), [0f32, 0f32, 0.5f32, 1f32], 40f32)
+                .with_font_name(TITLE_FONT)
+                .with_outline([1f32, 1f32, 1f32, 1f32], 2f32)
+                .with_shadow([0f32, 0f32, 0f32, 0.5f32], (3f32, 3f32), 2f32),
+        ).insert(RenderLayers::layer(HUD_LAYER));
+        world.spawn()
+            .insert(LocalizedText::new("hud-captures").with_arg("count", 0))
+            .insert(CaptureCounter::default())
+            .insert(TextBox::new("", (30f32, 80f32), [0f32, 0f32, 0.5f32, 1f32], 24f32).with_font_name(TITLE_FONT))
+            .insert(RenderLayers::layer(HUD_LAYER));
+        //the log sits on a framed panel that grows with it, the frame's studs repeat along its edges rather than stretching
+        //Board::keep_log_at_bottom sizes the panel to the text
+        let hud_camera = Camera2d::new((0f32, 0f32)).with_priority(1).with_layers(RenderLayers::layer(HUD_LAYER)).without_lighting();
+        let panel_slice = NineSlice::new(PANEL_INSETS, PANEL_PIXEL_SCALE / hud_camera.pixels_per_unit()).with_modes(SliceMode::Tile, SliceMode::Stretch);
+        world.spawn()
+            .insert(LocalizedText::new("hud-moves"))
+            .insert(MoveLog)
+            .insert(
+                TextBox::new("", (30f32, 0f32), [0f32, 0f32, 0.5f32, 1f32], 20f32)
+                    .with_font_name(TITLE_FONT)
+                    .with_line_spacing(1.2f32)
+                    .with_glow([1f32, 1f32, 1f32, 0.6f32], 3f32),
+            )
+            .insert(Sprite::new([0f32, 0f32], [0f32, 0f32], [1f32, 1f32, 1f32, 1f32]).with_texture(PANEL_TEXTURE).with_nine_slice(panel_slice))
+            .insert(RenderLayers::layer(HUD_LAYER));
         
-        world.spawn().insert(Sprite::new([11f32, 0f32], [0.4f32, 0.4f32], [1.0, 1.0, 1.0]).with_tile_in_texture("chess_piece_bitmap.png", 2, 6, 1, 0));
+        world.spawn()
+            .insert(Sprite::new([11f32, 0f32], [0.4f32, 0.4f32], [1.0, 1.0, 1.0, 1.0]).with_tile_in_texture("chess_piece_bitmap.png", 2, 6, 1, 0))
+            .insert(Transform2d::IDENTITY.with_pivot([11.2f32, 0.2f32]).with_rotation(0.3f32).with_flip(true, false));
 
         Board::init(&mut world);
 
-        world.spawn().insert(Camera2d::new((0f32, 0f32))).insert(CameraController2dPan::new());
+        //panning stops a little past the board and the pieces off to its side
+        world.spawn()
+            .insert(Camera2d::new((0f32, 0f32)))
+            .insert(CameraController2dPan::new())
+            .insert(CameraBounds::new((-3f32, -3f32), (14f32, 11f32)))
+            .insert(CameraShake::new())
+            //starts on a corner of the board and pulls back to take all of it in
+            .insert(CameraTransition::move_to((4f32, 4f32), Duration::from_millis(1500)).with_zoom(30f32));
+        world.spawn().insert(hud_camera);
 
+        let mut gamepad_system = SystemStage::single(Gamepads::poll);
+        let mut action_system = SystemStage::single(ActionState::update);
         let mut camera_resize_system = SystemStage::single(Camera2d::resize);
         let mut camera_controller_system = SystemStage::single(CameraController2dPan::update);
+        let mut camera_follow_system = SystemStage::single(CameraFollow::update);
+        let mut camera_transition_system = SystemStage::single(CameraTransition::update);
+        let mut camera_bounds_system = SystemStage::single(CameraBounds::clamp);
+        let mut camera_shake_system = SystemStage::single(CameraShake::update);
+        let mut transform_system = SystemStage::single(GlobalTransform2d::propagate);
+        let mut animation_system = SystemStage::single(SpriteAnimator::update);
+        let mut fade_system = SystemStage::single(Fade::update);
+        let mut particle_system = SystemStage::single(ParticleEmitter::update);
+        let mut capture_system = SystemStage::single(Board::finish_captures);
+        let mut dropped_maps_system = SystemStage::single(Board::load_dropped_maps);
+        let mut highlight_outline_system = SystemStage::single(Board::outline_highlights);
+        let mut picking_system = SystemStage::single(Picking::update);
+        let mut drag_pieces_system = SystemStage::single(Board::drag_pieces);
+        let mut hover_outline_system = SystemStage::single(Board::outline_hovered);
+        let mut picking_events_system = SystemStage::single(Events::<PickingEvent>::update_system);
+        let mut animation_events_system = SystemStage::single(Events::<AnimationFinished>::update_system);
+        let mut localization_system = SystemStage::single(LocalizedText::update);
+        let mut move_log_system = SystemStage::single(Board::keep_log_at_bottom);
+        let mut gizmo_clear_system = SystemStage::single(Gizmos::clear);
 
         let mut last_frame = Instant::now();
         event_loop.run(move |event, _, control_flow| { 
             let my_window_id = world.get_resource::<WindowSystem>().expect("Window does not exist?").window().id();
             
+            //a headless replay doesn't wait for redraws, which a hidden window may never get, it runs a frame every time round
+            let frame_due = match event {
+                Event::RedrawRequested(window_id) => window_id == my_window_id && !options.headless,
+                Event::MainEventsCleared => options.headless,
+                _ => false,
+            };
+
             match event {
                 Event::WindowEvent {
                     ref event,
                     window_id,
                 } if window_id == my_window_id => match event {
-                    //quit if they press escape or close the window
-                    WindowEvent::CloseRequested
-                    | WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
-                                ..
-                            },
-                        ..
-                    } => *control_flow = ControlFlow::Exit,
+                    //quit if they close the window, the exit action is checked with the others once a frame
+                    WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                     //handle resizes
                     WindowEvent::Resized(new_size) => {
                         let mut render_context = world.get_resource_mut::<RenderContext>().expect("Renderer is not initialized and render was called");
@@ -91,37 +319,78 @@ impl App {
                     },
                     e => event_system.on_event(&mut world, e)
                 },
-                Event::RedrawRequested(window_id) if window_id == my_window_id => {
-                    event_system.update(&mut world);
+                Event::MainEventsCleared if !options.headless => {
+                    world.get_resource::<WindowSystem>().expect("No window?").window().request_redraw();
+                }
+                _ => {}
+            };
+
+            if frame_due {
+                //a replay swaps in the frame time it recorded
+                let measured = if options.fixed_timestep { FIXED_TIMESTEP } else { last_frame.elapsed() };
+                last_frame = Instant::now();
+                let frame_time = event_system.update(&mut world, measured);
+                world.insert_resource(FrameTime(frame_time));
+
+                //gamepads aren't recorded, so they're left alone during a replay rather than mixing into it
+                if !event_system.is_replaying() {
+                    gamepad_system.run(&mut world);
+                }
+                action_system.run(&mut world);
+                if world.get_resource::<ActionState>().is_some_and(|actions| actions.just_pressed(EXIT_ACTION)) {
+                    *control_flow = ControlFlow::Exit;
+                }
+
+                //cameras spawned or given a new viewport since the last resize pick up their size here
+                camera_resize_system.run(&mut world);
+                camera_controller_system.run(&mut world);
+                transform_system.run(&mut world);
 
-                    camera_controller_system.run(&mut world);
+                //follow and transitions move the camera, then bounds have the final say before the shake goes on top
+                camera_follow_system.run(&mut world);
+                camera_transition_system.run(&mut world);
+                camera_bounds_system.run(&mut world);
+                camera_shake_system.run(&mut world);
+                picking_system.run(&mut world);
 
-                    //generate frametime here, and set it as a resource
-                    world.insert_resource(FrameTime(last_frame.elapsed()));
-                    last_frame = Instant::now();
+                animation_system.run(&mut world);
+                fade_system.run(&mut world);
+                particle_system.run(&mut world);
+                capture_system.run(&mut world);
+                dropped_maps_system.run(&mut world);
+                highlight_outline_system.run(&mut world);
+                drag_pieces_system.run(&mut world);
+                hover_outline_system.run(&mut world);
+                picking_events_system.run(&mut world);
+                animation_events_system.run(&mut world);
+                localization_system.run(&mut world);
+                move_log_system.run(&mut world);
 
+                //headless frames run every system but draw nothing
+                if !options.headless {
                     world.get_resource_mut::<RenderContext>().expect("No render context").build_surface_texture();
 
                     renderer.render(&mut world);
                     ui.render(&mut world);
 
                     world.get_resource_mut::<RenderContext>().expect("No render context").present();
-                    
-                    /*match renderer.render() {
-                        Ok(_) => {}
-                        // Reconfigure the surface if lost
-                        Err(wgpu::SurfaceError::Lost) => renderer.resize(None),
-                        // The system is out of memory, we should probably quit
-                        Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
-                        // All other errors (Outdated, Timeout) should be resolved by the next frame
-                        Err(e) => eprintln!("{:?}", e),
-                    }*/
                 }
-                Event::MainEventsCleared => {
-                    world.get_resource::<WindowSystem>().expect("No window?").window().request_redraw();
+                gizmo_clear_system.run(&mut world);
+
+                if options.headless && !event_system.is_replaying() {
+                    *control_flow = ControlFlow::Exit;
                 }
-                _ => {}
-            };
+
+                /*match renderer.render() {
+                    Ok(_) => {}
+                    // Reconfigure the surface if lost
+                    Err(wgpu::SurfaceError::Lost) => renderer.resize(None),
+                    // The system is out of memory, we should probably quit
+                    Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
+                    // All other errors (Outdated, Timeout) should be resolved by the next frame
+                    Err(e) => eprintln!("{:?}", e),
+                }*/
+            }
 
             ui.handle_event(&mut world, &event);
         });
@@ -1,70 +1,783 @@
-use bevy_ecs::prelude::*;
+use std::time::Duration;
 
-use crate::two_dimensional::sprite::Sprite;
+use bevy_ecs::{prelude::*, system::Command};
 
+use fluent_bundle::FluentArgs;
+
+use crate::app::FIGURINE_FONT;
+use crate::core::Input;
+use crate::graphics::RenderContext;
+use crate::localization::{LocalizedText, Localization};
+use crate::two_dimensional::{
+    add_child, despawn_recursive,
+    gizmos::{Gizmos, ShapeStyle},
+    lighting::{AmbientLight, Occluder, PointLight, SpotLight},
+    particles::{FrameMode, ParticleEmitter},
+    path::{Path2d, Stroke, StrokeCap, StrokeJoin},
+    text::{FontRegistry, HorizontalAlign, TextBox, TextSpan, VerticalAlign},
+    tilemap::{load_tiled_map, PropertyValue, Tilemap},
+    sprite::{AnimationClip, AnimationClips, AnimationFinished, AnimationMode, BlendMode, Fade, Sprite, SpriteAnimator},
+    AddChild, Camera2d, CameraFollow, CameraShake, CameraTransition, DespawnRecursive, GlobalTransform2d, Parent, Pickable, Picking, PickingEvent, RenderLayers, Transform2d,
+};
+
+//the board entity is the root of the board hierarchy, tiles are its children and pieces are children of their tile
+//the squares themselves are drawn by a tilemap on the board, the tile entities place what sits on them and are picked in their place
 #[derive(Component)]
-pub struct Board {
-    children: Vec<Vec<Entity>>,
-}
+pub struct Board;
+
+//which square a tile of the board is, (0, 0) being the bottom left
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Square(pub (usize, usize));
+
+//marks the overlay sprites put over squares by Board::highlight
+#[derive(Component)]
+pub struct Highlight;
+
+//marks the light Board::set_mood hangs beside the board after dark
+#[derive(Component)]
+struct Lantern;
+
+//the dot showing whose move it is, see Board::drag_pieces
+#[derive(Component)]
+pub struct TurnIndicator;
+
+//counts the captures made on the board into the count argument of the LocalizedText on the same entity
+#[derive(Component, Default)]
+pub struct CaptureCounter(usize);
 
+//the last few moves in words under the heading of the TextBox on the same entity, a span for the piece and one for the move
+//the box grows upwards from the bottom of the window, see Board::keep_log_at_bottom
+#[derive(Component)]
+pub struct MoveLog;
+
+//the log's text, the panel behind it and the layers that say which camera draws them, see Board::keep_log_at_bottom
+type MoveLogQuery<'w, 's> = Query<'w, 's, (&'static mut TextBox, Option<&'static mut Sprite>, Option<&'static RenderLayers>), With<MoveLog>>;
+
+//lives on the tile, pointing at the piece sprite sitting on it
 #[derive(Component)]
 pub struct Piece {
     entity: Entity,
+    //the cell of the piece in the piece sheet
+    cell: (u32, u32),
 }
 
 impl Board {
     pub fn init(world: &mut World) {
-        let mut children = Vec::new();
+        let board = world.spawn().insert(Transform2d::IDENTITY).id();
+
+        //a square is one unit across
+        match load_tiled_map(BOARD_MAP, [1f32, 1f32]) {
+            Ok(tilemap) => {
+                world.entity_mut(board).insert(tilemap);
+            }
+            Err(error) => eprintln!("Couldn't load the board squares from {}: {}", BOARD_MAP, error),
+        }
+
+        let mut tiles = Vec::new();
         for x in 0..8 {
             let mut column = Vec::new();
             for y in 0..8 {
-                let color = if (x + y) % 2 == 0 {
-                    [0.632, 0.3f32, 0f32]
-                } else {
-                    [1f32, 1f32, 1f32]
-                };
-
-                let position = [x as f32, y as f32];
-                let dimensions = [1f32, 1f32];
-
                 let tile = world
                     .spawn()
-                    .insert(Sprite::new(position, dimensions, color).with_depth(1))
+                    .insert(Transform2d::from_translation([x as f32, y as f32]))
+                    .insert(Square((x, y)))
+                    .insert(Pickable::new().with_area([0f32, 0f32], [1f32, 1f32]))
                     .id();
+                add_child(world, board, tile);
                 column.push(tile);
             }
-            children.push(column);
-        }
-
-        let board_data = [
-            (1, 1),
-            (1, 2),
-            (1, 3),
-            (1, 5),
-            (1, 4),
-            (1, 3),
-            (1, 2),
-            (1, 1),
-        ];
-
-        for (x, (row, col)) in board_data.iter().enumerate() {
-            let position = [x as f32, 0 as f32];
+            tiles.push(column);
+        }
+
+        //columns of the piece sheet along the back rank, white is the bottom row of the sheet and black the top
+        let back_rank = [1, 2, 3, 5, 4, 3, 2, 1];
+        let board_data = back_rank.iter().enumerate().flat_map(|(x, col)| [(x, 0, (1, *col)), (x, 7, (0, *col))]);
+
+        for (x, y, (row, col)) in board_data {
             let dimensions = [1f32, 1f32];
+            Self::register_piece_clips(world, row, col);
 
             let piece =
                 Piece {
                     entity: world
                         .spawn()
                         .insert(
-                            Sprite::new(position, dimensions, [1f32, 1f32, 1f32])
-                                .with_tile_in_texture("chess_piece_bitmap.png", 2, 6, *row, *col),
+                            Sprite::new([0f32, 0f32], dimensions, [1f32, 1f32, 1f32, 1f32])
+                                .with_opacity(0f32)
+                                .with_tile_in_texture(PIECE_SHEET, 2, 6, row, col),
                         )
+                        .insert(Fade::fade_in(Duration::from_millis(400)))
+                        .insert(SpriteAnimator::new(&idle_clip(row, col)))
+                        .insert(Pickable::draggable())
+                        .insert(Occluder::rectangle([0.25f32, 0.1f32], [0.5f32, 0.8f32]))
                         .id(),
+                    cell: (row, col),
                 };
 
-            world.get_entity_mut(children[0][x]).unwrap().insert(piece);
+            add_child(world, tiles[x][y], piece.entity);
+            world.get_entity_mut(tiles[x][y]).unwrap().insert(piece);
+        }
+
+        //dust drifting in the air over the board
+        let dust = world
+            .spawn()
+            .insert(ParticleEmitter::dust().with_texture(DUST_TEXTURE))
+            .insert(Transform2d::from_translation([4f32, 4f32]))
+            .id();
+        add_child(world, board, dust);
+
+        Self::decorate(world, board);
+        world.entity_mut(board).insert(Self);
+    }
+
+    //lights the scene with an ambient mood, any mood darker than daylight also gets a lantern shining across the board
+    pub fn set_mood(world: &mut World, ambient: AmbientLight) {
+        world.insert_resource(ambient);
+
+        let lanterns: Vec<Entity> = world.query_filtered::<Entity, With<Lantern>>().iter(world).collect();
+        if ambient.is_unlit() {
+            for lantern in lanterns {
+                despawn_recursive(world, lantern);
+            }
+            return;
+        }
+
+        let board = match world.query_filtered::<Entity, With<Board>>().iter(world).next() {
+            Some(board) if lanterns.is_empty() => board,
+            _ => return,
+        };
+        let light = PointLight::new(LANTERN_COLOR, 1.6f32, 12f32).with_height(0.8f32);
+        let lantern = world
+            .spawn()
+            .insert(SpotLight::new(light, std::f32::consts::PI, 0.8f32).with_softness(0.4f32))
+            .insert(Transform2d::from_translation([10f32, 4f32]))
+            .insert(Lantern)
+            .id();
+        add_child(world, board, lantern);
+    }
+
+    //a Tiled map dropped on the window takes over drawing the squares, like the board's own map it needs a dark property on its tiles
+    pub fn load_dropped_maps(mut commands: Commands, input: Res<Input>, boards: Query<Entity, With<Board>>) {
+        let board = match boards.iter().next() {
+            Some(board) => board,
+            None => return,
+        };
+
+        let maps = input.dropped_files().iter().filter(|path| path.extension().is_some_and(|extension| TILED_EXTENSIONS.iter().any(|tiled| extension == *tiled)));
+        for path in maps {
+            match load_tiled_map(&path.to_string_lossy(), [1f32, 1f32]) {
+                Ok(tilemap) => {
+                    commands.entity(board).insert(tilemap);
+                }
+                Err(error) => eprintln!("Couldn't load the board squares from {}: {}", path.display(), error),
+            }
+        }
+    }
+
+    //a frame around the board with a dashed inner border and the square coordinates, all sharp at any zoom
+    fn decorate(world: &mut World, board: Entity) {
+        let frame = world
+            .spawn()
+            .insert(
+                Path2d::rounded_rect([-0.25f32, -0.25f32], [8.5f32, 8.5f32], 0.2f32)
+                    .with_stroke(Stroke::new(FRAME_COLOR, 0.15f32).with_join(StrokeJoin::Round))
+                    .with_depth(1),
+            )
+            .insert(Transform2d::IDENTITY)
+            .id();
+        add_child(world, board, frame);
+
+        //a leaf over each corner of the frame, pointing away from the board
+        //the outline is bevelled so the sharp tips don't spike out
+        let corners = [([-0.25f32, -0.25f32], 2f32), ([8.25f32, -0.25f32], 3f32), ([8.25f32, 8.25f32], 0f32), ([-0.25f32, 8.25f32], 1f32)];
+        for (corner, quarter_turns) in corners {
+            let leaf = world
+                .spawn()
+                .insert(
+                    Path2d::new()
+                        .move_to([0f32, 0f32])
+                        .quadratic_to([0.5f32, 0f32], [0.4f32, 0.4f32])
+                        .cubic_to([0.15f32, 0.45f32], [-0.05f32, 0.3f32], [0f32, 0f32])
+                        .close()
+                        .with_fill(TRIM_COLOR)
+                        .with_stroke(Stroke::new(FRAME_COLOR, 0.03f32).with_join(StrokeJoin::Bevel)),
+                )
+                .insert(Transform2d::from_translation(corner).with_rotation(quarter_turns * std::f32::consts::FRAC_PI_2))
+                .id();
+            add_child(world, board, leaf);
+        }
+
+        //a dot beside the board in the color of the side to move, white starts
+        let turn = world
+            .spawn()
+            .insert(
+                Path2d::circle([8.75f32, 4f32], 0.18f32)
+                    .with_fill(turn_color(1))
+                    .with_stroke(Stroke::new(FRAME_COLOR, 0.03f32)),
+            )
+            .insert(Transform2d::IDENTITY)
+            .insert(TurnIndicator)
+            .id();
+        add_child(world, board, turn);
+
+        let border = world
+            .spawn()
+            .insert(
+                Path2d::new()
+                    .move_to([-0.08f32, -0.08f32])
+                    .line_to([8.08f32, -0.08f32])
+                    .line_to([8.08f32, 8.08f32])
+                    .line_to([-0.08f32, 8.08f32])
+                    .close()
+                    .with_stroke(
+                        Stroke::new(TRIM_COLOR, 0.03f32)
+                            .with_cap(StrokeCap::Round)
+                            .with_dashes(&[0.15f32, 0.1f32], 0f32),
+                    ),
+            )
+            .insert(Transform2d::IDENTITY)
+            .id();
+        add_child(world, board, border);
+
+        //files along the bottom and ranks up the left, as children of the board so they move with it
+        //each label is centered in a box as wide or as tall as its square
+        for (index, file) in "abcdefgh".chars().enumerate() {
+            let label = world
+                .spawn()
+                .insert(
+                    TextBox::world(&file.to_string(), (index as f32, -0.3f32), TRIM_COLOR, LABEL_SCALE)
+                        .with_bounds(1f32, LABEL_SCALE)
+                        .with_align(HorizontalAlign::Center, VerticalAlign::Top),
+                )
+                .insert(Transform2d::IDENTITY)
+                .id();
+            add_child(world, board, label);
+        }
+        for rank in 0..8 {
+            let label = world
+                .spawn()
+                .insert(
+                    TextBox::world(&(rank + 1).to_string(), (-0.5f32, rank as f32 + 1f32), TRIM_COLOR, LABEL_SCALE)
+                        .with_bounds(0.4f32, 1f32)
+                        .with_align(HorizontalAlign::Center, VerticalAlign::Center),
+                )
+                .insert(Transform2d::IDENTITY)
+                .id();
+            add_child(world, board, label);
+        }
+    }
+
+    //fades an additive overlay in over a square, it sits between the tile and its piece
+    pub fn highlight(world: &mut World, square: (usize, usize), color: [f32; 4]) -> Option<Entity> {
+        let tile = Self::tile(world, square)?;
+
+        let highlight = world
+            .spawn()
+            .insert(
+                Sprite::new([0f32, 0f32], [1f32, 1f32], color)
+                    .with_depth(HIGHLIGHT_DEPTH)
+                    .with_blend_mode(BlendMode::Additive)
+                    .with_opacity(0f32),
+            )
+            .insert(Fade::fade_in(Duration::from_millis(150)))
+            .insert(Highlight)
+            .id();
+        add_child(world, tile, highlight);
+
+        Some(highlight)
+    }
+
+    pub fn clear_highlights(world: &mut World) {
+        let highlights: Vec<Entity> = world.query_filtered::<Entity, With<Highlight>>().iter(world).collect();
+        for highlight in highlights {
+            despawn_recursive(world, highlight);
+        }
+    }
+
+    //the tile entity for a square, (0, 0) being the bottom left, found among the board's children
+    pub fn tile(world: &mut World, square: (usize, usize)) -> Option<Entity> {
+        let board = world.query_filtered::<Entity, With<Board>>().iter(world).next()?;
+        let mut tiles = world.query::<(Entity, &Square, &Parent)>();
+        tiles.iter(world).find(|(_, tile, Parent(parent))| tile.0 == square && *parent == board).map(|(tile, ..)| tile)
+    }
+
+    //the square under a point in world space, like one from Camera2d::screen_to_world, none when it's off the board
+    pub fn square_at(world: &mut World, world_point: (f32, f32)) -> Option<(usize, usize)> {
+        let mut boards = world.query_filtered::<Option<&GlobalTransform2d>, With<Board>>();
+        square_in(boards.iter(world).next()?, world_point)
+    }
+
+    //dragged pieces follow the cursor, and land on the square they're dropped on if it's empty or holds the other color's piece
+    //anywhere else, off the board or onto a piece of their own color, and they go back to where they were
+    //a piece's idle animation holds still while it's lifted, and once it lands it's the other color's move
+    //the view follows a piece taken near its edges, so pieces can be carried past what's on screen
+    #[allow(clippy::too_many_arguments)]
+    pub fn drag_pieces(
+        mut commands: Commands,
+        mut picking_events: EventReader<PickingEvent>,
+        tiles: Query<(Entity, &Square)>,
+        pieces: Query<&Piece>,
+        parents: Query<&Parent>,
+        transforms: Query<&GlobalTransform2d>,
+        mut animators: Query<&mut SpriteAnimator>,
+        mut turn_indicators: Query<&mut Path2d, With<TurnIndicator>>,
+        cameras: Query<(Entity, &Camera2d)>,
+    ) {
+        for event in picking_events.iter() {
+            match event {
+                PickingEvent::DragStart((entity, _)) => {
+                    if let Ok(mut animator) = animators.get_mut(*entity) {
+                        animator.pause();
+                    }
+                    for (camera_entity, camera) in cameras.iter().filter(|(_, camera)| camera.sees(None)) {
+                        let (width, height) = camera.visible_size();
+                        //a piece's transform is its bottom left corner, it's followed by its middle
+                        let follow = CameraFollow::new(*entity)
+                            .with_dead_zone(width * DRAG_FOLLOW_DEAD_ZONE, height * DRAG_FOLLOW_DEAD_ZONE)
+                            .with_offset((-0.5f32, -0.5f32))
+                            .with_damping(0.3f32);
+                        commands.entity(camera_entity).insert(follow);
+                    }
+                }
+                PickingEvent::Drag((entity, point)) => {
+                    let tile_transform = match parents.get(*entity).and_then(|Parent(tile)| transforms.get(*tile)) {
+                        Ok(tile_transform) if pieces.iter().any(|piece| piece.entity == *entity) => tile_transform,
+                        _ => continue,
+                    };
+                    //held by its middle
+                    if let Some(local) = tile_transform.inverse_transform_point([point.0, point.1]) {
+                        commands.entity(*entity).insert(Transform2d::from_translation([local[0] - 0.5f32, local[1] - 0.5f32]));
+                    }
+                }
+                PickingEvent::Drop((entity, _, target)) => {
+                    if let Ok(mut animator) = animators.get_mut(*entity) {
+                        animator.resume();
+                    }
+                    for (camera_entity, _) in cameras.iter() {
+                        commands.entity(camera_entity).remove::<CameraFollow>();
+                    }
+
+                    let from_tile = match tile_of(*entity, &tiles, &parents) {
+                        Some(tile) => tile,
+                        None => continue,
+                    };
+                    let piece = match pieces.get(from_tile) {
+                        Ok(piece) if piece.entity == *entity => piece,
+                        _ => continue,
+                    };
+                    commands.entity(*entity).insert(Transform2d::IDENTITY);
+
+                    //whatever it was dropped on, the tile itself or a piece on it
+                    let to_tile = match target.and_then(|target| tile_of(target, &tiles, &parents)) {
+                        Some(to_tile) => to_tile,
+                        None => continue,
+                    };
+                    if to_tile == from_tile {
+                        continue;
+                    }
+                    let captures = match pieces.get(to_tile) {
+                        //the sheet's rows are the two colors
+                        Ok(other) if other.cell.0 == piece.cell.0 => continue,
+                        Ok(_) => true,
+                        Err(_) => false,
+                    };
+
+                    //logged before anything moves, so it can tell a capture from a move
+                    if let (Ok((_, from)), Ok((_, to))) = (tiles.get(from_tile), tiles.get(to_tile)) {
+                        commands.add(LogMove { from: from.0, to: to.0 });
+                    }
+                    if captures {
+                        commands.add(Capture { tile: to_tile });
+                    }
+
+                    commands.entity(from_tile).remove::<Piece>();
+                    commands.entity(to_tile).insert(Piece { entity: piece.entity, cell: piece.cell });
+                    commands.add(AddChild { parent: to_tile, child: *entity });
+                    for mut turn_indicator in turn_indicators.iter_mut() {
+                        turn_indicator.set_fill(Some(turn_color(piece.cell.0 ^ 1)));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    //outlines the hovered square, the one under the cursor or under the piece being dragged, hovering a piece outlines its square
+    //a dragged piece also gets an arrow from the square it was lifted from
+    pub fn outline_hovered(
+        mut gizmos: ResMut<Gizmos>,
+        mut picking_events: EventReader<PickingEvent>,
+        mut hovered: Local<Option<Entity>>,
+        picking: Res<Picking>,
+        boards: Query<(Option<&GlobalTransform2d>, Option<&Tilemap>), With<Board>>,
+        tiles: Query<(Entity, &Square)>,
+        parents: Query<&Parent>,
+    ) {
+        for event in picking_events.iter() {
+            match event {
+                PickingEvent::HoverEnter(entity) => *hovered = Some(*entity),
+                PickingEvent::HoverExit(entity) if *hovered == Some(*entity) => *hovered = None,
+                _ => {}
+            }
+        }
+
+        let (board_transform, tilemap) = match boards.iter().next() {
+            Some(board) => board,
+            None => return,
+        };
+        let square_of = |entity: Entity| tile_of(entity, &tiles, &parents).and_then(|tile| tiles.get(tile).ok()).map(|(_, square)| square.0);
+        let square = match hovered.and_then(square_of) {
+            Some(square) => square,
+            None => return,
+        };
+
+        //the squares' tiles say which color they are, the outline has to stand out against it
+        let dark = tilemap
+            .and_then(|tilemap| {
+                let tile = tilemap.layer(SQUARES_LAYER)?.get_tile(square.0 as i32, square.1 as i32)?;
+                tilemap.tile_properties(tile)?.get("dark")
+            })
+            .is_some_and(|dark| *dark == PropertyValue::Bool(true));
+        let color = if dark { HOVER_COLOR } else { HOVER_COLOR_LIGHT_SQUARE };
+
+        let (x, y) = (square.0 as f32, square.1 as f32);
+        let identity = GlobalTransform2d::default();
+        let transform = board_transform.unwrap_or(&identity);
+        let corners = [[x, y], [x + 1f32, y], [x + 1f32, y + 1f32], [x, y + 1f32]].map(|corner| transform.transform_point(corner));
+        gizmos.polygon(&corners, ShapeStyle::Outlined(OUTLINE_THICKNESS), color);
+
+        let from = picking.dragged().and_then(square_of).filter(|from| *from != square);
+        if let Some(from) = from {
+            Self::draw_move(&mut gizmos, transform, from, square, color);
+        }
+    }
+
+    //the move in words in the current locale, like "Knight from b1 to c3", none if there is no piece on the from square
+    pub fn describe_move(world: &mut World, localization: &Localization, from: (usize, usize), to: (usize, usize)) -> Option<String> {
+        let from_tile = Self::tile(world, from)?;
+        let to_tile = Self::tile(world, to)?;
+        let piece = localization.text(piece_key(world.get::<Piece>(from_tile)?.cell));
+
+        let mut args = FluentArgs::new();
+        args.set("piece", piece);
+        args.set("from", square_name(from));
+        args.set("to", square_name(to));
+
+        let key = if world.get::<Piece>(to_tile).is_some() { "move-capture" } else { "move" };
+        Some(localization.format(key, Some(&args)))
+    }
+
+    //plays the capture animation on the piece on a tile while fading it out, the piece is removed once the animation finishes
+    //it can't be picked up again in the meantime, and another piece can take its place on the tile straight away
+    pub fn capture(world: &mut World, tile: Entity) {
+        let (piece, cell) = match world.get::<Piece>(tile) {
+            Some(piece) => (piece.entity, piece.cell),
+            None => return,
+        };
+
+        let mut piece = world.entity_mut(piece);
+        piece.remove::<Pickable>();
+        //two frames for each flash
+        let flashing: u64 = CAPTURE_FLASH_MILLIS.iter().sum();
+        piece.insert(Fade::fade_out(Duration::from_millis(2 * flashing)));
+        if let Some(mut animator) = piece.get_mut::<SpriteAnimator>() {
+            animator.play(&capture_clip(cell.0, cell.1));
         }
 
-        world.spawn().insert(Self { children });
+        //the sparks despawn themselves once they've burnt out
+        let sparks = world
+            .spawn()
+            .insert(ParticleEmitter::sparks())
+            .insert(Transform2d::from_translation([0.5f32, 0.5f32]))
+            .id();
+        add_child(world, tile, sparks);
+    }
+
+    //throws confetti up from the middle of the board, for checkmate, and pulls the view back to take in all of it
+    //the confetti is the winner's pieces, each one promoting its way from a pawn to a queen as it falls
+    pub fn celebrate(world: &mut World, winner_row: u32) {
+        let board = match world.query_filtered::<Entity, With<Board>>().iter(world).next() {
+            Some(board) => board,
+            None => return,
+        };
+
+        let promotions = [0, 2, 3, 1, 5].map(|col| (winner_row, col));
+        let confetti = world
+            .spawn()
+            .insert(ParticleEmitter::confetti().with_tile_frames(PIECE_SHEET, 2, 6, &promotions, FrameMode::OverLifetime))
+            .insert(Transform2d::from_translation([4f32, 4f32]))
+            .id();
+        add_child(world, board, confetti);
+
+        let cameras: Vec<Entity> = world.query::<(Entity, &Camera2d)>().iter(world).filter(|(_, camera)| camera.sees(None)).map(|(entity, _)| entity).collect();
+        for camera in cameras {
+            world.entity_mut(camera).insert(CameraTransition::zoom_to(CELEBRATION_ZOOM, Duration::from_millis(800)));
+        }
     }
+
+    //an arrow between the centers of two squares, for showing moves
+    pub fn draw_move(gizmos: &mut Gizmos, board: &GlobalTransform2d, from: (usize, usize), to: (usize, usize), color: [f32; 4]) {
+        let center = |square: (usize, usize)| board.transform_point([square.0 as f32 + 0.5f32, square.1 as f32 + 0.5f32]);
+        gizmos.arrow(center(from), center(to), MOVE_ARROW_THICKNESS, color);
+    }
+
+    //outlines every highlighted square, so highlights stay readable on either tile color
+    pub fn outline_highlights(
+        mut gizmos: ResMut<Gizmos>,
+        highlights: Query<(&Sprite, &GlobalTransform2d), With<Highlight>>,
+    ) {
+        for (sprite, transform) in highlights.iter() {
+            let [x0, y0] = sprite.position;
+            let (x1, y1) = (x0 + sprite.dimensions[0], y0 + sprite.dimensions[1]);
+            let corners = [[x0, y0], [x1, y0], [x1, y1], [x0, y1]].map(|corner| transform.transform_point(corner));
+
+            let color = [sprite.color[0], sprite.color[1], sprite.color[2], sprite.opacity];
+            gizmos.polygon(&corners, ShapeStyle::Outlined(OUTLINE_THICKNESS), color);
+        }
+    }
+
+    pub fn finish_captures(
+        mut commands: Commands,
+        mut finished: EventReader<AnimationFinished>,
+        parents: Query<&Parent>,
+        pieces: Query<&Piece>,
+        mut shakes: Query<&mut CameraShake>,
+    ) {
+        for event in finished.iter() {
+            if !event.clip.starts_with(CAPTURE_CLIP) {
+                continue;
+            }
+
+            //the tile may already belong to the piece that took this one
+            if let Ok(Parent(tile)) = parents.get(event.entity) {
+                if pieces.get(*tile).is_ok_and(|piece| piece.entity == event.entity) {
+                    commands.entity(*tile).remove::<Piece>();
+                }
+            }
+            commands.add(DespawnRecursive { entity: event.entity });
+
+            for mut shake in shakes.iter_mut() {
+                shake.add_trauma(CAPTURE_TRAUMA);
+            }
+        }
+    }
+
+    //the log's last line sits a margin above the bottom of the window, however many moves it holds and whatever the window's size
+    //a panel sprite on the log is fitted around the text, in the world of the camera that draws it
+    pub fn keep_log_at_bottom(
+        render_context: Res<RenderContext>,
+        font_registry: Res<FontRegistry>,
+        cameras: Query<&Camera2d>,
+        mut logs: MoveLogQuery,
+    ) {
+        for (mut text_box, panel, layers) in logs.iter_mut() {
+            let (width, height) = text_box.measure(&font_registry);
+            let y = render_context.config.height as f32 - height - MOVE_LOG_MARGIN;
+            if text_box.position.1 != y {
+                text_box.position.1 = y;
+            }
+
+            let (mut panel, camera) = match (panel, cameras.iter().find(|camera| camera.sees(layers))) {
+                (Some(panel), Some(camera)) => (panel, camera),
+                _ => continue,
+            };
+            let x = text_box.position.0;
+            let bottom_left = camera.screen_to_world((x - MOVE_LOG_PADDING, y + height + MOVE_LOG_PADDING));
+            let top_right = camera.screen_to_world((x + width + MOVE_LOG_PADDING, y - MOVE_LOG_PADDING));
+            let position = [bottom_left.0, bottom_left.1];
+            let dimensions = [top_right.0 - bottom_left.0, top_right.1 - bottom_left.1];
+            if panel.position != position || panel.dimensions != dimensions {
+                panel.position = position;
+                panel.dimensions = dimensions;
+            }
+        }
+    }
+
+    fn register_piece_clips(world: &mut World, row: u32, col: u32) {
+        let mut clips = world.get_resource_mut::<AnimationClips>().expect("Animation has not been initialized");
+
+        //the sheet only has a single frame per piece for now, so idle holds it and capture flashes between colors
+        //idle ping pongs so it sways back and forth once the sheet has more frames
+        clips.insert(&idle_clip(row, col), AnimationClip::uniform(&[(row, col)], Duration::from_secs(1), AnimationMode::PingPong));
+
+        //the other color is the other row of the sheet, flashing faster and faster before the piece goes
+        let other_row = row ^ 1;
+        let capture = CAPTURE_FLASH_MILLIS.iter().fold(AnimationClip::new(AnimationMode::Once), |clip, millis| {
+            clip.with_frame(row, col, Duration::from_millis(*millis)).with_frame(other_row, col, Duration::from_millis(*millis))
+        });
+        clips.insert(&capture_clip(row, col), capture);
+    }
+}
+
+//Board::capture as a command, for systems that can only queue it, taking a king wins the game
+struct Capture {
+    tile: Entity,
+}
+
+impl Command for Capture {
+    fn write(self, world: &mut World) {
+        let king = world.get::<Piece>(self.tile).map(|piece| piece.cell).filter(|cell| piece_key(*cell) == "piece-king");
+        Board::capture(world, self.tile);
+        if let Some((row, _)) = king {
+            Board::celebrate(world, row ^ 1);
+        }
+
+        for (mut counter, mut text) in world.query::<(&mut CaptureCounter, &mut LocalizedText)>().iter_mut(world) {
+            counter.0 += 1;
+            text.set_arg("count", counter.0);
+        }
+    }
+}
+
+//adds a move to the log, before it's made so the pieces are still where they started
+struct LogMove {
+    from: (usize, usize),
+    to: (usize, usize),
+}
+
+impl Command for LogMove {
+    fn write(self, world: &mut World) {
+        let cell = match Board::tile(world, self.from).and_then(|tile| world.get::<Piece>(tile)) {
+            Some(piece) => piece.cell,
+            None => return,
+        };
+        let captures = Board::tile(world, self.to).is_some_and(|tile| world.get::<Piece>(tile).is_some());
+        let description = match world.resource_scope(|world, localization: Mut<Localization>| Board::describe_move(world, &localization, self.from, self.to)) {
+            Some(description) => description,
+            None => return,
+        };
+
+        let figurine = TextSpan::new(&format!("\n{} ", figurine(cell))).with_font_name(FIGURINE_FONT).with_scale(MOVE_LOG_FIGURINE_SCALE);
+        let mut description = TextSpan::new(&description);
+        if captures {
+            description = description.with_color(MOVE_LOG_CAPTURE_COLOR);
+        }
+
+        for mut text_box in world.query_filtered::<&mut TextBox, With<MoveLog>>().iter_mut(world) {
+            text_box.spans.extend([figurine.clone(), description.clone()]);
+            let overflow = text_box.spans.len().saturating_sub(2 * MOVE_LOG_LENGTH);
+            text_box.spans.drain(..overflow);
+        }
+    }
+}
+
+//two rows of six pieces, see piece_key
+const PIECE_SHEET: &str = "chess_piece_bitmap.png";
+const DUST_TEXTURE: &str = "soft_dot.png";
+
+//a Tiled map of the squares, (0, 0) is a1, each tile has a dark property for the color of its square
+const BOARD_MAP: &str = "maps/board.tmj";
+const SQUARES_LAYER: &str = "squares";
+//the files load_tiled_map reads, Tiled json and xml
+const TILED_EXTENSIONS: [&str; 3] = ["tmj", "json", "tmx"];
+
+//higher depths are drawn first
+const HIGHLIGHT_DEPTH: u32 = 1;
+
+const FRAME_COLOR: [f32; 4] = [0.3f32, 0.16f32, 0.05f32, 1f32];
+//the inner border, the corner leaves and the square coordinates
+const TRIM_COLOR: [f32; 4] = [0.85f32, 0.7f32, 0.4f32, 1f32];
+
+//in world units, where a square is one unit across
+const OUTLINE_THICKNESS: f32 = 0.05;
+const MOVE_ARROW_THICKNESS: f32 = 0.08;
+const LABEL_SCALE: f32 = 0.2;
+
+const HOVER_COLOR: [f32; 4] = [1f32, 0.9f32, 0.3f32, 0.8f32];
+//yellow hardly shows up on white
+const HOVER_COLOR_LIGHT_SQUARE: [f32; 4] = [0.9f32, 0.45f32, 0f32, 0.8f32];
+
+//the lighting moods by their message key, see Board::set_mood
+pub const MOODS: [(&str, AmbientLight); 3] = [
+    ("mood-day", AmbientLight::DAY),
+    ("mood-dusk", AmbientLight::DUSK),
+    ("mood-night", AmbientLight::NIGHT),
+];
+const LANTERN_COLOR: [f32; 3] = [1f32, 0.75f32, 0.45f32];
+
+//how many moves the log keeps, and how it's laid out in screen pixels
+const MOVE_LOG_LENGTH: usize = 6;
+const MOVE_LOG_MARGIN: f32 = 30f32;
+//between the text and the edges of its panel
+const MOVE_LOG_PADDING: f32 = 20f32;
+//the symbols stand a little taller than the words next to them
+const MOVE_LOG_FIGURINE_SCALE: f32 = 26f32;
+const MOVE_LOG_CAPTURE_COLOR: [f32; 4] = [0.6f32, 0.05f32, 0.05f32, 1f32];
+
+//the fraction of the view's width and height a dragged piece can go from the middle before the view follows it
+const DRAG_FOLLOW_DEAD_ZONE: f32 = 0.35;
+
+//the camera scale the view pulls back to for the confetti, see Board::celebrate
+const CELEBRATION_ZOOM: f32 = 25.0;
+
+//how hard a capture knocks the camera, see CameraShake
+const CAPTURE_TRAUMA: f32 = 0.5;
+
+//how long each of the capture clip's flashes shows each color
+const CAPTURE_FLASH_MILLIS: [u64; 5] = [120, 100, 80, 60, 40];
+
+const IDLE_CLIP: &str = "piece_idle";
+const CAPTURE_CLIP: &str = "piece_capture";
+
+//the tile a tile or a piece on it belongs to
+fn tile_of(entity: Entity, tiles: &Query<(Entity, &Square)>, parents: &Query<&Parent>) -> Option<Entity> {
+    if tiles.contains(entity) {
+        return Some(entity);
+    }
+    parents.get(entity).ok().map(|Parent(parent)| *parent).filter(|parent| tiles.contains(*parent))
+}
+
+//the square a world point is over, given the board's transform
+fn square_in(board_transform: Option<&GlobalTransform2d>, world_point: (f32, f32)) -> Option<(usize, usize)> {
+    let board_point = match board_transform {
+        Some(transform) => transform.inverse_transform_point([world_point.0, world_point.1])?,
+        None => [world_point.0, world_point.1],
+    };
+
+    let square = (board_point[0].floor(), board_point[1].floor());
+    if (0f32..8f32).contains(&square.0) && (0f32..8f32).contains(&square.1) {
+        Some((square.0 as usize, square.1 as usize))
+    } else {
+        None
+    }
+}
+
+//the message key for the piece in a cell of the piece sheet, columns go pawn, rook, knight, bishop, king, queen
+fn piece_key(cell: (u32, u32)) -> &'static str {
+    match cell.1 {
+        0 => "piece-pawn",
+        1 => "piece-rook",
+        2 => "piece-knight",
+        3 => "piece-bishop",
+        4 => "piece-king",
+        _ => "piece-queen",
+    }
+}
+
+//the chess symbol for the piece in a cell of the piece sheet, the bottom row is white
+fn figurine(cell: (u32, u32)) -> char {
+    let symbols = if cell.0 == 1 { ['♙', '♖', '♘', '♗', '♔', '♕'] } else { ['♟', '♜', '♞', '♝', '♚', '♛'] };
+    symbols[(cell.1 as usize).min(5)]
+}
+
+//the color of the side playing a row of the piece sheet, for the turn indicator
+fn turn_color(row: u32) -> [f32; 4] {
+    if row == 1 {
+        [0.95f32, 0.93f32, 0.88f32, 1f32]
+    } else {
+        [0.12f32, 0.1f32, 0.1f32, 1f32]
+    }
+}
+
+//a square in algebraic notation, (0, 0) is a1
+fn square_name(square: (usize, usize)) -> String {
+    format!("{}{}", (b'a' + square.0 as u8) as char, square.1 + 1)
+}
+
+fn idle_clip(row: u32, col: u32) -> String {
+    format!("{}_{}_{}", IDLE_CLIP, row, col)
+}
+
+fn capture_clip(row: u32, col: u32) -> String {
+    format!("{}_{}_{}", CAPTURE_CLIP, row, col)
 }
@@ -0,0 +1,300 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use super::{Gamepads, Input};
+
+//a key, with whichever of control, shift and alt have to be held along with it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub key: VirtualKeyCode,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub ctrl: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub shift: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    pub fn new(key: VirtualKeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn with_ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    //extra modifiers don't get in the way, so shift+w still pans
+    fn pressed(&self, input: &Input) -> bool {
+        self.modifiers_held(input) && input.keys().pressed(self.key)
+    }
+
+    fn just_pressed(&self, input: &Input) -> bool {
+        self.modifiers_held(input) && input.keys().just_pressed(self.key)
+    }
+
+    fn modifiers_held(&self, input: &Input) -> bool {
+        let modifiers = input.modifiers();
+        (!self.ctrl || modifiers.ctrl()) && (!self.shift || modifiers.shift()) && (!self.alt || modifiers.alt())
+    }
+}
+
+//which way a stick or trigger has to be pushed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+//one way of triggering an action, written to the config file as {"Key": {"key": "Escape"}}, {"Mouse": "Left"} and so on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyBinding),
+    Mouse(MouseButton),
+    //on any connected gamepad
+    Gamepad(gilrs::Button),
+    //a stick or trigger pushed past Gamepads::AXIS_THRESHOLD
+    GamepadAxis((gilrs::Axis, AxisDirection)),
+}
+
+impl Binding {
+    pub fn key(key: VirtualKeyCode) -> Self {
+        Binding::Key(KeyBinding::new(key))
+    }
+
+    fn pressed(&self, input: &Input, gamepads: Option<&Gamepads>) -> bool {
+        match self {
+            Binding::Key(key) => key.pressed(input),
+            Binding::Mouse(button) => input.mouse_buttons().pressed(*button),
+            Binding::Gamepad(button) => gamepads.is_some_and(|gamepads| gamepads.buttons().pressed(*button)),
+            Binding::GamepadAxis(axis) => gamepads.is_some_and(|gamepads| gamepads.axes().pressed(*axis)),
+        }
+    }
+
+    fn just_pressed(&self, input: &Input, gamepads: Option<&Gamepads>) -> bool {
+        match self {
+            Binding::Key(key) => key.just_pressed(input),
+            Binding::Mouse(button) => input.mouse_buttons().just_pressed(*button),
+            Binding::Gamepad(button) => gamepads.is_some_and(|gamepads| gamepads.buttons().just_pressed(*button)),
+            Binding::GamepadAxis(axis) => gamepads.is_some_and(|gamepads| gamepads.axes().just_pressed(*axis)),
+        }
+    }
+
+    //the first thing pressed this frame, for listening for a new binding
+    //modifiers on their own aren't bindings, they're picked up along with the key they're held with
+    pub fn just_pressed_any(input: &Input, gamepads: Option<&Gamepads>) -> Option<Binding> {
+        let modifiers = input.modifiers();
+        let key = input.keys().iter_just_pressed().find(|key| !is_modifier(**key)).map(|key| {
+            Binding::Key(KeyBinding {
+                key: *key,
+                ctrl: modifiers.ctrl(),
+                shift: modifiers.shift(),
+                alt: modifiers.alt(),
+            })
+        });
+
+        key.or_else(|| input.mouse_buttons().iter_just_pressed().next().map(|button| Binding::Mouse(*button)))
+            .or_else(|| gamepads.and_then(|gamepads| gamepads.buttons().iter_just_pressed().next()).map(|button| Binding::Gamepad(*button)))
+            .or_else(|| gamepads.and_then(|gamepads| gamepads.axes().iter_just_pressed().next()).map(|axis| Binding::GamepadAxis(*axis)))
+    }
+}
+
+//how bindings are shown to the player, like Ctrl+Z or Mouse Right
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Key(key) => {
+                if key.ctrl {
+                    write!(f, "Ctrl+")?;
+                }
+                if key.shift {
+                    write!(f, "Shift+")?;
+                }
+                if key.alt {
+                    write!(f, "Alt+")?;
+                }
+                write!(f, "{:?}", key.key)
+            }
+            Binding::Mouse(MouseButton::Other(button)) => write!(f, "Mouse {}", button),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            Binding::Gamepad(button) => write!(f, "Gamepad {:?}", button),
+            Binding::GamepadAxis((axis, AxisDirection::Positive)) => write!(f, "Gamepad {:?}+", axis),
+            Binding::GamepadAxis((axis, AxisDirection::Negative)) => write!(f, "Gamepad {:?}-", axis),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InputMapError {
+    Io(PathBuf, io::Error),
+    Json(PathBuf, serde_json::Error),
+}
+
+impl fmt::Display for InputMapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputMapError::Io(path, error) => write!(f, "couldn't access {}: {}", path.display(), error),
+            InputMapError::Json(path, error) => write!(f, "{} isn't a valid input map: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for InputMapError {}
+
+//the bindings of every named action, an action is triggered by any one of its bindings
+//read from and saved to a json file of action names to lists of bindings
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InputMap {
+    //sorted so the actions are listed in the same order every time
+    bindings: BTreeMap<String, Vec<Binding>>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self { bindings: BTreeMap::new() }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, InputMapError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|error| InputMapError::Io(path.to_path_buf(), error))?;
+        serde_json::from_str(&source).map_err(|error| InputMapError::Json(path.to_path_buf(), error))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), InputMapError> {
+        let path = path.as_ref();
+        let source = serde_json::to_string_pretty(self).map_err(|error| InputMapError::Json(path.to_path_buf(), error))?;
+        std::fs::write(path, source).map_err(|error| InputMapError::Io(path.to_path_buf(), error))
+    }
+
+    //actions this map doesn't mention get their bindings from the defaults
+    //so a saved map still picks up actions added after it was written
+    pub fn with_defaults(mut self, defaults: &InputMap) -> Self {
+        for (action, bindings) in defaults.bindings.iter() {
+            self.bindings.entry(action.clone()).or_insert_with(|| bindings.clone());
+        }
+        self
+    }
+
+    pub fn with_binding(mut self, action: &str, binding: Binding) -> Self {
+        self.bind(action, binding);
+        self
+    }
+
+    //binding the same thing twice does nothing
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        let bindings = self.bindings.entry(String::from(action)).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    //the action stays in the map with no bindings, so it can still be rebound from the ui
+    pub fn unbind(&mut self, action: &str, binding: Binding) {
+        if let Some(bindings) = self.bindings.get_mut(action) {
+            bindings.retain(|bound| *bound != binding);
+        }
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.bindings.get(action).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.bindings.keys().map(|action| action.as_str())
+    }
+}
+
+//which actions are down, and which went down this frame
+//systems ask this instead of looking at keys and buttons, so the player can rebind them
+#[derive(Default)]
+pub struct ActionState {
+    pressed: HashSet<String>,
+    just_pressed: HashSet<String>,
+
+    //while suspended every action reads as up, see ActionState::suspend
+    suspended: bool,
+    //what was held last frame, kept up to date while suspended too
+    held: HashSet<String>,
+}
+
+impl ActionState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pressed(&self, action: &str) -> bool {
+        self.pressed.contains(action)
+    }
+
+    //a press and release within the same frame counts as both
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.just_pressed.contains(action)
+    }
+
+    //stops actions from firing, like while the ui is listening for a new binding
+    //anything still held when it's lifted has to be let go and pressed again before it counts
+    pub fn suspend(&mut self, suspended: bool) {
+        self.suspended = suspended;
+    }
+
+    //runs after the EventSystem and Gamepads have started the frame, before anything reads actions
+    pub fn update(mut actions: ResMut<ActionState>, input_map: Res<InputMap>, input: Res<Input>, gamepads: Option<NonSend<Gamepads>>) {
+        let gamepads = gamepads.as_deref();
+        let actions = &mut *actions;
+        actions.pressed.clear();
+        actions.just_pressed.clear();
+
+        let mut held = HashSet::new();
+        for (action, bindings) in input_map.bindings.iter() {
+            let pressed = bindings.iter().any(|binding| binding.pressed(&input, gamepads));
+            let tapped = bindings.iter().any(|binding| binding.just_pressed(&input, gamepads));
+            let was_held = actions.held.contains(action);
+            if pressed {
+                held.insert(action.clone());
+            }
+            if actions.suspended {
+                continue;
+            }
+
+            if pressed {
+                actions.pressed.insert(action.clone());
+            }
+            if !was_held && (pressed || tapped) {
+                actions.just_pressed.insert(action.clone());
+            }
+        }
+        actions.held = held;
+    }
+}
+
+fn is_modifier(key: VirtualKeyCode) -> bool {
+    matches!(
+        key,
+        VirtualKeyCode::LControl
+            | VirtualKeyCode::RControl
+            | VirtualKeyCode::LShift
+            | VirtualKeyCode::RShift
+            | VirtualKeyCode::LAlt
+            | VirtualKeyCode::RAlt
+            | VirtualKeyCode::LWin
+            | VirtualKeyCode::RWin
+    )
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
@@ -1,18 +1,50 @@
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
 use bevy_ecs::prelude::*;
-use winit::event::{VirtualKeyCode, WindowEvent, ElementState, MouseButton};
+use serde::{Deserialize, Serialize};
+use winit::event::{VirtualKeyCode, WindowEvent, ElementState, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase};
+
+use super::{Input, InputFocus, InputOwner, Recorder, Replay};
 
-#[derive(Clone)]
+//trackpads scroll in pixels and wheels in lines, pixel scrolls are turned into lines at this rate
+const PIXELS_PER_LINE: f64 = 20f64;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Event {
     KeyPressed(VirtualKeyCode),
     KeyReleased(VirtualKeyCode),
     MousePressed((MouseButton, (f64, f64))),
     MouseReleased((MouseButton, (f64, f64))),
-    CursorMoved((f64, f64))
+    CursorMoved((f64, f64)),
+    //how far was scrolled in lines, positive is up and right, and where the cursor was
+    Scroll(((f64, f64), (f64, f64))),
+    //how much two fingers spread apart, 2 is twice as far apart, and the point between them
+    Pinch((f64, (f64, f64))),
+    //shift, control, alt and logo, sent whenever one of them goes up or down
+    ModifiersChanged(ModifiersState),
+    //typed text, with the keyboard layout and dead keys already applied
+    //composed input from an input method arrives here once it's committed, winit 0.26 doesn't report the composition itself
+    //control characters like backspace and enter are left to the key events
+    Character(char),
+    //the touch id, what the finger did and where it is, ids are reused once a finger is lifted
+    Touch((u64, TouchPhase, (f64, f64))),
+    FileHovered(PathBuf),
+    FileHoverCancelled,
+    FileDropped(PathBuf),
+    //whether the window has keyboard focus, keys and buttons held when it's lost are released in Input
+    Focused(bool),
 } 
 
 pub struct EventSystem {
     //private storage of shit
     mouse_inside: bool,
+    //where each finger on a touch screen is, by touch id
+    touches: HashMap<u64, (f64, f64)>,
+
+    //every event sent is written to this, see Recorder
+    recorder: Option<Recorder>,
+    //while this is set the window's input is ignored and the recorded events are sent instead
+    replay: Option<Replay>,
 
     pub cursor_moved: bool,
     pub mouse_pos: (f64, f64)
@@ -22,6 +54,9 @@ impl EventSystem {
     pub fn new() -> Self {
         Self {
             mouse_inside: true,
+            touches: HashMap::new(),
+            recorder: None,
+            replay: None,
             cursor_moved: false,
             mouse_pos: (0f64, 0f64)
         }
@@ -29,9 +64,32 @@ impl EventSystem {
 
     pub fn init(&self, world: &mut World) {
         world.insert_resource(Events::<Event>::default());
+        world.insert_resource(Input::new());
+        world.insert_resource(InputFocus::new());
+    }
+
+    pub fn start_recording(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    pub fn start_replay(&mut self, replay: Replay) {
+        self.replay = Some(replay);
+    }
+
+    //false again once the replay has run out, and the window's input is back in charge
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
     }
 
     pub fn on_event(&mut self, world: &mut World, event: &WindowEvent) {
+        //the cursor is still followed, so it's in the right place once the replay is over
+        if self.is_replaying() {
+            if let WindowEvent::CursorMoved { position, .. } = event {
+                self.mouse_pos = (position.x, position.y);
+            }
+            return;
+        }
+
         //construct my event object here
         let event = match event {
             //WindowEvent::Resized(_) => todo!(),
@@ -49,7 +107,12 @@ impl EventSystem {
                     None
                 }
             },
-            //WindowEvent::ModifiersChanged(_) => todo!(),
+            WindowEvent::ModifiersChanged(modifiers) => Some(Event::ModifiersChanged(*modifiers)),
+            WindowEvent::ReceivedCharacter(character) if !character.is_control() => Some(Event::Character(*character)),
+            WindowEvent::HoveredFile(path) => Some(Event::FileHovered(path.clone())),
+            WindowEvent::HoveredFileCancelled => Some(Event::FileHoverCancelled),
+            WindowEvent::DroppedFile(path) => Some(Event::FileDropped(path.clone())),
+            WindowEvent::Focused(focused) => Some(Event::Focused(*focused)),
             WindowEvent::CursorMoved { position, .. } => {
                 //update our internal mouse position and don't emit an event
                 self.mouse_pos = (position.x, position.y);
@@ -64,7 +127,28 @@ impl EventSystem {
                 self.mouse_inside = false;
                 None
             },
-            //WindowEvent::MouseWheel { device_id, delta, phase, modifiers } => todo!(),
+            WindowEvent::MouseWheel { delta, .. } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => (*x as f64, *y as f64),
+                    MouseScrollDelta::PixelDelta(position) => (position.x / PIXELS_PER_LINE, position.y / PIXELS_PER_LINE),
+                };
+                Some(Event::Scroll((lines, self.mouse_pos)))
+            },
+            WindowEvent::Touch(touch) => {
+                let position = (touch.location.x, touch.location.y);
+                self.send_unclaimed(world, Event::Touch((touch.id, touch.phase, position)));
+                match touch.phase {
+                    TouchPhase::Started => {
+                        self.touches.insert(touch.id, position);
+                        None
+                    }
+                    TouchPhase::Moved => self.move_touch(touch.id, position),
+                    TouchPhase::Ended | TouchPhase::Cancelled => {
+                        self.touches.remove(&touch.id);
+                        None
+                    }
+                }
+            },
             WindowEvent::MouseInput { state, button, .. } => {
                 match state {
                     ElementState::Pressed => {
@@ -79,20 +163,173 @@ impl EventSystem {
         };
 
         if let Some(event) = event {
-            let mut events = world.get_resource_mut::<Events<Event>>().expect("No events in world? has event system been initialized");
-            events.send(event);
+            self.send_unclaimed(world, event);
+        }
+    }
+
+    fn send_unclaimed(&mut self, world: &mut World, event: Event) {
+        let focus = world.get_resource::<InputFocus>().expect("No input focus in world? has event system been initialized");
+        if !Self::claimed_by_ui(focus, &event) {
+            self.send(world, event);
         }
     }
 
-    pub fn update(&mut self, world: &mut World) {
+    //what the ui holds back from the game while it has focus, releases and lifted fingers always get through
+    fn claimed_by_ui(focus: &InputFocus, event: &Event) -> bool {
+        match event {
+            Event::MousePressed(_) | Event::Scroll(_) | Event::Pinch(_) => focus.mouse() == InputOwner::Ui,
+            Event::Touch((_, TouchPhase::Started | TouchPhase::Moved, _)) => focus.mouse() == InputOwner::Ui,
+            Event::KeyPressed(_) | Event::Character(_) => focus.keyboard() == InputOwner::Ui,
+            _ => false,
+        }
+    }
+
+    //everything goes out through here, so Input and recordings see exactly what systems do, live or replayed
+    fn send(&mut self, world: &mut World, event: Event) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(&event);
+        }
+
+        let mut input = world.get_resource_mut::<Input>().expect("No input in world? has event system been initialized");
+        Self::update_input(&mut input, &event);
+
+        let mut events = world.get_resource_mut::<Events<Event>>().expect("No events in world? has event system been initialized");
+        events.send(event);
+    }
+
+    fn update_input(input: &mut Input, event: &Event) {
+        match event {
+            Event::KeyPressed(key) => input.press_key(*key),
+            Event::KeyReleased(key) => input.release_key(*key),
+            Event::MousePressed((button, position)) => {
+                input.move_cursor(*position);
+                input.press_mouse_button(*button);
+            }
+            Event::MouseReleased((button, position)) => {
+                input.move_cursor(*position);
+                input.release_mouse_button(*button);
+            }
+            Event::CursorMoved(position) => input.move_cursor(*position),
+            Event::ModifiersChanged(modifiers) => input.set_modifiers(*modifiers),
+            Event::Character(character) => input.type_character(*character),
+            Event::Touch((id, phase, position)) => {
+                let position = match phase {
+                    TouchPhase::Started | TouchPhase::Moved => Some(*position),
+                    TouchPhase::Ended | TouchPhase::Cancelled => None,
+                };
+                input.set_touch(*id, position);
+            }
+            Event::FileDropped(path) => input.drop_file(path.clone()),
+            Event::Focused(false) => input.lose_focus(),
+            _ => {}
+        }
+    }
+
+    //two fingers moving apart or together is a pinch
+    fn move_touch(&mut self, id: u64, position: (f64, f64)) -> Option<Event> {
+        let other = match self.touches.iter().find(|(other_id, _)| **other_id != id) {
+            Some((_, other)) if self.touches.len() == 2 => *other,
+            _ => {
+                self.touches.insert(id, position);
+                return None;
+            }
+        };
+        let previous = self.touches.insert(id, position)?;
+
+        let distance = |a: (f64, f64), b: (f64, f64)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+        let previous_distance = distance(previous, other);
+        if previous_distance <= f64::EPSILON {
+            return None;
+        }
+
+        let center = ((position.0 + other.0) / 2f64, (position.1 + other.1) / 2f64);
+        Some(Event::Pinch((distance(position, other) / previous_distance, center)))
+    }
+
+    //starts a frame that took delta, and gives back the frame time to use
+    //a replay sends the frame's recorded events and swaps in its recorded frame time
+    pub fn update(&mut self, world: &mut World, delta: Duration) -> Duration {
+        let mut delta = delta;
+        if let Some(replay) = self.replay.as_mut() {
+            match replay.next_frame() {
+                Some((events, recorded_delta)) => {
+                    delta = recorded_delta;
+                    for event in events {
+                        self.send(world, event);
+                    }
+                }
+                None => {
+                    self.replay = None;
+                    //the window's cursor is wherever it is now, not where the replay left it
+                    self.cursor_moved = true;
+                }
+            }
+        }
+
         //aggregate cursor moved events here
-        if self.cursor_moved {
-            let mut events = world.get_resource_mut::<Events<Event>>().expect("No events in world? has event system been initialized");
-            events.send(Event::CursorMoved(self.mouse_pos));
+        if self.cursor_moved && !self.is_replaying() {
+            self.send(world, Event::CursorMoved(self.mouse_pos));
             self.cursor_moved = false;
         }
 
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(error) = recorder.finish_frame(delta) {
+                eprintln!("Stopped recording: {}", error);
+                self.recorder = None;
+            }
+        }
+
         let mut events = world.get_resource_mut::<Events<Event>>().expect("No events in world?, has event system been initialized");
         events.update();
+
+        //what came in since the last frame becomes this frame's just pressed and released
+        let mut input = world.get_resource_mut::<Input>().expect("No input in world? has event system been initialized");
+        input.start_frame();
+
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn focus(mouse: InputOwner, keyboard: InputOwner) -> InputFocus {
+        let mut focus = InputFocus::new();
+        focus.set_mouse(mouse);
+        focus.set_keyboard(keyboard);
+        focus
+    }
+
+    #[test]
+    fn the_ui_holds_back_presses_but_not_releases() {
+        let focus = focus(InputOwner::Ui, InputOwner::Game);
+
+        assert!(EventSystem::claimed_by_ui(&focus, &Event::MousePressed((MouseButton::Left, (0f64, 0f64)))));
+        assert!(EventSystem::claimed_by_ui(&focus, &Event::Scroll(((0f64, 1f64), (0f64, 0f64)))));
+        assert!(!EventSystem::claimed_by_ui(&focus, &Event::MouseReleased((MouseButton::Left, (0f64, 0f64)))));
+        //the keyboard is still the game's
+        assert!(!EventSystem::claimed_by_ui(&focus, &Event::KeyPressed(VirtualKeyCode::A)));
+
+        let focus = InputFocus::new();
+        assert!(!EventSystem::claimed_by_ui(&focus, &Event::MousePressed((MouseButton::Left, (0f64, 0f64)))));
+    }
+
+    #[test]
+    fn the_ui_holds_back_touches_and_pinches_over_it() {
+        let ui = focus(InputOwner::Ui, InputOwner::Game);
+        let game = InputFocus::new();
+        let touch = |phase: TouchPhase| Event::Touch((0, phase, (10f64, 10f64)));
+        let pinch = Event::Pinch((1.5f64, (10f64, 10f64)));
+
+        assert!(EventSystem::claimed_by_ui(&ui, &touch(TouchPhase::Started)));
+        assert!(EventSystem::claimed_by_ui(&ui, &touch(TouchPhase::Moved)));
+        assert!(EventSystem::claimed_by_ui(&ui, &pinch));
+        //lifted fingers get through so the game doesn't think they're still down
+        assert!(!EventSystem::claimed_by_ui(&ui, &touch(TouchPhase::Ended)));
+        assert!(!EventSystem::claimed_by_ui(&ui, &touch(TouchPhase::Cancelled)));
+
+        assert!(!EventSystem::claimed_by_ui(&game, &touch(TouchPhase::Started)));
+        assert!(!EventSystem::claimed_by_ui(&game, &pinch));
     }
 }
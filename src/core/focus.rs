@@ -0,0 +1,45 @@
+//who the mouse or keyboard is going to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputOwner {
+    Game,
+    Ui,
+}
+
+//whether the ui or the game owns the mouse and keyboard, the ui sets this once it's drawn each frame
+//while the ui owns one, the EventSystem holds back its presses, touches, scrolling, pinches and typing from the game
+//releases still go through so nothing is left held down
+pub struct InputFocus {
+    mouse: InputOwner,
+    keyboard: InputOwner,
+}
+
+impl InputFocus {
+    pub fn new() -> Self {
+        Self {
+            mouse: InputOwner::Game,
+            keyboard: InputOwner::Game,
+        }
+    }
+
+    pub fn mouse(&self) -> InputOwner {
+        self.mouse
+    }
+
+    pub fn keyboard(&self) -> InputOwner {
+        self.keyboard
+    }
+
+    pub fn set_mouse(&mut self, owner: InputOwner) {
+        self.mouse = owner;
+    }
+
+    pub fn set_keyboard(&mut self, owner: InputOwner) {
+        self.keyboard = owner;
+    }
+}
+
+impl Default for InputFocus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
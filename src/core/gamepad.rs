@@ -0,0 +1,81 @@
+use bevy_ecs::prelude::*;
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+use super::{AxisDirection, ButtonInput};
+
+//the buttons and sticks of every connected gamepad, merged as if they were one
+//gilrs isn't Send, so this lives in the world as a non send resource
+pub struct Gamepads {
+    //none when the platform has no gamepad support, everything just reads as up
+    gilrs: Option<Gilrs>,
+    buttons: ButtonInput<Button>,
+    //sticks and triggers pushed past AXIS_THRESHOLD one way or the other
+    axes: ButtonInput<(Axis, AxisDirection)>,
+}
+
+impl Gamepads {
+    //how far from the middle a stick has to be pushed to count as pressed, from 0 to 1
+    pub const AXIS_THRESHOLD: f32 = 0.5f32;
+
+    pub fn init(world: &mut World) {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(error) => {
+                eprintln!("Gamepads are unavailable: {}", error);
+                None
+            }
+        };
+
+        world.insert_non_send_resource(Self {
+            gilrs,
+            buttons: ButtonInput::new(),
+            axes: ButtonInput::new(),
+        });
+    }
+
+    pub fn buttons(&self) -> &ButtonInput<Button> {
+        &self.buttons
+    }
+
+    pub fn axes(&self) -> &ButtonInput<(Axis, AxisDirection)> {
+        &self.axes
+    }
+
+    //runs once a frame alongside the EventSystem's update
+    pub fn poll(mut gamepads: NonSendMut<Gamepads>) {
+        let gamepads = &mut *gamepads;
+        let gilrs = match gamepads.gilrs.as_mut() {
+            Some(gilrs) => gilrs,
+            None => return,
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => gamepads.buttons.press(button),
+                EventType::ButtonReleased(button, _) => gamepads.buttons.release(button),
+                EventType::AxisChanged(axis, value, _) => {
+                    let (pushed, other) = if value >= 0f32 {
+                        (AxisDirection::Positive, AxisDirection::Negative)
+                    } else {
+                        (AxisDirection::Negative, AxisDirection::Positive)
+                    };
+                    gamepads.axes.release((axis, other));
+                    if value.abs() >= Self::AXIS_THRESHOLD {
+                        gamepads.axes.press((axis, pushed));
+                    } else {
+                        gamepads.axes.release((axis, pushed));
+                    }
+                }
+                //a gamepad unplugged mid press never sends the release
+                EventType::Disconnected => {
+                    gamepads.buttons.release_all();
+                    gamepads.axes.release_all();
+                }
+                _ => {}
+            }
+        }
+
+        gamepads.buttons.start_frame();
+        gamepads.axes.start_frame();
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    path::PathBuf,
+};
+
+use winit::event::{ModifiersState, MouseButton, VirtualKeyCode};
+
+//which buttons of one kind are down, and which went down or up since the last frame
+pub struct ButtonInput<T> {
+    pressed: HashSet<T>,
+    just_pressed: HashSet<T>,
+    just_released: HashSet<T>,
+
+    //what came in since the last frame, it becomes just_pressed and just_released when the frame starts
+    pending_pressed: HashSet<T>,
+    pending_released: HashSet<T>,
+}
+
+impl<T: Copy + Eq + Hash> ButtonInput<T> {
+    pub(super) fn new() -> Self {
+        Self {
+            pressed: HashSet::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+
+            pending_pressed: HashSet::new(),
+            pending_released: HashSet::new(),
+        }
+    }
+
+    pub fn pressed(&self, button: T) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    //a press and release within the same frame counts as both
+    pub fn just_pressed(&self, button: T) -> bool {
+        self.just_pressed.contains(&button)
+    }
+
+    pub fn just_released(&self, button: T) -> bool {
+        self.just_released.contains(&button)
+    }
+
+    pub fn any_pressed(&self, buttons: &[T]) -> bool {
+        buttons.iter().any(|button| self.pressed(*button))
+    }
+
+    pub fn any_just_pressed(&self, buttons: &[T]) -> bool {
+        buttons.iter().any(|button| self.just_pressed(*button))
+    }
+
+    pub fn iter_pressed(&self) -> impl Iterator<Item = &T> {
+        self.pressed.iter()
+    }
+
+    pub fn iter_just_pressed(&self) -> impl Iterator<Item = &T> {
+        self.just_pressed.iter()
+    }
+
+    pub fn iter_just_released(&self) -> impl Iterator<Item = &T> {
+        self.just_released.iter()
+    }
+
+    //key repeats are not presses, only the first one counts
+    pub(super) fn press(&mut self, button: T) {
+        if self.pressed.insert(button) {
+            self.pending_pressed.insert(button);
+        }
+    }
+
+    pub(super) fn release(&mut self, button: T) {
+        if self.pressed.remove(&button) {
+            self.pending_released.insert(button);
+        }
+    }
+
+    pub(super) fn release_all(&mut self) {
+        let pressed: Vec<T> = self.pressed.iter().copied().collect();
+        for button in pressed {
+            self.release(button);
+        }
+    }
+
+    pub(super) fn start_frame(&mut self) {
+        self.just_pressed = std::mem::take(&mut self.pending_pressed);
+        self.just_released = std::mem::take(&mut self.pending_released);
+    }
+}
+
+//the state of the keyboard, mouse and touches, kept up to date by the EventSystem
+//for when it's easier to ask whether something is down than to follow the events
+pub struct Input {
+    keys: ButtonInput<VirtualKeyCode>,
+    mouse_buttons: ButtonInput<MouseButton>,
+    modifiers: ModifiersState,
+    cursor_position: (f64, f64),
+    //every finger on a touch screen by touch id
+    touches: HashMap<u64, (f64, f64)>,
+
+    text: String,
+    pending_text: String,
+    dropped_files: Vec<PathBuf>,
+    pending_dropped_files: Vec<PathBuf>,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self {
+            keys: ButtonInput::new(),
+            mouse_buttons: ButtonInput::new(),
+            modifiers: ModifiersState::empty(),
+            cursor_position: (0f64, 0f64),
+            touches: HashMap::new(),
+
+            text: String::new(),
+            pending_text: String::new(),
+            dropped_files: Vec::new(),
+            pending_dropped_files: Vec::new(),
+        }
+    }
+
+    pub fn keys(&self) -> &ButtonInput<VirtualKeyCode> {
+        &self.keys
+    }
+
+    pub fn mouse_buttons(&self) -> &ButtonInput<MouseButton> {
+        &self.mouse_buttons
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    //window pixels from the top left
+    pub fn cursor_position(&self) -> (f64, f64) {
+        self.cursor_position
+    }
+
+    pub fn touches(&self) -> impl Iterator<Item = (u64, (f64, f64))> + '_ {
+        self.touches.iter().map(|(id, position)| (*id, *position))
+    }
+
+    //everything typed since the last frame, in order
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn dropped_files(&self) -> &[PathBuf] {
+        &self.dropped_files
+    }
+
+    pub(super) fn press_key(&mut self, key: VirtualKeyCode) {
+        self.keys.press(key);
+    }
+
+    pub(super) fn release_key(&mut self, key: VirtualKeyCode) {
+        self.keys.release(key);
+    }
+
+    pub(super) fn press_mouse_button(&mut self, button: MouseButton) {
+        self.mouse_buttons.press(button);
+    }
+
+    pub(super) fn release_mouse_button(&mut self, button: MouseButton) {
+        self.mouse_buttons.release(button);
+    }
+
+    pub(super) fn set_modifiers(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
+    }
+
+    pub(super) fn move_cursor(&mut self, position: (f64, f64)) {
+        self.cursor_position = position;
+    }
+
+    pub(super) fn set_touch(&mut self, id: u64, position: Option<(f64, f64)>) {
+        match position {
+            Some(position) => self.touches.insert(id, position),
+            None => self.touches.remove(&id),
+        };
+    }
+
+    pub(super) fn type_character(&mut self, character: char) {
+        self.pending_text.push(character);
+    }
+
+    pub(super) fn drop_file(&mut self, path: PathBuf) {
+        self.pending_dropped_files.push(path);
+    }
+
+    //the window stops getting key and button releases once it loses focus, so everything is let go
+    pub(super) fn lose_focus(&mut self) {
+        self.keys.release_all();
+        self.mouse_buttons.release_all();
+        self.modifiers = ModifiersState::empty();
+    }
+
+    pub(super) fn start_frame(&mut self) {
+        self.keys.start_frame();
+        self.mouse_buttons.start_frame();
+        self.text = std::mem::take(&mut self.pending_text);
+        self.dropped_files = std::mem::take(&mut self.pending_dropped_files);
+    }
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn press_and_release_show_up_one_frame_each() {
+        let mut keys = ButtonInput::new();
+
+        keys.press(VirtualKeyCode::A);
+        keys.start_frame();
+        assert!(keys.pressed(VirtualKeyCode::A));
+        assert!(keys.just_pressed(VirtualKeyCode::A));
+        assert!(!keys.just_released(VirtualKeyCode::A));
+
+        keys.release(VirtualKeyCode::A);
+        keys.start_frame();
+        assert!(!keys.pressed(VirtualKeyCode::A));
+        assert!(!keys.just_pressed(VirtualKeyCode::A));
+        assert!(keys.just_released(VirtualKeyCode::A));
+        assert_eq!(keys.iter_just_released().collect::<Vec<_>>(), vec![&VirtualKeyCode::A]);
+
+        //both are cleared once a frame passes with nothing new
+        keys.start_frame();
+        assert!(!keys.just_released(VirtualKeyCode::A));
+        assert_eq!(keys.iter_just_pressed().count(), 0);
+    }
+}
@@ -2,4 +2,19 @@ mod window;
 pub use window::WindowSystem;
 
 mod event;
-pub use event::{Event, EventSystem};
\ No newline at end of file
+pub use event::{Event, EventSystem};
+
+mod input;
+pub use input::{ButtonInput, Input};
+
+mod actions;
+pub use actions::{ActionState, AxisDirection, Binding, InputMap, KeyBinding};
+
+mod gamepad;
+pub use gamepad::Gamepads;
+
+mod recording;
+pub use recording::{Recorder, RecordingHeader, Replay};
+
+mod focus;
+pub use focus::{InputFocus, InputOwner};
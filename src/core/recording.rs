@@ -0,0 +1,139 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::Event;
+
+//the first line of a recording, what has to match for a replay to play out the same
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    //in physical pixels, cameras and picking depend on it
+    pub window_size: (u32, u32),
+    //what ParticleRng was seeded with, recordings from before it was kept replay with 0
+    #[serde(default)]
+    pub seed: u64,
+}
+
+//every line after the header, one per frame, frames without events are kept for their frame time
+#[derive(Clone, Serialize, Deserialize)]
+struct RecordedFrame {
+    frame: u64,
+    //seconds
+    delta: f64,
+    events: Vec<Event>,
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(PathBuf, io::Error),
+    //the line number, counting from 1
+    Json(PathBuf, usize, serde_json::Error),
+    MissingHeader(PathBuf),
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordingError::Io(path, error) => write!(f, "couldn't access {}: {}", path.display(), error),
+            RecordingError::Json(path, line, error) => write!(f, "line {} of {} isn't valid: {}", line, path.display(), error),
+            RecordingError::MissingHeader(path) => write!(f, "{} is empty", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+//writes every event the EventSystem sends to a json lines file, a frame at a time
+//each frame is flushed as it's written so a crash still leaves everything up to it
+pub struct Recorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    frame: u64,
+    events: Vec<Event>,
+}
+
+impl Recorder {
+    pub fn create<P: AsRef<Path>>(path: P, header: RecordingHeader) -> Result<Self, RecordingError> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path).map_err(|error| RecordingError::Io(path.clone(), error))?;
+        let mut recorder = Self {
+            path,
+            writer: BufWriter::new(file),
+            frame: 0,
+            events: Vec::new(),
+        };
+        recorder.write_line(&header)?;
+        Ok(recorder)
+    }
+
+    pub(super) fn record(&mut self, event: &Event) {
+        self.events.push(event.clone());
+    }
+
+    //writes out everything recorded since the last frame
+    pub(super) fn finish_frame(&mut self, delta: Duration) -> Result<(), RecordingError> {
+        let frame = RecordedFrame {
+            frame: self.frame,
+            delta: delta.as_secs_f64(),
+            events: std::mem::take(&mut self.events),
+        };
+        self.frame += 1;
+        self.write_line(&frame)
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) -> Result<(), RecordingError> {
+        let line = self.frame as usize + 1;
+        let json = serde_json::to_string(value).map_err(|error| RecordingError::Json(self.path.clone(), line, error))?;
+        writeln!(self.writer, "{}", json)
+            .and_then(|_| self.writer.flush())
+            .map_err(|error| RecordingError::Io(self.path.clone(), error))
+    }
+}
+
+//a recording read back, played through the EventSystem a frame at a time in place of the window's input
+//particles pick their own random numbers, so they're the one thing that won't look the same
+pub struct Replay {
+    header: RecordingHeader,
+    frames: VecDeque<RecordedFrame>,
+}
+
+impl Replay {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RecordingError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|error| RecordingError::Io(path.to_path_buf(), error))?;
+
+        //blank lines are skipped, line numbers in errors count them anyway
+        let mut lines = BufReader::new(file)
+            .lines()
+            .enumerate()
+            .map(|(index, line)| line.map(|line| (index + 1, line)).map_err(|error| RecordingError::Io(path.to_path_buf(), error)))
+            .filter(|line| !matches!(line, Ok((_, line)) if line.trim().is_empty()));
+
+        let (number, header) = lines.next().ok_or_else(|| RecordingError::MissingHeader(path.to_path_buf()))??;
+        let header = serde_json::from_str(&header).map_err(|error| RecordingError::Json(path.to_path_buf(), number, error))?;
+
+        let mut frames = VecDeque::new();
+        for line in lines {
+            let (number, line) = line?;
+            frames.push_back(serde_json::from_str(&line).map_err(|error| RecordingError::Json(path.to_path_buf(), number, error))?);
+        }
+
+        Ok(Self { header, frames })
+    }
+
+    pub fn header(&self) -> RecordingHeader {
+        self.header
+    }
+
+    //the next frame's events and how long it took
+    pub(super) fn next_frame(&mut self) -> Option<(Vec<Event>, Duration)> {
+        self.frames.pop_front().map(|frame| (frame.events, Duration::from_secs_f64(frame.delta.max(0f64))))
+    }
+}
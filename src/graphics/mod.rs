@@ -12,4 +12,7 @@ mod uniform;
 pub use uniform::Uniform;
 
 mod texture;
-pub use texture::{Texture, TextureBindLayout};
\ No newline at end of file
+pub use texture::{Texture, TextureBindLayout};
+
+mod texture_cache;
+pub use texture_cache::TextureCache;
@@ -1,8 +1,8 @@
 use bevy_ecs::prelude::*;
 
-use crate::core::WindowSystem;
+use crate::{core::WindowSystem, two_dimensional::{ActiveCamera, Camera2d}};
 
-use super::{RenderContext, Subpass};
+use super::{RenderContext, Subpass, TextureCache};
 
 pub trait RenderPass {
     fn get_name() -> &'static str;
@@ -11,7 +11,7 @@ pub trait RenderPass {
 }
 
 pub struct RenderPassContainer {
-    _name: &'static str,
+    name: &'static str,
     render_system: fn() -> Box<dyn System<In = (), Out = ()>>,
     init_system: fn() -> Box<dyn System<In = (), Out = ()>>,
 }
@@ -33,7 +33,9 @@ impl Renderer {
         //window is a dependency of renderer
         let window_system = world.get_resource::<WindowSystem>().expect("WindowSystem dependency of renderer is not met");
 
-        world.insert_resource(RenderContext::new(window_system.window()).await);
+        let render_context = RenderContext::new(window_system.window()).await;
+        world.insert_resource(TextureCache::new(&render_context));
+        world.insert_resource(render_context);
 
         let mut init = SystemStage::parallel();
         for pass in self.passes.iter() {
@@ -43,29 +45,43 @@ impl Renderer {
 
         //we'll have to reconstruct this render schedule everytime we get a new stage
 
-        //from an optimization standpoint, probably want to chain these systems
-        let mut first_pass = SystemStage::parallel();
+        //every pass gets its own stage so passes draw in the order they were added
         for pass in self.passes.iter() {
-            first_pass
-                .add_system((pass.render_system)());
+            self.render_schedule
+                .add_stage(pass.name, SystemStage::single((pass.render_system)()));
         }
 
-        self.render_schedule.add_stage("Render", first_pass);
         self.render_schedule
             .add_stage("End pass", SystemStage::single(Self::finish_render_pass));
     }
 
+    //every pass runs once per camera, lowest priority first, each camera drawing over the ones before it
     pub fn render(&mut self, world: &mut World) {
+        let mut cameras = world.query::<(Entity, &Camera2d)>();
+        let mut views: Vec<(i32, Entity)> = cameras.iter(world).map(|(entity, camera)| (camera.priority, entity)).collect();
+        views.sort_by_key(|(priority, _)| *priority);
+
+        //nothing to draw, but the frame still gets cleared
+        if views.is_empty() {
+            let render_context = world.get_resource::<RenderContext>().expect("There should be a render context here");
+            let mut subpass = Self::start_subpass(render_context, Self::CLEAR);
+            render_context.queue.submit(std::iter::once(subpass.finish()));
+            return;
+        }
 
-        let render_context = world.get_resource::<RenderContext>().expect("There should be a render context here");
-        let surface_texture = render_context.get_surface_texture();
+        for (index, (_, view)) in views.into_iter().enumerate() {
+            let render_context = world.get_resource::<RenderContext>().expect("There should be a render context here");
+            let load_op = if index == 0 { Self::CLEAR } else { wgpu::LoadOp::Load };
+            let subpass = Self::start_subpass(render_context, load_op);
 
-        //start our renderpass with the data that we need
-        let texture_view = surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
-        world.insert_resource(Subpass::start(texture_view, render_context, wgpu::LoadOp::Clear(wgpu::Color { r: 0.1, g: 0.2, b: 0.1, a: 1.0}) ));
+            world.insert_resource(subpass);
+            world.insert_resource(ActiveCamera(view));
+
+            //the end pass submits, so uniforms each camera writes are in place before the next camera writes its own
+            self.render_schedule.run(world);
+        }
 
-        //begin our pass here
-        self.render_schedule.run(world);
+        world.remove_resource::<ActiveCamera>();
     }
 
     pub fn add_pass<T>(&mut self)
@@ -73,7 +89,7 @@ impl Renderer {
         T: RenderPass,
     {
         self.passes.push(RenderPassContainer {
-            _name: T::get_name(),
+            name: T::get_name(),
             init_system: T::get_init_system,
             render_system: T::get_render_system,
         });
@@ -81,6 +97,14 @@ impl Renderer {
 }
 
 impl Renderer {
+    const CLEAR: wgpu::LoadOp<wgpu::Color> = wgpu::LoadOp::Clear(wgpu::Color { r: 0.1, g: 0.2, b: 0.1, a: 1.0 });
+
+    fn start_subpass(render_context: &RenderContext, load_op: wgpu::LoadOp<wgpu::Color>) -> Subpass {
+        let surface_texture = render_context.get_surface_texture();
+        let texture_view = surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Subpass::start(texture_view, render_context, load_op)
+    }
+
     fn finish_render_pass(mut subpass: ResMut<Subpass>, render_context: Res<RenderContext>) {
         render_context
             .queue
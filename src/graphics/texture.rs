@@ -93,20 +93,95 @@ impl Texture {
             _ => panic!("Invalid pixel format")
         };
 
-        Self::create(texture_rgba, (width, height), render_context)
+        Self::create(texture_rgba, (width, height), wgpu::TextureFormat::Rgba8UnormSrgb, render_context)
     }
 
     pub fn load(file_path: &str, render_context: &RenderContext) -> Self {
+        Self::load_with_format(file_path, wgpu::TextureFormat::Rgba8UnormSrgb, render_context)
+    }
+
+    //for textures that hold data rather than color, like normal maps, which must not be converted from srgb
+    pub fn load_linear(file_path: &str, render_context: &RenderContext) -> Self {
+        Self::load_with_format(file_path, wgpu::TextureFormat::Rgba8Unorm, render_context)
+    }
+
+    //a blank texture that is filled in a region at a time, like a glyph atlas
+    //sampled linearly both ways since its contents get scaled up and down
+    pub fn empty(width: u32, height: u32, format: wgpu::TextureFormat, render_context: &RenderContext) -> Self {
+        let texture = render_context.device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            label: Some("empty_texture"),
+        });
+
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = render_context.device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            width,
+            height,
+
+            texture,
+            texture_view,
+            sampler,
+        }
+    }
+
+    //bytes holds the rows of the region tightly packed, in the texture's format
+    pub fn write_region(&self, origin: (u32, u32), size: (u32, u32), bytes: &[u8], render_context: &RenderContext) {
+        let bytes_per_row = bytes.len() as u32 / size.1.max(1);
+        render_context.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: origin.0,
+                    y: origin.1,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytes,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(bytes_per_row),
+                rows_per_image: std::num::NonZeroU32::new(size.1),
+            },
+            wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    fn load_with_format(file_path: &str, format: wgpu::TextureFormat, render_context: &RenderContext) -> Self {
         
         let t_bytes_vec = load_file_bytes(file_path);
         let texture_bytes = t_bytes_vec.as_slice();
         let texture_image = image::load_from_memory(texture_bytes).unwrap();
         let texture_rgba = texture_image.to_rgba8();
 
-        Self::create(texture_rgba, (texture_image.width(), texture_image.height()), render_context)
+        Self::create(texture_rgba, (texture_image.width(), texture_image.height()), format, render_context)
     }
 
-    fn create<T>(texture_rgba: ImageBuffer<T, Vec<u8>>, dimensions: (u32, u32), render_context: &RenderContext) -> Self 
+    fn create<T>(texture_rgba: ImageBuffer<T, Vec<u8>>, dimensions: (u32, u32), format: wgpu::TextureFormat, render_context: &RenderContext) -> Self 
     where 
         T: image::Pixel<Subpixel = u8> 
     {
@@ -123,7 +198,7 @@ impl Texture {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             // Most images are stored using sRGB so we need to reflect that here.
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            format,
             // TEXTURE_BINDING tells wgpu that we want to use this texture in shaders
             // COPY_DST means that we want to copy data to this texture
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use image::Rgba;
+
+use super::{RenderContext, Texture, TextureBindLayout};
+
+//every texture loaded from disk by path, shared by the passes that draw them so each is only loaded once
+//the empty path is a plain white texture, for things drawn without one
+pub struct TextureCache {
+    bind_layout: TextureBindLayout,
+    textures: HashMap<String, (Texture, wgpu::BindGroup)>,
+}
+
+impl TextureCache {
+    pub fn new(render_context: &RenderContext) -> Self {
+        let bind_layout = TextureBindLayout::new(0, 1, render_context);
+
+        let blank_texture = Texture::new::<Rgba<u8>>(10, 10, vec![255, 255, 255, 255], render_context);
+        let blank_texture_bind_group = bind_layout.create_bind_group(&blank_texture, render_context);
+
+        let mut textures = HashMap::new();
+        textures.insert(String::from(""), (blank_texture, blank_texture_bind_group));
+
+        Self { bind_layout, textures }
+    }
+
+    //the layout of every bind group in the cache, pipelines drawing with them need it
+    pub fn bind_layout(&self) -> &TextureBindLayout {
+        &self.bind_layout
+    }
+
+    //loads the texture the first time it's asked for
+    pub fn load(&mut self, path: &str, render_context: &RenderContext) {
+        if !self.textures.contains_key(path) {
+            let texture = Texture::load(path, render_context);
+            let bind_group = self.bind_layout.create_bind_group(&texture, render_context);
+            self.textures.insert(String::from(path), (texture, bind_group));
+        }
+    }
+
+    pub fn get(&self, path: &str) -> Option<&(Texture, wgpu::BindGroup)> {
+        self.textures.get(path)
+    }
+}
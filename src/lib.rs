@@ -2,6 +2,7 @@ mod graphics;
 mod app;
 mod two_dimensional;
 mod ui;
+mod localization;
 mod core;
 
 mod board;
@@ -0,0 +1,114 @@
+use std::{collections::HashMap, fmt, path::PathBuf};
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+#[derive(Debug)]
+pub enum LocalizationError {
+    Io(PathBuf, std::io::Error),
+    //the messages that did parse are still added
+    Syntax(PathBuf, usize),
+    InvalidLocale(String),
+}
+
+impl fmt::Display for LocalizationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocalizationError::Io(path, error) => write!(f, "couldn't read {}: {}", path.display(), error),
+            LocalizationError::Syntax(path, count) => write!(f, "{} has {} broken messages", path.display(), count),
+            LocalizationError::InvalidLocale(locale) => write!(f, "{} is not a locale", locale),
+        }
+    }
+}
+
+impl std::error::Error for LocalizationError {}
+
+//every user facing string, by message key and locale, read from fluent files
+//messages are looked up in the current locale, then one of the same language, then the fallback locale
+//a key that is missing everywhere is shown as is, so missing translations are easy to spot
+pub struct Localization {
+    bundles: HashMap<LanguageIdentifier, FluentBundle<FluentResource>>,
+    locale: LanguageIdentifier,
+    fallback: LanguageIdentifier,
+}
+
+impl Localization {
+    pub fn new(fallback: &str) -> Result<Self, LocalizationError> {
+        let fallback = parse_locale(fallback)?;
+        Ok(Self {
+            bundles: HashMap::new(),
+            locale: fallback.clone(),
+            fallback,
+        })
+    }
+
+    //loads a .ftl file into a locale, messages already loaded under the same key are replaced
+    pub fn load(&mut self, locale: &str, path: &str) -> Result<(), LocalizationError> {
+        let locale = parse_locale(locale)?;
+        let path = PathBuf::from(path);
+        let source = std::fs::read_to_string(&path).map_err(|error| LocalizationError::Io(path.clone(), error))?;
+        let (resource, broken) = match FluentResource::try_new(source) {
+            Ok(resource) => (resource, 0),
+            Err((resource, errors)) => (resource, errors.len()),
+        };
+
+        let bundle = self.bundles.entry(locale.clone()).or_insert_with(|| {
+            let mut bundle = FluentBundle::new_concurrent(vec![locale]);
+            //the bidi isolation marks fluent puts around arguments aren't in our fonts
+            bundle.set_use_isolating(false);
+            bundle
+        });
+        bundle.add_resource_overriding(resource);
+
+        match broken {
+            0 => Ok(()),
+            broken => Err(LocalizationError::Syntax(path, broken)),
+        }
+    }
+
+    //switching locale updates every LocalizedText on the next frame
+    pub fn set_locale(&mut self, locale: &str) -> Result<(), LocalizationError> {
+        self.locale = parse_locale(locale)?;
+        Ok(())
+    }
+
+    pub fn locale(&self) -> &LanguageIdentifier {
+        &self.locale
+    }
+
+    //every locale with something loaded, sorted so menus list them the same way every time
+    pub fn locales(&self) -> Vec<&LanguageIdentifier> {
+        let mut locales: Vec<&LanguageIdentifier> = self.bundles.keys().collect();
+        locales.sort_by_key(|locale| locale.to_string());
+        locales
+    }
+
+    pub fn text(&self, key: &str) -> String {
+        self.format(key, None)
+    }
+
+    //arguments are filled into the message's placeables and select its plural forms
+    pub fn format(&self, key: &str, args: Option<&FluentArgs>) -> String {
+        let same_language = self
+            .bundles
+            .iter()
+            .filter(|(locale, _)| locale.language == self.locale.language && **locale != self.locale)
+            .min_by_key(|(locale, _)| locale.to_string())
+            .map(|(_, bundle)| bundle);
+
+        [self.bundles.get(&self.locale), same_language, self.bundles.get(&self.fallback)]
+            .into_iter()
+            .flatten()
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(key)?.value()?;
+                //a missing argument is left as its name in the text, which is all we'd do about it anyway
+                let mut errors = Vec::new();
+                Some(bundle.format_pattern(pattern, args, &mut errors).into_owned())
+            })
+            .unwrap_or_else(|| String::from(key))
+    }
+}
+
+fn parse_locale(locale: &str) -> Result<LanguageIdentifier, LocalizationError> {
+    locale.parse().map_err(|_| LocalizationError::InvalidLocale(String::from(locale)))
+}
@@ -0,0 +1,96 @@
+use bevy_ecs::prelude::*;
+use fluent_bundle::{FluentArgs, FluentValue};
+
+use crate::two_dimensional::text::TextBox;
+
+use super::Localization;
+
+//a value filled into a message, numbers also pick the message's plural form
+#[derive(Clone, Debug, PartialEq)]
+pub enum LocaleArg {
+    Number(f64),
+    String(String),
+}
+
+impl From<f64> for LocaleArg {
+    fn from(number: f64) -> Self {
+        LocaleArg::Number(number)
+    }
+}
+
+impl From<i32> for LocaleArg {
+    fn from(number: i32) -> Self {
+        LocaleArg::Number(number as f64)
+    }
+}
+
+impl From<usize> for LocaleArg {
+    fn from(number: usize) -> Self {
+        LocaleArg::Number(number as f64)
+    }
+}
+
+impl From<&str> for LocaleArg {
+    fn from(string: &str) -> Self {
+        LocaleArg::String(String::from(string))
+    }
+}
+
+impl From<String> for LocaleArg {
+    fn from(string: String) -> Self {
+        LocaleArg::String(string)
+    }
+}
+
+//keeps the text of the TextBox on the same entity in the current locale
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct LocalizedText {
+    pub key: String,
+    pub args: Vec<(String, LocaleArg)>,
+}
+
+impl LocalizedText {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: String::from(key),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn with_arg(mut self, name: &str, value: impl Into<LocaleArg>) -> Self {
+        self.set_arg(name, value);
+        self
+    }
+
+    pub fn set_arg(&mut self, name: &str, value: impl Into<LocaleArg>) {
+        let value = value.into();
+        match self.args.iter_mut().find(|(arg_name, _)| arg_name == name) {
+            Some((_, arg)) => *arg = value,
+            None => self.args.push((String::from(name), value)),
+        }
+    }
+
+    pub fn resolve(&self, localization: &Localization) -> String {
+        if self.args.is_empty() {
+            return localization.text(&self.key);
+        }
+
+        let mut args = FluentArgs::new();
+        for (name, value) in self.args.iter() {
+            match value {
+                LocaleArg::Number(number) => args.set(name.as_str(), FluentValue::from(*number)),
+                LocaleArg::String(string) => args.set(name.as_str(), FluentValue::from(string.as_str())),
+            }
+        }
+        localization.format(&self.key, Some(&args))
+    }
+
+    //only texts whose key or arguments changed are looked up again, unless the locale changed
+    pub fn update(localization: Res<Localization>, mut texts: Query<(&LocalizedText, ChangeTrackers<LocalizedText>, &mut TextBox)>) {
+        for (localized_text, tracker, mut text_box) in texts.iter_mut() {
+            if localization.is_changed() || tracker.is_changed() {
+                text_box.text = localized_text.resolve(&localization);
+            }
+        }
+    }
+}
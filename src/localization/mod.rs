@@ -0,0 +1,5 @@
+mod localization;
+pub use localization::Localization;
+
+mod localized_text;
+pub use localized_text::LocalizedText;
@@ -1,6 +1,9 @@
 use bevy_ecs::prelude::*;
+use cgmath::{Matrix4, SquareMatrix, Vector3, Vector4};
 use winit::event::MouseButton;
-use crate::core::{WindowSystem, Event};
+use crate::{core::{ActionState, WindowSystem, Event}, app::FrameTime};
+
+use super::RenderLayers;
 
 #[rustfmt::skip]
 pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
@@ -14,10 +17,55 @@ pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
 pub struct Camera {
     pub position: (f32, f32),
 
+    //the size of the viewport in pixels
     pub screen_size: (f32, f32),
-    pub scale: f32
+    pub scale: f32,
+
+    pub viewport: Viewport,
+    //cameras draw lowest priority first, so a higher priority camera draws over the ones below it
+    pub priority: i32,
+    pub layers: RenderLayers,
+    //whether the lighting pass darkens what this camera sees, off for things like a hud
+    pub lighting: bool,
+
+    //where the viewport is in the window, in pixels, kept up to date with screen_size
+    viewport_origin: (f32, f32),
+    window_size: (f32, f32),
+    //added on top of the position when drawing, so shaking never moves the camera itself
+    pub(super) shake_offset: (f32, f32),
+}
+
+//the part of the window a camera draws to, in fractions of the window from the top left
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    pub const FULL: Viewport = Viewport {
+        x: 0f32,
+        y: 0f32,
+        width: 1f32,
+        height: 1f32,
+    };
+
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
 }
 
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport::FULL
+    }
+}
+
+//the camera the render passes are drawing for, the renderer runs every pass once per camera
+pub struct ActiveCamera(pub Entity);
+
 #[derive(Copy, Clone, Debug)]
 pub struct CameraMatrix {
     pub view: [[f32; 4]; 4]
@@ -31,39 +79,212 @@ impl Camera {
         Self {
             position,
             screen_size: (0f32, 0f32),
-            scale: 100f32
+            scale: 100f32,
+
+            viewport: Viewport::FULL,
+            priority: 0,
+            layers: RenderLayers::DEFAULT,
+            lighting: true,
+
+            viewport_origin: (0f32, 0f32),
+            window_size: (0f32, 0f32),
+            shake_offset: (0f32, 0f32),
         }
     }
 
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_layers(mut self, layers: RenderLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    pub fn without_lighting(mut self) -> Self {
+        self.lighting = false;
+        self
+    }
+
+    //the camera being drawn for, none when there isn't one
+    pub fn active<'a>(cameras: &'a Query<&Camera>, active_camera: Option<&ActiveCamera>) -> Option<&'a Camera> {
+        cameras.get(active_camera?.0).ok()
+    }
+
+    //whether something on these layers shows up in this camera
+    pub fn sees(&self, layers: Option<&RenderLayers>) -> bool {
+        self.layers.intersects(&RenderLayers::of(layers))
+    }
+
+    //x, y, width and height in window pixels
+    pub fn viewport_rect(&self) -> (f32, f32, f32, f32) {
+        (self.viewport_origin.0, self.viewport_origin.1, self.screen_size.0, self.screen_size.1)
+    }
+
+    pub fn window_size(&self) -> (f32, f32) {
+        self.window_size
+    }
+
+    pub fn contains_screen_point(&self, screen_point: (f32, f32)) -> bool {
+        let (x, y, width, height) = self.viewport_rect();
+        screen_point.0 >= x && screen_point.0 < x + width && screen_point.1 >= y && screen_point.1 < y + height
+    }
+
+    //limits drawing to the viewport, every pass calls this on its render passes
+    pub fn set_viewport(&self, render_pass: &mut wgpu::RenderPass) {
+        let (x, y, width, height) = self.viewport_rect();
+        render_pass.set_viewport(x, y, width.max(1f32), height.max(1f32), 0f32, 1f32);
+    }
+
+    //camera clip space to window clip space, for drawing that can't set a viewport, like the glyph brush
+    pub fn viewport_matrix(&self) -> Matrix4<f32> {
+        let (x, y, width, height) = self.viewport_rect();
+        let window = (self.window_size.0.max(1f32), self.window_size.1.max(1f32));
+        Matrix4::from_translation(Vector3::new(
+            (2f32 * x + width) / window.0 - 1f32,
+            1f32 - (2f32 * y + height) / window.1,
+            0f32,
+        )) * Matrix4::from_nonuniform_scale(width / window.0, height / window.1, 1f32)
+    }
+
     pub fn get_matrix(&self) -> CameraMatrix {
+        CameraMatrix { view: self.matrix().into() }
+    }
+
+    //world to clip space, the position is the bottom left of the screen
+    fn matrix(&self) -> Matrix4<f32> {
         let dimensions = (self.screen_size.0 / self.scale, self.screen_size.1 / self.scale);
+        let position = (self.position.0 + self.shake_offset.0, self.position.1 + self.shake_offset.1);
 
         //move this to an update function and update this every frame
         let ortho = cgmath::ortho(
-            self.position.0, 
-            self.position.0 + dimensions.0/2f32, 
-            self.position.1, 
-            self.position.1 + dimensions.1/2f32, 1f32, -1f32);
+            position.0, 
+            position.0 + dimensions.0/2f32, 
+            position.1, 
+            position.1 + dimensions.1/2f32, 1f32, -1f32);
         
-        let matrix = OPENGL_TO_WGPU_MATRIX * ortho;
-        CameraMatrix { view:  matrix.into() }
+        OPENGL_TO_WGPU_MATRIX * ortho
     }
 
+    //window pixels, like the positions in mouse events, with y pointing down from the top left
+    pub fn screen_to_world(&self, screen_point: (f32, f32)) -> (f32, f32) {
+        let screen_point = (screen_point.0 - self.viewport_origin.0, screen_point.1 - self.viewport_origin.1);
+        let clip = Vector4::new(
+            2f32 * screen_point.0 / self.screen_size.0 - 1f32,
+            1f32 - 2f32 * screen_point.1 / self.screen_size.1,
+            0f32,
+            1f32,
+        );
+        //the matrix is only singular before the window size is known
+        let world = self.matrix().invert().map_or(Vector4::new(0f32, 0f32, 0f32, 1f32), |inverse| inverse * clip);
+        (world.x, world.y)
+    }
 
-    //the camera 2d update system
+    pub fn world_to_screen(&self, world_point: (f32, f32)) -> (f32, f32) {
+        let clip = self.matrix() * Vector4::new(world_point.0, world_point.1, 0f32, 1f32);
+        (
+            self.viewport_origin.0 + (clip.x + 1f32) / 2f32 * self.screen_size.0,
+            self.viewport_origin.1 + (1f32 - clip.y) / 2f32 * self.screen_size.1,
+        )
+    }
+
+
+    //how many screen pixels one world unit covers
+    pub fn pixels_per_unit(&self) -> f32 {
+        2f32 * self.scale
+    }
+
+    //the width and height of the world the viewport shows, in world units
+    pub fn visible_size(&self) -> (f32, f32) {
+        let pixels_per_unit = self.pixels_per_unit();
+        (self.screen_size.0 / pixels_per_unit, self.screen_size.1 / pixels_per_unit)
+    }
+
+    pub fn center(&self) -> (f32, f32) {
+        let size = self.visible_size();
+        (self.position.0 + size.0 / 2f32, self.position.1 + size.1 / 2f32)
+    }
+
+    pub fn set_center(&mut self, center: (f32, f32)) {
+        let size = self.visible_size();
+        self.position = (center.0 - size.0 / 2f32, center.1 - size.1 / 2f32);
+    }
+
+    //the camera 2d update system, keeps every viewport in pixels in step with the window
     pub fn resize(mut cameras: Query<&mut Camera>, window_system: Res<WindowSystem>) {
+        let size = window_system.window().inner_size();
+        let window_size = (size.width as f32, size.height as f32);
         for mut camera in cameras.iter_mut() {
-            let size = window_system.window().inner_size();
-            camera.screen_size = (size.width as f32, size.height as f32);
+            let viewport = camera.viewport;
+            let origin = (
+                (viewport.x.clamp(0f32, 1f32) * window_size.0).round(),
+                (viewport.y.clamp(0f32, 1f32) * window_size.1).round(),
+            );
+            let end = (
+                ((viewport.x + viewport.width).clamp(0f32, 1f32) * window_size.0).round(),
+                ((viewport.y + viewport.height).clamp(0f32, 1f32) * window_size.1).round(),
+            );
+
+            camera.window_size = window_size;
+            camera.viewport_origin = origin;
+            camera.screen_size = ((end.0 - origin.0).max(0f32), (end.1 - origin.1).max(0f32));
         }
     }
 }
 
+//the actions that pan the camera and which way, up and right are positive like world space
+pub const DEFAULT_PAN_ACTIONS: [(&str, (f32, f32)); 4] = [
+    ("pan_up", (0f32, 1f32)),
+    ("pan_down", (0f32, -1f32)),
+    ("pan_left", (-1f32, 0f32)),
+    ("pan_right", (1f32, 0f32)),
+];
+
+//below this many screen pixels a second a flick has run out and the camera stops
+const MIN_FLICK_SPEED: f32 = 5f32;
+//roughly how many seconds of dragging the flick speed is averaged over
+const FLICK_SMOOTHING: f32 = 0.05f32;
+
 #[derive(Component)]
 pub struct CameraController {
     last_cursor_pos: (f32, f32),
     start_camera_pos: (f32, f32),
     pressed: bool,
+
+    //where the cursor is now, zooming keeps the world point under it in place
+    cursor_pos: (f32, f32),
+    //the scale the camera is easing towards and the screen point it zooms around
+    target_scale: Option<f32>,
+    zoom_anchor: (f32, f32),
+
+    //in world units a second, measured while dragging and carried on after letting go
+    velocity: (f32, f32),
+    //where the drag had the camera last frame, to measure the velocity from
+    last_drag_pos: (f32, f32),
+
+    //camera scales, see Camera::scale
+    pub min_scale: f32,
+    pub max_scale: f32,
+    //how much one line of scrolling zooms by
+    pub zoom_speed: f32,
+    //roughly how many seconds the zoom takes to catch up with the wheel, 0 zooms straight away
+    pub zoom_smoothing: f32,
+
+    //the button that drags the camera around, left is left free for the board
+    pub pan_button: MouseButton,
+    //read from the ActionState, so they can be bound to keys, buttons or sticks, no actions turns panning with actions off
+    pub pan_actions: Vec<(String, (f32, f32))>,
+    //in screen pixels a second, so panning with actions feels the same at any zoom
+    pub key_pan_speed: f32,
+    //how quickly a flick slows down, the speed left after a second is e to the minus friction
+    //none stops the camera as soon as the button is let go
+    pub friction: Option<f32>,
 }
 
 impl CameraController {
@@ -72,37 +293,224 @@ impl CameraController {
             last_cursor_pos: (0f32, 0f32),
             start_camera_pos: (0f32, 0f32),
             pressed: false,
+
+            cursor_pos: (0f32, 0f32),
+            target_scale: None,
+            zoom_anchor: (0f32, 0f32),
+
+            velocity: (0f32, 0f32),
+            last_drag_pos: (0f32, 0f32),
+
+            min_scale: 10f32,
+            max_scale: 1000f32,
+            zoom_speed: 1.15f32,
+            zoom_smoothing: 0.08f32,
+
+            pan_button: MouseButton::Right,
+            pan_actions: DEFAULT_PAN_ACTIONS.iter().map(|(action, direction)| (String::from(*action), *direction)).collect(),
+            key_pan_speed: 600f32,
+            friction: Some(4f32),
         }
     }
 
-    pub fn update(mut cameras: Query<(&mut Camera, &mut CameraController)>, mut reader: EventReader<Event>) {
+    //the wheel zooms around the cursor and a pinch around the middle of the fingers
+    //winit doesn't report trackpad pinches, but windows sends them as scrolling so they zoom too
+    pub fn update(
+        mut cameras: Query<(&mut Camera, &mut CameraController)>,
+        mut reader: EventReader<Event>,
+        actions: Option<Res<ActionState>>,
+        frame_time: Option<Res<FrameTime>>,
+    ) {
         //respond to events here for every camera
 
         for event in reader.iter() {
             for (mut camera, mut cam_controller) in cameras.iter_mut() {
                 //do our updating here
                 match event {
-                    Event::MousePressed((MouseButton::Left, position)) => {
+                    //with several cameras, only the one under the cursor starts a drag or zooms
+                    Event::MousePressed((button, position))
+                        if *button == cam_controller.pan_button && camera.contains_screen_point((position.0 as f32, position.1 as f32)) =>
+                    {
                         cam_controller.last_cursor_pos = (position.0 as f32, position.1 as f32);
                         cam_controller.start_camera_pos = camera.position;
+                        cam_controller.last_drag_pos = camera.position;
+                        cam_controller.velocity = (0f32, 0f32);
                         cam_controller.pressed = true;
                     },
                     Event::CursorMoved(position) => {
+                        cam_controller.cursor_pos = (position.0 as f32, position.1 as f32);
                         if cam_controller.pressed {
                             let position = (position.0 as f32, position.1 as f32);
-                            let dpass = ((position.0 - cam_controller.last_cursor_pos.0), (position.1 - cam_controller.last_cursor_pos.1) as f32);
+                            let dpass = ((position.0 - cam_controller.last_cursor_pos.0), (position.1 - cam_controller.last_cursor_pos.1));
                             let position_translation = (dpass.0 / (2f32 * camera.scale), dpass.1 / (2f32 * camera.scale));
 
                             camera.position = (cam_controller.start_camera_pos.0 - position_translation.0, cam_controller.start_camera_pos.1 + position_translation.1);
                         }
                     }
-                    Event::MouseReleased((MouseButton::Left, ..)) => {
-                        //do some calculation for what the new position is
+                    Event::MouseReleased((button, ..)) if *button == cam_controller.pan_button => {
+                        //the camera carries on at the speed it was dragged at
                         cam_controller.pressed = false;
+                        if cam_controller.friction.is_none() {
+                            cam_controller.velocity = (0f32, 0f32);
+                        }
+                    },
+                    Event::Scroll(((_, lines), position)) if camera.contains_screen_point((position.0 as f32, position.1 as f32)) => {
+                        let factor = cam_controller.zoom_speed.powf(*lines as f32);
+                        cam_controller.zoom_by(&camera, factor, (position.0 as f32, position.1 as f32));
+                    },
+                    Event::Pinch((factor, center)) if camera.contains_screen_point((center.0 as f32, center.1 as f32)) => {
+                        cam_controller.zoom_by(&camera, *factor as f32, (center.0 as f32, center.1 as f32));
                     },
                     _ => {}
                 }
             }
-        }   
+        }
+
+        let delta = frame_time.map_or(0f32, |frame_time| frame_time.0.as_secs_f32());
+        for (mut camera, mut cam_controller) in cameras.iter_mut() {
+            if let Some(actions) = actions.as_deref() {
+                cam_controller.pan_with_actions(&mut camera, actions, delta);
+            }
+            cam_controller.drift(&mut camera, delta);
+            cam_controller.ease_zoom(&mut camera, delta);
+        }
+    }
+
+    //moves by the held actions, diagonals aren't any faster than straight lines
+    fn pan_with_actions(&self, camera: &mut Camera, actions: &ActionState, delta: f32) {
+        let direction = self
+            .pan_actions
+            .iter()
+            .filter(|(action, _)| actions.pressed(action))
+            .fold((0f32, 0f32), |sum, (_, direction)| (sum.0 + direction.0, sum.1 + direction.1));
+        let length = (direction.0 * direction.0 + direction.1 * direction.1).sqrt();
+        if length <= f32::EPSILON {
+            return;
+        }
+
+        let distance = self.key_pan_speed / camera.pixels_per_unit() * delta / length;
+        camera.position = (camera.position.0 + direction.0 * distance, camera.position.1 + direction.1 * distance);
+    }
+
+    //measures the drag while the button is down, and coasts to a stop once it's let go
+    fn drift(&mut self, camera: &mut Camera, delta: f32) {
+        if delta <= 0f32 {
+            return;
+        }
+
+        if self.pressed {
+            let moved = (camera.position.0 - self.last_drag_pos.0, camera.position.1 - self.last_drag_pos.1);
+            let amount = 1f32 - (-delta / FLICK_SMOOTHING).exp();
+            self.velocity = (
+                self.velocity.0 + (moved.0 / delta - self.velocity.0) * amount,
+                self.velocity.1 + (moved.1 / delta - self.velocity.1) * amount,
+            );
+            self.last_drag_pos = camera.position;
+            return;
+        }
+
+        let friction = match self.friction {
+            Some(friction) => friction,
+            None => return,
+        };
+        let speed = (self.velocity.0 * self.velocity.0 + self.velocity.1 * self.velocity.1).sqrt();
+        if speed * camera.pixels_per_unit() < MIN_FLICK_SPEED {
+            self.velocity = (0f32, 0f32);
+            return;
+        }
+
+        camera.position = (camera.position.0 + self.velocity.0 * delta, camera.position.1 + self.velocity.1 * delta);
+        let decay = (-friction * delta).exp();
+        self.velocity = (self.velocity.0 * decay, self.velocity.1 * decay);
     }
-}
\ No newline at end of file
+
+    //ease towards the target zoom, in log space so zooming in and out feel the same
+    fn ease_zoom(&mut self, camera: &mut Camera, delta: f32) {
+        let target_scale = match self.target_scale {
+            Some(target_scale) => target_scale,
+            None => return,
+        };
+
+        let amount = if self.zoom_smoothing > 0f32 {
+            1f32 - (-delta / self.zoom_smoothing).exp()
+        } else {
+            1f32
+        };
+        let mut scale = (camera.scale.ln() + (target_scale.ln() - camera.scale.ln()) * amount).exp();
+        if (scale - target_scale).abs() <= target_scale * 0.001f32 {
+            scale = target_scale;
+            self.target_scale = None;
+        }
+
+        zoom_around(camera, scale, self.zoom_anchor);
+
+        //a drag carries on from where the zoom left the camera
+        if self.pressed {
+            self.start_camera_pos = camera.position;
+            self.last_cursor_pos = self.cursor_pos;
+            self.last_drag_pos = camera.position;
+        }
+    }
+
+    fn zoom_by(&mut self, camera: &Camera, factor: f32, anchor: (f32, f32)) {
+        let current = self.target_scale.unwrap_or(camera.scale);
+        self.target_scale = Some((current * factor).clamp(self.min_scale, self.max_scale));
+        self.zoom_anchor = anchor;
+    }
+}
+
+//changes the scale while keeping the world point under a screen point where it is
+fn zoom_around(camera: &mut Camera, scale: f32, anchor: (f32, f32)) {
+    let world_point = camera.screen_to_world(anchor);
+    camera.scale = scale;
+
+    let moved_to = camera.screen_to_world(anchor);
+    camera.position = (
+        camera.position.0 + world_point.0 - moved_to.0,
+        camera.position.1 + world_point.1 - moved_to.1,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+            "{:?} is not {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn camera_on_screen() -> Camera {
+        let mut camera = Camera::new((2f32, 3f32));
+        camera.screen_size = (800f32, 600f32);
+        camera.viewport_origin = (100f32, 50f32);
+        camera
+    }
+
+    #[test]
+    fn screen_and_world_points_round_trip() {
+        let camera = camera_on_screen();
+
+        //the bottom left of the viewport is the camera position
+        assert_close(camera.screen_to_world((100f32, 650f32)), (2f32, 3f32));
+        for point in [(100f32, 50f32), (500f32, 350f32), (733f32, 121f32)] {
+            assert_close(camera.world_to_screen(camera.screen_to_world(point)), point);
+        }
+    }
+
+    #[test]
+    fn zooming_keeps_the_point_under_the_anchor() {
+        let mut camera = camera_on_screen();
+        let anchor = (320f32, 200f32);
+        let world_point = camera.screen_to_world(anchor);
+
+        zoom_around(&mut camera, 50f32, anchor);
+
+        assert_eq!(camera.scale, 50f32);
+        assert_close(camera.screen_to_world(anchor), world_point);
+    }
+}
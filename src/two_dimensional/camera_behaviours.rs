@@ -0,0 +1,344 @@
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+
+use crate::app::FrameTime;
+
+use super::{Camera2d as Camera, GlobalTransform2d};
+
+//keeps a target entity in view, the target can move around inside the dead zone before the camera follows
+#[derive(Component, Debug)]
+pub struct CameraFollow {
+    pub target: Entity,
+    //roughly how many seconds the camera takes to catch up, 0 sticks to the target
+    pub damping: f32,
+    //half the width and height of the box around the middle of the view, in world units
+    pub dead_zone: (f32, f32),
+    //where the target is kept, from the middle of the view
+    pub offset: (f32, f32),
+}
+
+impl CameraFollow {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            damping: 0.2f32,
+            dead_zone: (0f32, 0f32),
+            offset: (0f32, 0f32),
+        }
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_dead_zone(mut self, half_width: f32, half_height: f32) -> Self {
+        self.dead_zone = (half_width, half_height);
+        self
+    }
+
+    pub fn with_offset(mut self, offset: (f32, f32)) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    //runs after the transforms are propagated, a target that's gone leaves the camera where it is
+    pub fn update(mut cameras: Query<(&mut Camera, &CameraFollow)>, targets: Query<&GlobalTransform2d>, frame_time: Option<Res<FrameTime>>) {
+        let delta = frame_time.map_or(0f32, |frame_time| frame_time.0.as_secs_f32());
+        for (mut camera, follow) in cameras.iter_mut() {
+            let target = match targets.get(follow.target) {
+                Ok(transform) => transform.translation(),
+                Err(_) => continue,
+            };
+            let target = (target[0] - follow.offset.0, target[1] - follow.offset.1);
+
+            let center = camera.center();
+            let wanted = (
+                into_dead_zone(center.0, target.0, follow.dead_zone.0),
+                into_dead_zone(center.1, target.1, follow.dead_zone.1),
+            );
+
+            let amount = smoothing(delta, follow.damping);
+            camera.set_center((center.0 + (wanted.0 - center.0) * amount, center.1 + (wanted.1 - center.1) * amount));
+        }
+    }
+}
+
+//the view is kept inside the bounds, a view bigger than the bounds is centered on them instead
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct CameraBounds {
+    //the bottom left and top right corners, in world units
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+impl CameraBounds {
+    pub fn new(min: (f32, f32), max: (f32, f32)) -> Self {
+        Self { min, max }
+    }
+
+    //runs after everything else that moves or zooms the camera
+    pub fn clamp(mut cameras: Query<(&mut Camera, &CameraBounds)>) {
+        for (mut camera, bounds) in cameras.iter_mut() {
+            let size = camera.visible_size();
+            camera.position = (
+                clamp_axis(camera.position.0, size.0, bounds.min.0, bounds.max.0),
+                clamp_axis(camera.position.1, size.1, bounds.min.1, bounds.max.1),
+            );
+        }
+    }
+}
+
+//eases the camera to a center and scale, and removes itself when it gets there
+//whatever isn't being changed stays where it was when the transition started
+#[derive(Component, Debug)]
+pub struct CameraTransition {
+    to_center: Option<(f32, f32)>,
+    to_scale: Option<f32>,
+    //the center and scale the camera had on the transition's first update
+    from: Option<((f32, f32), f32)>,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+impl CameraTransition {
+    pub fn move_to(center: (f32, f32), duration: Duration) -> Self {
+        Self {
+            to_center: Some(center),
+            to_scale: None,
+            from: None,
+            duration,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    //zooms around the middle of the view, see Camera2d::scale
+    pub fn zoom_to(scale: f32, duration: Duration) -> Self {
+        Self {
+            to_center: None,
+            to_scale: Some(scale),
+            from: None,
+            duration,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn with_zoom(mut self, scale: f32) -> Self {
+        self.to_scale = Some(scale);
+        self
+    }
+
+    pub fn update(mut commands: Commands, mut cameras: Query<(Entity, &mut Camera, &mut CameraTransition)>, frame_time: Option<Res<FrameTime>>) {
+        let frame_time = match frame_time {
+            Some(frame_time) => frame_time.0,
+            None => return,
+        };
+
+        for (entity, mut camera, mut transition) in cameras.iter_mut() {
+            let (from_center, from_scale) = match transition.from {
+                Some(from) => from,
+                None => {
+                    let from = (camera.center(), camera.scale);
+                    transition.from = Some(from);
+                    from
+                }
+            };
+            transition.elapsed += frame_time;
+
+            let t = if transition.duration.is_zero() {
+                1f32
+            } else {
+                (transition.elapsed.as_secs_f32() / transition.duration.as_secs_f32()).min(1f32)
+            };
+            //starts and ends gently
+            let eased = t * t * (3f32 - 2f32 * t);
+
+            //scale is eased in log space so zooming in and out take the same time
+            let to_scale = transition.to_scale.unwrap_or(from_scale);
+            camera.scale = (from_scale.ln() + (to_scale.ln() - from_scale.ln()) * eased).exp();
+
+            let to_center = transition.to_center.unwrap_or(from_center);
+            camera.set_center((
+                from_center.0 + (to_center.0 - from_center.0) * eased,
+                from_center.1 + (to_center.1 - from_center.1) * eased,
+            ));
+
+            if t >= 1f32 {
+                commands.entity(entity).remove::<CameraTransition>();
+            }
+        }
+    }
+}
+
+//trauma based shake, knocks add trauma which wears off over time and the view shakes with its square
+//so small knocks barely move it and big ones shake hard, the camera's position is never changed
+#[derive(Component, Debug)]
+pub struct CameraShake {
+    //0 to 1
+    pub trauma: f32,
+    //trauma lost every second
+    pub decay: f32,
+    //how far the view moves at full trauma, in world units
+    pub max_offset: f32,
+    //how many times a second the shake changes direction, roughly
+    pub frequency: f32,
+    time: f32,
+}
+
+impl CameraShake {
+    pub fn new() -> Self {
+        Self {
+            trauma: 0f32,
+            decay: 1.5f32,
+            max_offset: 0.25f32,
+            frequency: 20f32,
+            time: 0f32,
+        }
+    }
+
+    pub fn with_decay(mut self, decay: f32) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    pub fn with_max_offset(mut self, max_offset: f32) -> Self {
+        self.max_offset = max_offset;
+        self
+    }
+
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0f32, 1f32);
+    }
+
+    //the offset stays on the camera if the shake is removed mid shake, so leave it on and let it settle instead
+    pub fn update(mut cameras: Query<(&mut Camera, &mut CameraShake)>, frame_time: Option<Res<FrameTime>>) {
+        let delta = frame_time.map_or(0f32, |frame_time| frame_time.0.as_secs_f32());
+        for (mut camera, mut shake) in cameras.iter_mut() {
+            shake.time += delta;
+            shake.trauma = (shake.trauma - shake.decay * delta).max(0f32);
+
+            let amount = shake.trauma * shake.trauma * shake.max_offset;
+            let time = shake.time * shake.frequency;
+            camera.shake_offset = (wobble(time, 0f32) * amount, wobble(time, 31.7f32) * amount);
+        }
+    }
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//moves the center just far enough that the target is back inside the dead zone
+fn into_dead_zone(center: f32, target: f32, half_size: f32) -> f32 {
+    if target > center + half_size {
+        target - half_size
+    } else if target < center - half_size {
+        target + half_size
+    } else {
+        center
+    }
+}
+
+fn clamp_axis(position: f32, size: f32, min: f32, max: f32) -> f32 {
+    if size >= max - min {
+        min + (max - min - size) / 2f32
+    } else {
+        position.clamp(min, max - size)
+    }
+}
+
+//how far to move towards a target this frame, for smoothing that doesn't depend on the frame rate
+fn smoothing(delta: f32, seconds: f32) -> f32 {
+    if seconds > 0f32 {
+        1f32 - (-delta / seconds).exp()
+    } else {
+        1f32
+    }
+}
+
+//smooth noise from -1 to 1, a few sines that never quite line up so it doesn't look like it repeats
+fn wobble(time: f32, seed: f32) -> f32 {
+    ((time + seed).sin() + 0.5f32 * (2.3f32 * time + 1.7f32 * seed).sin() + 0.25f32 * (4.9f32 * time + 2.9f32 * seed).sin()) / 1.75f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::two_dimensional::Transform2d;
+
+    fn assert_close(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-4 && (actual.1 - expected.1).abs() < 1e-4,
+            "{:?} is not {:?}",
+            actual,
+            expected
+        );
+    }
+
+    //an 800x600 view whose middle is at (2, 1.5)
+    fn camera() -> Camera {
+        let mut camera = Camera::new((0f32, 0f32));
+        camera.screen_size = (800f32, 600f32);
+        camera
+    }
+
+    #[test]
+    fn zooming_eases_to_the_scale_around_the_middle_and_finishes() {
+        let mut world = World::new();
+        world.insert_resource(FrameTime(Duration::from_millis(250)));
+        let entity = world.spawn().insert(camera()).insert(CameraTransition::zoom_to(50f32, Duration::from_secs(1))).id();
+        let mut transition_system = SystemStage::single(CameraTransition::update);
+
+        transition_system.run(&mut world);
+        transition_system.run(&mut world);
+        let halfway = world.get::<Camera>(entity).unwrap().scale;
+        assert!(halfway < 100f32 && halfway > 50f32, "{} isn't between the scales", halfway);
+        assert_close(world.get::<Camera>(entity).unwrap().center(), (2f32, 1.5f32));
+
+        transition_system.run(&mut world);
+        transition_system.run(&mut world);
+        let camera = world.get::<Camera>(entity).unwrap();
+        assert!((camera.scale - 50f32).abs() < 1e-3);
+        assert_close(camera.center(), (2f32, 1.5f32));
+        assert!(world.get::<CameraTransition>(entity).is_none());
+    }
+
+    #[test]
+    fn moving_and_zooming_together_land_on_both() {
+        let mut world = World::new();
+        world.insert_resource(FrameTime(Duration::from_millis(400)));
+        let transition = CameraTransition::move_to((10f32, -4f32), Duration::from_secs(1)).with_zoom(200f32);
+        let entity = world.spawn().insert(camera()).insert(transition).id();
+        let mut transition_system = SystemStage::single(CameraTransition::update);
+
+        for _ in 0..3 {
+            transition_system.run(&mut world);
+        }
+        let camera = world.get::<Camera>(entity).unwrap();
+        assert!((camera.scale - 200f32).abs() < 1e-3);
+        assert_close(camera.center(), (10f32, -4f32));
+        assert!(world.get::<CameraTransition>(entity).is_none());
+    }
+
+    #[test]
+    fn following_keeps_the_target_at_the_offset() {
+        let mut world = World::new();
+        world.insert_resource(FrameTime(Duration::from_millis(16)));
+        let target = world.spawn().insert(GlobalTransform2d::from(&Transform2d::from_translation([5f32, 5f32]))).id();
+        let follow = CameraFollow::new(target).with_damping(0f32).with_offset((1f32, -0.5f32));
+        let entity = world.spawn().insert(camera()).insert(follow).id();
+
+        SystemStage::single(CameraFollow::update).run(&mut world);
+
+        assert_close(world.get::<Camera>(entity).unwrap().center(), (4f32, 5.5f32));
+    }
+}
@@ -0,0 +1,153 @@
+use bevy_ecs::prelude::*;
+use wgpu::{RenderPassDescriptor, RenderPipeline};
+
+use crate::{
+    graphics::{RenderContext, RenderPass, Subpass, Uniform},
+    two_dimensional::{
+        camera::{Camera, CameraMatrix},
+        sprite::BlendMode,
+        ActiveCamera, RenderLayers,
+    },
+};
+
+use super::{Gizmos, ShapeVertex};
+
+//draws whatever was put into Gizmos this frame, Gizmos::clear empties it once every camera has drawn
+pub struct GizmoPass {
+    camera_uniform: Uniform,
+    render_pipeline: RenderPipeline,
+}
+
+impl RenderPass for GizmoPass {
+    fn get_name() -> &'static str {
+        "Gizmo Pass"
+    }
+
+    fn get_init_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::init))
+    }
+
+    fn get_render_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::render))
+    }
+}
+
+impl GizmoPass {
+    fn init(mut commands: Commands, render_context: Res<RenderContext>) {
+        let camera_uniform = Uniform::new::<CameraMatrix>(render_context.as_ref(), 0);
+
+        let shader = render_context
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shape Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shape.wgsl").into()),
+            });
+
+        let render_pipeline_layout =
+            render_context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Shape Pipeline Layout"),
+                    bind_group_layouts: &[&camera_uniform.bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        let render_pipeline = Self::create_pipeline(&render_context, &render_pipeline_layout, &shader);
+
+        commands.insert_resource(Self { camera_uniform, render_pipeline });
+    }
+
+    fn render(
+        cameras: Query<&Camera>,
+        active_camera: Option<Res<ActiveCamera>>,
+        gizmos: Option<Res<Gizmos>>,
+        mut gizmo_pass: ResMut<GizmoPass>,
+        mut subpass: ResMut<Subpass>,
+        render_context: Res<RenderContext>,
+    ) {
+        let gizmos = match gizmos {
+            Some(gizmos) => gizmos,
+            None => return,
+        };
+        let vertices = gizmos.vertices();
+        if vertices.is_empty() {
+            return;
+        }
+
+        let camera = match Camera::active(&cameras, active_camera.as_deref()) {
+            Some(camera) if camera.layers.intersects(&RenderLayers::DEFAULT) => camera,
+            _ => return,
+        };
+        gizmo_pass.camera_uniform.set_buffer(render_context.as_ref(), camera.get_matrix());
+
+        let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
+            &render_context.device,
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Shape Vertex Buffer"),
+                contents: bytemuck::cast_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            },
+        );
+
+        let subpass = &mut *subpass;
+        let encoder = subpass.encoder.as_mut().unwrap();
+
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Gizmo Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &subpass.texture,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        camera.set_viewport(&mut render_pass);
+        render_pass.set_pipeline(&gizmo_pass.render_pipeline);
+        render_pass.set_bind_group(0, &gizmo_pass.camera_uniform.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.draw(0..vertices.len() as u32, 0..1);
+    }
+
+    pub(crate) fn create_pipeline(render_context: &RenderContext, layout: &wgpu::PipelineLayout, shader: &wgpu::ShaderModule) -> RenderPipeline {
+        render_context
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Shape Pipeline"),
+                layout: Some(layout),
+                vertex: wgpu::VertexState {
+                    module: shader,
+                    entry_point: "vs_main",
+                    buffers: &[ShapeVertex::desc()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: render_context.config.format,
+                        blend: Some(BlendMode::Alpha.blend_state()),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+    }
+}
@@ -0,0 +1,171 @@
+use std::f32::consts::PI;
+
+use bevy_ecs::prelude::*;
+
+use super::ShapeVertex;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShapeStyle {
+    Filled,
+    //the thickness of the outline in world units, centered on the shape's edge
+    Outlined(f32),
+}
+
+//miters longer than this many times the half thickness are cut short, so sharp corners don't spike out
+const MITER_LIMIT: f32 = 4f32;
+
+//immediate mode drawing in world space, anything drawn is shown for one frame and then cleared
+//shapes are drawn over the world in the order they were added, and under the text
+//they show up in every camera that draws the default render layer
+pub struct Gizmos {
+    vertices: Vec<ShapeVertex>,
+}
+
+impl Gizmos {
+    pub fn new() -> Self {
+        Self { vertices: Vec::new() }
+    }
+
+    pub fn init(world: &mut World) {
+        world.insert_resource(Self::new());
+    }
+
+    pub fn line(&mut self, from: [f32; 2], to: [f32; 2], thickness: f32, color: [f32; 4]) {
+        self.polyline(&[from, to], false, thickness, color);
+    }
+
+    //a line with a triangular head at the end, the head scales with the thickness
+    pub fn arrow(&mut self, from: [f32; 2], to: [f32; 2], thickness: f32, color: [f32; 4]) {
+        let delta = [to[0] - from[0], to[1] - from[1]];
+        let length = (delta[0] * delta[0] + delta[1] * delta[1]).sqrt();
+        if length <= f32::EPSILON {
+            return;
+        }
+
+        let direction = [delta[0] / length, delta[1] / length];
+        let normal = [-direction[1], direction[0]];
+        let head_length = (thickness * 4f32).min(length);
+        let head_width = thickness * 2f32;
+
+        let base = [to[0] - direction[0] * head_length, to[1] - direction[1] * head_length];
+        self.line(from, base, thickness, color);
+        self.triangle(
+            [base[0] + normal[0] * head_width, base[1] + normal[1] * head_width],
+            [base[0] - normal[0] * head_width, base[1] - normal[1] * head_width],
+            to,
+            color,
+        );
+    }
+
+    pub fn rect(&mut self, position: [f32; 2], dimensions: [f32; 2], style: ShapeStyle, color: [f32; 4]) {
+        let [x0, y0] = position;
+        let (x1, y1) = (x0 + dimensions[0], y0 + dimensions[1]);
+        self.polygon(&[[x0, y0], [x1, y0], [x1, y1], [x0, y1]], style, color);
+    }
+
+    pub fn circle(&mut self, center: [f32; 2], radius: f32, style: ShapeStyle, color: [f32; 4]) {
+        //enough segments that the circle looks round at any sensible size
+        let segments = ((radius * 32f32) as usize).clamp(16, 128);
+        let points: Vec<[f32; 2]> = (0..segments)
+            .map(|segment| {
+                let angle = segment as f32 / segments as f32 * 2f32 * PI;
+                [center[0] + angle.cos() * radius, center[1] + angle.sin() * radius]
+            })
+            .collect();
+        self.polygon(&points, style, color);
+    }
+
+    //filled polygons are fanned out from their first point, so they have to be convex
+    pub fn polygon(&mut self, points: &[[f32; 2]], style: ShapeStyle, color: [f32; 4]) {
+        match style {
+            ShapeStyle::Filled => {
+                for index in 1..points.len().saturating_sub(1) {
+                    self.triangle(points[0], points[index], points[index + 1], color);
+                }
+            }
+            ShapeStyle::Outlined(thickness) => self.polyline(points, true, thickness, color),
+        }
+    }
+
+    //connected line segments with mitered corners, closed joins the last point back to the first
+    pub fn polyline(&mut self, points: &[[f32; 2]], closed: bool, thickness: f32, color: [f32; 4]) {
+        if points.len() < 2 {
+            return;
+        }
+
+        let half = thickness / 2f32;
+        let count = points.len();
+        let segment_normal = |from: [f32; 2], to: [f32; 2]| {
+            let delta = [to[0] - from[0], to[1] - from[1]];
+            let length = (delta[0] * delta[0] + delta[1] * delta[1]).sqrt().max(f32::EPSILON);
+            [-delta[1] / length, delta[0] / length]
+        };
+
+        //the two sides of the line at every point
+        let sides: Vec<([f32; 2], [f32; 2])> = (0..count)
+            .map(|index| {
+                let point = points[index];
+                let previous = if index > 0 { Some(points[index - 1]) } else if closed { Some(points[count - 1]) } else { None };
+                let next = if index + 1 < count { Some(points[index + 1]) } else if closed { Some(points[0]) } else { None };
+
+                let offset = match (previous, next) {
+                    (Some(previous), Some(next)) => {
+                        let (n0, n1) = (segment_normal(previous, point), segment_normal(point, next));
+                        let miter = [n0[0] + n1[0], n0[1] + n1[1]];
+                        let miter_length = (miter[0] * miter[0] + miter[1] * miter[1]).sqrt();
+                        if miter_length <= f32::EPSILON {
+                            //the line doubles back on itself
+                            [n1[0] * half, n1[1] * half]
+                        } else {
+                            let miter = [miter[0] / miter_length, miter[1] / miter_length];
+                            let scale = (half / (miter[0] * n1[0] + miter[1] * n1[1])).min(half * MITER_LIMIT);
+                            [miter[0] * scale, miter[1] * scale]
+                        }
+                    }
+                    (Some(previous), None) => {
+                        let normal = segment_normal(previous, point);
+                        [normal[0] * half, normal[1] * half]
+                    }
+                    (None, Some(next)) => {
+                        let normal = segment_normal(point, next);
+                        [normal[0] * half, normal[1] * half]
+                    }
+                    (None, None) => [0f32, 0f32],
+                };
+
+                ([point[0] + offset[0], point[1] + offset[1]], [point[0] - offset[0], point[1] - offset[1]])
+            })
+            .collect();
+
+        let segments = if closed { count } else { count - 1 };
+        for index in 0..segments {
+            let (left_0, right_0) = sides[index];
+            let (left_1, right_1) = sides[(index + 1) % count];
+            self.triangle(left_0, right_0, left_1, color);
+            self.triangle(left_1, right_0, right_1, color);
+        }
+    }
+
+    pub fn triangle(&mut self, a: [f32; 2], b: [f32; 2], c: [f32; 2], color: [f32; 4]) {
+        self.vertices.extend_from_slice(&[
+            ShapeVertex { position: a, color },
+            ShapeVertex { position: b, color },
+            ShapeVertex { position: c, color },
+        ]);
+    }
+
+    pub(super) fn vertices(&self) -> &[ShapeVertex] {
+        &self.vertices
+    }
+
+    //run once every camera has drawn the frame's gizmos
+    pub fn clear(mut gizmos: ResMut<Gizmos>) {
+        gizmos.vertices.clear();
+    }
+}
+
+impl Default for Gizmos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
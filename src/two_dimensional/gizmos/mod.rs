@@ -0,0 +1,8 @@
+mod gizmos;
+pub use gizmos::{Gizmos, ShapeStyle};
+
+mod gizmo_pass;
+pub use gizmo_pass::GizmoPass;
+
+mod shape_vertex;
+pub(crate) use shape_vertex::ShapeVertex;
@@ -0,0 +1,160 @@
+use bevy_ecs::{prelude::*, system::Command};
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+#[derive(Component, Default, Debug)]
+pub struct Children(Vec<Entity>);
+
+impl Children {
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.0.iter()
+    }
+}
+
+//attaches child to parent, detaching it from whatever parent it had before
+//false when parent is the child or one of its descendants, that would make a loop, and nothing is changed
+pub fn add_child(world: &mut World, parent: Entity, child: Entity) -> bool {
+    if is_descendant_or_self(world, parent, child) {
+        return false;
+    }
+    remove_from_parent(world, child);
+
+    let mut parent_entity = world.get_entity_mut(parent).expect("Trying to add a child to a parent that doesn't exist");
+    if let Some(mut children) = parent_entity.get_mut::<Children>() {
+        children.0.push(child);
+    } else {
+        parent_entity.insert(Children(vec![child]));
+    }
+
+    world.get_entity_mut(child).expect("Trying to add a child that doesn't exist").insert(Parent(parent));
+    true
+}
+
+fn is_descendant_or_self(world: &World, entity: Entity, ancestor: Entity) -> bool {
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        if entity == ancestor {
+            return true;
+        }
+        current = world.get::<Parent>(entity).map(|parent| parent.0);
+    }
+    false
+}
+
+//despawns entity and every one of its descendants
+pub fn despawn_recursive(world: &mut World, entity: Entity) {
+    remove_from_parent(world, entity);
+    despawn_with_children(world, entity);
+}
+
+fn despawn_with_children(world: &mut World, entity: Entity) {
+    let children = world
+        .get_entity_mut(entity)
+        .and_then(|mut entity| entity.remove::<Children>());
+
+    if let Some(children) = children {
+        for child in children.0 {
+            despawn_with_children(world, child);
+        }
+    }
+
+    world.despawn(entity);
+}
+
+fn remove_from_parent(world: &mut World, child: Entity) {
+    let parent = world
+        .get_entity_mut(child)
+        .and_then(|mut child| child.remove::<Parent>());
+
+    if let Some(Parent(parent)) = parent {
+        if let Some(mut parent_entity) = world.get_entity_mut(parent) {
+            if let Some(mut children) = parent_entity.get_mut::<Children>() {
+                children.0.retain(|sibling| *sibling != child);
+            }
+        }
+    }
+}
+
+//command versions of the above so systems can edit the hierarchy
+pub struct AddChild {
+    pub parent: Entity,
+    pub child: Entity,
+}
+
+impl Command for AddChild {
+    fn write(self, world: &mut World) {
+        if !add_child(world, self.parent, self.child) {
+            eprintln!("Not adding {:?} under {:?}, that's one of its own descendants", self.child, self.parent);
+        }
+    }
+}
+
+pub struct DespawnRecursive {
+    pub entity: Entity,
+}
+
+impl Command for DespawnRecursive {
+    fn write(self, world: &mut World) {
+        despawn_recursive(world, self.entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_child_links_both_ways() {
+        let mut world = World::new();
+        let parent = world.spawn().id();
+        let child = world.spawn().id();
+
+        assert!(add_child(&mut world, parent, child));
+        assert_eq!(world.get::<Parent>(child), Some(&Parent(parent)));
+        assert_eq!(world.get::<Children>(parent).unwrap().iter().copied().collect::<Vec<_>>(), vec![child]);
+    }
+
+    #[test]
+    fn add_child_rejects_loops() {
+        let mut world = World::new();
+        let root = world.spawn().id();
+        let middle = world.spawn().id();
+        let leaf = world.spawn().id();
+        add_child(&mut world, root, middle);
+        add_child(&mut world, middle, leaf);
+
+        assert!(!add_child(&mut world, leaf, root));
+        assert!(!add_child(&mut world, root, root));
+        assert_eq!(world.get::<Parent>(root), None);
+        assert_eq!(world.get::<Parent>(middle), Some(&Parent(root)));
+    }
+
+    #[test]
+    fn reparenting_leaves_the_old_parent() {
+        let mut world = World::new();
+        let first = world.spawn().id();
+        let second = world.spawn().id();
+        let child = world.spawn().id();
+        add_child(&mut world, first, child);
+
+        assert!(add_child(&mut world, second, child));
+        assert_eq!(world.get::<Children>(first).unwrap().iter().count(), 0);
+        assert_eq!(world.get::<Parent>(child), Some(&Parent(second)));
+    }
+
+    #[test]
+    fn despawn_recursive_takes_descendants() {
+        let mut world = World::new();
+        let root = world.spawn().id();
+        let child = world.spawn().id();
+        let grandchild = world.spawn().id();
+        add_child(&mut world, root, child);
+        add_child(&mut world, child, grandchild);
+
+        despawn_recursive(&mut world, child);
+        assert!(world.get_entity(child).is_none());
+        assert!(world.get_entity(grandchild).is_none());
+        assert_eq!(world.get::<Children>(root).unwrap().iter().count(), 0);
+    }
+}
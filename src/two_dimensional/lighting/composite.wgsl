@@ -0,0 +1,16 @@
+//one triangle that covers the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@group(0) @binding(0)
+var t_light: texture_2d<f32>;
+
+//the blend state multiplies this with what the other passes drew
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let light = textureLoad(t_light, vec2<i32>(position.xy), 0);
+    return vec4<f32>(light.rgb, 1.0);
+}
@@ -0,0 +1,140 @@
+use bevy_ecs::prelude::*;
+
+use crate::two_dimensional::GlobalTransform2d;
+
+//the light everything gets regardless of the lights around it, white at full intensity leaves the scene unlit
+#[derive(Clone, Copy, Debug)]
+pub struct AmbientLight {
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl AmbientLight {
+    pub const DAY: AmbientLight = AmbientLight { color: [1f32, 1f32, 1f32], intensity: 1f32 };
+    pub const DUSK: AmbientLight = AmbientLight { color: [0.9f32, 0.6f32, 0.5f32], intensity: 0.6f32 };
+    pub const NIGHT: AmbientLight = AmbientLight { color: [0.35f32, 0.4f32, 0.7f32], intensity: 0.25f32 };
+
+    pub fn is_unlit(&self) -> bool {
+        self.color.iter().all(|channel| *channel * self.intensity >= 1f32)
+    }
+}
+
+impl Default for AmbientLight {
+    fn default() -> Self {
+        Self::DAY
+    }
+}
+
+//a light shining in every direction from the entity's global position
+#[derive(Component, Clone, Debug)]
+pub struct PointLight {
+    pub color: [f32; 3],
+    pub intensity: f32,
+    //in world units, the light fades out to nothing at this distance
+    pub radius: f32,
+    //how far above the scene the light sits, lower lights make normal maps stand out more
+    pub height: f32,
+    pub casts_shadows: bool,
+}
+
+impl PointLight {
+    pub fn new(color: [f32; 3], intensity: f32, radius: f32) -> Self {
+        Self {
+            color,
+            intensity,
+            radius,
+            height: 0.5f32,
+            casts_shadows: true,
+        }
+    }
+
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+}
+
+//a point light limited to a cone
+#[derive(Component, Clone, Debug)]
+pub struct SpotLight {
+    pub light: PointLight,
+    //radians, relative to the entity's rotation
+    pub direction: f32,
+    //radians from the direction to the edge of the cone
+    pub angle: f32,
+    //fraction of the cone that fades out towards the edge
+    pub softness: f32,
+}
+
+impl SpotLight {
+    pub fn new(light: PointLight, direction: f32, angle: f32) -> Self {
+        Self {
+            light,
+            direction,
+            angle,
+            softness: 0.2f32,
+        }
+    }
+
+    pub fn with_softness(mut self, softness: f32) -> Self {
+        self.softness = softness;
+        self
+    }
+}
+
+//a closed shape that blocks light, in the entity's local space
+#[derive(Component, Clone, Debug)]
+pub struct Occluder {
+    pub points: Vec<[f32; 2]>,
+}
+
+impl Occluder {
+    pub fn polygon(points: &[[f32; 2]]) -> Self {
+        Self { points: points.to_vec() }
+    }
+
+    pub fn rectangle(position: [f32; 2], dimensions: [f32; 2]) -> Self {
+        let [x0, y0] = position;
+        let (x1, y1) = (x0 + dimensions[0], y0 + dimensions[1]);
+        Self::polygon(&[[x0, y0], [x1, y0], [x1, y1], [x0, y1]])
+    }
+
+    //the shadow quads for one light, every edge facing away from the light is pushed out past the light's radius
+    //the occluder itself stays lit, so whatever sprite it belongs to isn't darkened by its own shadow
+    pub(super) fn shadow_vertices(&self, transform: &GlobalTransform2d, light: [f32; 2], radius: f32) -> Vec<[f32; 2]> {
+        let points: Vec<[f32; 2]> = self.points.iter().map(|point| transform.transform_point(*point)).collect();
+        if points.len() < 2 {
+            return Vec::new();
+        }
+
+        //far enough that the end of the shadow is never inside the light
+        let extrude = |point: [f32; 2]| {
+            let direction = [point[0] - light[0], point[1] - light[1]];
+            let length = (direction[0] * direction[0] + direction[1] * direction[1]).sqrt().max(f32::EPSILON);
+            let distance = radius * 4f32;
+            [point[0] + direction[0] / length * distance, point[1] + direction[1] / length * distance]
+        };
+
+        //the winding decides which side of an edge is outside, so the shape can be given either way around
+        let signed_area: f32 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a[0] * b[1] - b[0] * a[1])
+            .sum();
+
+        let mut vertices = Vec::new();
+        for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+            let edge = [b[0] - a[0], b[1] - a[1]];
+            let to_light = [light[0] - a[0], light[1] - a[1]];
+            //positive when the light is on the inside of the edge, which makes it a back face
+            let facing = (edge[0] * to_light[1] - edge[1] * to_light[0]) * signed_area;
+            if facing <= 0f32 {
+                continue;
+            }
+
+            let (far_a, far_b) = (extrude(*a), extrude(*b));
+            vertices.extend_from_slice(&[*a, *b, far_a, far_a, *b, far_b]);
+        }
+        vertices
+    }
+}
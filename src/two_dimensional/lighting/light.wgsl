@@ -0,0 +1,73 @@
+struct CameraUniform {
+    view_ortho: mat4x4<f32>
+}
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+
+struct LightInstance {
+    @location(1) position: vec2<f32>,
+    @location(2) radius: f32,
+    @location(3) height: f32,
+    //already multiplied by the intensity
+    @location(4) color: vec4<f32>,
+    @location(5) direction: vec2<f32>,
+    //cosines of the outer and inner edges of a spot light's cone, point lights have an outer edge below -1
+    @location(6) cone: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec2<f32>,
+    @location(1) light_position: vec2<f32>,
+    @location(2) radius: f32,
+    @location(3) height: f32,
+    @location(4) color: vec4<f32>,
+    @location(5) direction: vec2<f32>,
+    @location(6) cone: vec2<f32>,
+};
+
+//every light is drawn as a square just covering its radius
+@vertex
+fn vs_main(@location(0) corner: vec2<f32>, light: LightInstance) -> VertexOutput {
+    let world_position = light.position + corner * light.radius;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_ortho * vec4<f32>(world_position, 0.0, 1.0);
+    out.world_position = world_position;
+    out.light_position = light.position;
+    out.radius = light.radius;
+    out.height = light.height;
+    out.color = light.color;
+    out.direction = light.direction;
+    out.cone = light.cone;
+    return out;
+}
+
+@group(1) @binding(0)
+var t_normal: texture_2d<f32>;
+@group(1) @binding(1)
+var t_shadow: texture_2d<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(in.clip_position.xy);
+
+    let to_light = in.light_position - in.world_position;
+    let light_distance = length(to_light);
+    let falloff = clamp(1.0 - light_distance / in.radius, 0.0, 1.0);
+    var attenuation = falloff * falloff;
+
+    if (in.cone.x >= -1.0) {
+        let along = dot(normalize(-to_light), in.direction);
+        attenuation = attenuation * smoothstep(in.cone.x, in.cone.y, along);
+    }
+
+    let normal = textureLoad(t_normal, pixel, 0).xyz * 2.0 - 1.0;
+    let light_direction = normalize(vec3<f32>(to_light, in.height));
+    let diffuse = max(dot(normalize(normal), light_direction), 0.0);
+
+    let shadow = textureLoad(t_shadow, pixel, 0).r;
+
+    return vec4<f32>(in.color.rgb * attenuation * diffuse * shadow, 1.0);
+}
@@ -0,0 +1,633 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use itertools::Itertools;
+use wgpu::{RenderPassDescriptor, RenderPipeline};
+
+use crate::{
+    graphics::{RenderContext, RenderPass, Subpass, Texture, TextureBindLayout, Uniform},
+    two_dimensional::{
+        camera::{Camera, CameraMatrix},
+        sprite::Sprite,
+        ActiveCamera, GlobalTransform2d, RenderLayers,
+    },
+};
+
+use super::{AmbientLight, Occluder, PointLight, SpotLight};
+
+const LIGHT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+const NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+
+//a flat normal facing the camera, packed into 0..1
+const FLAT_NORMAL: wgpu::Color = wgpu::Color { r: 0.5, g: 0.5, b: 1.0, a: 1.0 };
+
+const QUAD: [[f32; 2]; 6] = [[-1.0, -1.0], [1.0, -1.0], [-1.0, 1.0], [-1.0, 1.0], [1.0, -1.0], [1.0, 1.0]];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct NormalVertex {
+    position: [f32; 2],
+    tex_coord: [f32; 2],
+    basis: [f32; 4],
+}
+
+impl NormalVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<NormalVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+unsafe impl bytemuck::Pod for NormalVertex {}
+unsafe impl bytemuck::Zeroable for NormalVertex {}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct LightInstance {
+    position: [f32; 2],
+    radius: f32,
+    height: f32,
+    color: [f32; 4],
+    direction: [f32; 2],
+    cone: [f32; 2],
+}
+
+impl LightInstance {
+    const ATTRIBS: [wgpu::VertexAttribute; 6] =
+        wgpu::vertex_attr_array![1 => Float32x2, 2 => Float32, 3 => Float32, 4 => Float32x4, 5 => Float32x2, 6 => Float32x2];
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<LightInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+
+    fn new(light: &PointLight, position: [f32; 2]) -> Self {
+        Self {
+            position,
+            radius: light.radius.max(f32::EPSILON),
+            height: light.height,
+            color: [light.color[0] * light.intensity, light.color[1] * light.intensity, light.color[2] * light.intensity, 1f32],
+            direction: [1f32, 0f32],
+            cone: [-2f32, -2f32],
+        }
+    }
+}
+
+unsafe impl bytemuck::Pod for LightInstance {}
+unsafe impl bytemuck::Zeroable for LightInstance {}
+
+fn position_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+    const ATTRIBS: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![0 => Float32x2];
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &ATTRIBS,
+    }
+}
+
+//the screen sized textures lighting renders into, rebuilt when the window changes size
+struct LightTargets {
+    size: (u32, u32),
+    light_view: wgpu::TextureView,
+    normal_view: wgpu::TextureView,
+    shadow_view: wgpu::TextureView,
+    //normals and the shadow mask, read by every light
+    light_bind_group: wgpu::BindGroup,
+    //the accumulated light, read by the composite
+    composite_bind_group: wgpu::BindGroup,
+}
+
+//lights the scene drawn by the passes before it
+//light from every light is added up in a screen sized texture starting at the ambient light, that texture is then multiplied over the scene
+//each light first cuts the shadows of the occluders out of a mask, normals come from the sprites' normal maps or are flat
+pub struct LightingPass {
+    camera_uniform: Uniform,
+    normal_pipeline: RenderPipeline,
+    shadow_pipeline: RenderPipeline,
+    light_pipeline: RenderPipeline,
+    composite_pipeline: RenderPipeline,
+
+    normal_bind_layout: TextureBindLayout,
+    light_bind_layout: wgpu::BindGroupLayout,
+    composite_bind_layout: wgpu::BindGroupLayout,
+    quad_buffer: wgpu::Buffer,
+
+    normal_maps: HashMap<String, (Texture, wgpu::BindGroup)>,
+    targets: Option<LightTargets>,
+}
+
+impl RenderPass for LightingPass {
+    fn get_name() -> &'static str {
+        "Lighting Pass"
+    }
+
+    fn get_init_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::init))
+    }
+
+    fn get_render_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::render))
+    }
+}
+
+impl LightingPass {
+    fn init(mut commands: Commands, render_context: Res<RenderContext>) {
+        let camera_uniform = Uniform::new::<CameraMatrix>(render_context.as_ref(), 0);
+        let normal_bind_layout = TextureBindLayout::new(0, 1, &render_context);
+        let light_bind_layout = Self::create_target_bind_layout(&render_context, &[0, 1], "Light Bind Group Layout");
+        let composite_bind_layout = Self::create_target_bind_layout(&render_context, &[0], "Composite Bind Group Layout");
+
+        let normal_pipeline = Self::create_pipeline(
+            &render_context,
+            "Normal",
+            include_str!("normal.wgsl"),
+            &[&camera_uniform.bind_group_layout, normal_bind_layout.bind_group_layout()],
+            &[NormalVertex::desc()],
+            NORMAL_FORMAT,
+            None,
+        );
+
+        let shadow_pipeline = Self::create_pipeline(
+            &render_context,
+            "Shadow",
+            include_str!("shadow.wgsl"),
+            &[&camera_uniform.bind_group_layout],
+            &[position_desc()],
+            SHADOW_FORMAT,
+            None,
+        );
+
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let light_pipeline = Self::create_pipeline(
+            &render_context,
+            "Light",
+            include_str!("light.wgsl"),
+            &[&camera_uniform.bind_group_layout, &light_bind_layout],
+            &[position_desc(), LightInstance::desc()],
+            LIGHT_FORMAT,
+            Some(wgpu::BlendState { color: additive, alpha: additive }),
+        );
+
+        //scene * light, leaving the scene's alpha alone
+        let composite_pipeline = Self::create_pipeline(
+            &render_context,
+            "Light Composite",
+            include_str!("composite.wgsl"),
+            &[&composite_bind_layout],
+            &[],
+            render_context.config.format,
+            Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Dst,
+                    dst_factor: wgpu::BlendFactor::Zero,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Zero,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            }),
+        );
+
+        let quad_buffer = wgpu::util::DeviceExt::create_buffer_init(
+            &render_context.device,
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Light Quad Buffer"),
+                contents: bytemuck::cast_slice(&QUAD),
+                usage: wgpu::BufferUsages::VERTEX,
+            },
+        );
+
+        commands.insert_resource(Self {
+            camera_uniform,
+            normal_pipeline,
+            shadow_pipeline,
+            light_pipeline,
+            composite_pipeline,
+
+            normal_bind_layout,
+            light_bind_layout,
+            composite_bind_layout,
+            quad_buffer,
+
+            normal_maps: HashMap::new(),
+            targets: None,
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render(
+        sprites: Query<(&Sprite, Option<&GlobalTransform2d>, Option<&RenderLayers>)>,
+        point_lights: Query<(&PointLight, Option<&GlobalTransform2d>, Option<&RenderLayers>)>,
+        spot_lights: Query<(&SpotLight, Option<&GlobalTransform2d>, Option<&RenderLayers>)>,
+        occluders: Query<(&Occluder, Option<&GlobalTransform2d>, Option<&RenderLayers>)>,
+        cameras: Query<&Camera>,
+        active_camera: Option<Res<ActiveCamera>>,
+        ambient_light: Option<Res<AmbientLight>>,
+        mut lighting_pass: ResMut<LightingPass>,
+        mut subpass: ResMut<Subpass>,
+        render_context: Res<RenderContext>,
+    ) {
+        let lighting_pass = &mut *lighting_pass;
+        let identity = GlobalTransform2d::default();
+
+        //cameras without lighting, like a hud, are left as they are
+        let camera = match Camera::active(&cameras, active_camera.as_deref()) {
+            Some(camera) if camera.lighting => camera,
+            _ => return,
+        };
+
+        let default_ambient = AmbientLight::default();
+        let ambient_light = ambient_light.as_deref().unwrap_or(&default_ambient);
+
+        //(light, whether it casts shadows)
+        let mut lights: Vec<(LightInstance, bool)> = Vec::new();
+        for (light, transform, _) in point_lights.iter().filter(|(.., layers)| camera.sees(*layers)) {
+            let position = transform.unwrap_or(&identity).translation();
+            lights.push((LightInstance::new(light, position), light.casts_shadows));
+        }
+        for (spot_light, transform, _) in spot_lights.iter().filter(|(.., layers)| camera.sees(*layers)) {
+            let transform = transform.unwrap_or(&identity);
+            let matrix = transform.matrix();
+            let direction = spot_light.direction + matrix.x.y.atan2(matrix.x.x);
+            let outer = spot_light.angle.cos();
+            let inner = (spot_light.angle * (1f32 - spot_light.softness.clamp(0f32, 1f32))).cos();
+
+            let mut instance = LightInstance::new(&spot_light.light, transform.translation());
+            instance.direction = [direction.cos(), direction.sin()];
+            //keep the edges apart, smoothstep is undefined when they meet
+            instance.cone = [outer, inner.max(outer + 0.0001f32)];
+            lights.push((instance, spot_light.light.casts_shadows));
+        }
+
+        //nothing to light, leave the scene as it is
+        if lights.is_empty() && ambient_light.is_unlit() {
+            return;
+        }
+
+        lighting_pass.camera_uniform.set_buffer(render_context.as_ref(), camera.get_matrix());
+
+        let size = (render_context.config.width, render_context.config.height);
+        if lighting_pass.targets.as_ref().map(|targets| targets.size) != Some(size) {
+            lighting_pass.targets = Some(lighting_pass.create_targets(&render_context, size));
+        }
+
+        //normals of every normal mapped sprite, back to front like the sprite pass
+        let mut normal_batches: Vec<(String, wgpu::Buffer, u32)> = Vec::new();
+        let normal_sprites = sprites
+            .iter()
+            .filter(|(sprite, _, layers)| sprite.normal_map_path().is_some() && camera.sees(*layers))
+            .map(|(sprite, transform, _)| (sprite, transform))
+            .sorted_by(|(sprite_1, _), (sprite_2, _)| Ord::cmp(&sprite_2.depth, &sprite_1.depth));
+        for (sprite, transform) in normal_sprites {
+            let path = sprite.normal_map_path().as_ref().unwrap();
+            if !lighting_pass.normal_maps.contains_key(path) {
+                let texture = Texture::load_linear(path, &render_context);
+                let bind_group = lighting_pass.normal_bind_layout.create_bind_group(&texture, &render_context);
+                lighting_pass.normal_maps.insert(path.clone(), (texture, bind_group));
+            }
+
+            let transform = transform.unwrap_or(&identity);
+            let matrix = transform.matrix();
+            let normalize = |x: f32, y: f32| {
+                let length = (x * x + y * y).sqrt().max(f32::EPSILON);
+                [x / length, y / length]
+            };
+            let [tx, ty] = normalize(matrix.x.x, matrix.x.y);
+            let [bx, by] = normalize(matrix.y.x, matrix.y.y);
+
+            //laid out like the sprite's texture, so it stands in for its size
+            let (normal_map, _) = &lighting_pass.normal_maps[path];
+            let vertices: Vec<NormalVertex> = sprite
+                .get_vertex_buffer(transform, (normal_map.width, normal_map.height))
+                .iter()
+                .map(|vertex| NormalVertex {
+                    position: vertex.position,
+                    tex_coord: vertex.tex_coord,
+                    basis: [tx, ty, bx, by],
+                })
+                .collect();
+
+            let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
+                &render_context.device,
+                &wgpu::util::BufferInitDescriptor {
+                    label: Some("Normal Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                },
+            );
+            normal_batches.push((path.clone(), vertex_buffer, vertices.len() as u32));
+        }
+
+        //the shadows of every occluder, one buffer per shadow casting light
+        let occluders: Vec<(&Occluder, &GlobalTransform2d)> = occluders
+            .iter()
+            .filter(|(.., layers)| camera.sees(*layers))
+            .map(|(occluder, transform, _)| (occluder, transform.unwrap_or(&identity)))
+            .collect();
+        let shadow_buffers: Vec<Option<(wgpu::Buffer, u32)>> = lights
+            .iter()
+            .map(|(light, casts_shadows)| {
+                if !casts_shadows {
+                    return None;
+                }
+
+                let vertices: Vec<[f32; 2]> = occluders
+                    .iter()
+                    .flat_map(|(occluder, transform)| occluder.shadow_vertices(transform, light.position, light.radius))
+                    .collect();
+                if vertices.is_empty() {
+                    return None;
+                }
+
+                let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
+                    &render_context.device,
+                    &wgpu::util::BufferInitDescriptor {
+                        label: Some("Shadow Vertex Buffer"),
+                        contents: bytemuck::cast_slice(&vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    },
+                );
+                Some((vertex_buffer, vertices.len() as u32))
+            })
+            .collect();
+
+        let instances: Vec<LightInstance> = lights.iter().map(|(light, _)| *light).collect();
+        //wgpu doesn't like empty buffers, the padding is never drawn
+        let contents: &[u8] = if instances.is_empty() { bytemuck::cast_slice(&QUAD) } else { bytemuck::cast_slice(&instances) };
+        let light_buffer = wgpu::util::DeviceExt::create_buffer_init(
+            &render_context.device,
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Light Instance Buffer"),
+                contents,
+                usage: wgpu::BufferUsages::VERTEX,
+            },
+        );
+
+        let targets = lighting_pass.targets.as_ref().unwrap();
+        let subpass = &mut *subpass;
+        let encoder = subpass.encoder.as_mut().unwrap();
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("Normal Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &targets.normal_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(FLAT_NORMAL),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            camera.set_viewport(&mut render_pass);
+            render_pass.set_pipeline(&lighting_pass.normal_pipeline);
+            render_pass.set_bind_group(0, &lighting_pass.camera_uniform.bind_group, &[]);
+            for (path, vertex_buffer, num_vertices) in normal_batches.iter() {
+                render_pass.set_bind_group(1, &lighting_pass.normal_maps[path].1, &[]);
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.draw(0..*num_vertices, 0..1);
+            }
+        }
+
+        //start from the ambient light
+        let ambient = ambient_light.color.map(|channel| (channel * ambient_light.intensity) as f64);
+        encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Ambient Light Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &targets.light_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color { r: ambient[0], g: ambient[1], b: ambient[2], a: 1.0 }),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        for (index, shadow_buffer) in shadow_buffers.iter().enumerate() {
+            {
+                let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                    label: Some("Shadow Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &targets.shadow_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+
+                if let Some((vertex_buffer, num_vertices)) = shadow_buffer {
+                    camera.set_viewport(&mut render_pass);
+                    render_pass.set_pipeline(&lighting_pass.shadow_pipeline);
+                    render_pass.set_bind_group(0, &lighting_pass.camera_uniform.bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.draw(0..*num_vertices, 0..1);
+                }
+            }
+
+            let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("Light Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &targets.light_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            camera.set_viewport(&mut render_pass);
+            render_pass.set_pipeline(&lighting_pass.light_pipeline);
+            render_pass.set_bind_group(0, &lighting_pass.camera_uniform.bind_group, &[]);
+            render_pass.set_bind_group(1, &targets.light_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, lighting_pass.quad_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, light_buffer.slice(..));
+            let index = index as u32;
+            render_pass.draw(0..QUAD.len() as u32, index..index + 1);
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Light Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &subpass.texture,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        //the targets are window sized and drawn to at the viewport, so the composite reads them at window pixels too
+        camera.set_viewport(&mut render_pass);
+        render_pass.set_pipeline(&lighting_pass.composite_pipeline);
+        render_pass.set_bind_group(0, &targets.composite_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    fn create_targets(&self, render_context: &RenderContext, size: (u32, u32)) -> LightTargets {
+        let create_view = |format: wgpu::TextureFormat, label: &str| {
+            render_context
+                .device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: size.0.max(1),
+                        height: size.1.max(1),
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        let light_view = create_view(LIGHT_FORMAT, "Light Texture");
+        let normal_view = create_view(NORMAL_FORMAT, "Normal Texture");
+        let shadow_view = create_view(SHADOW_FORMAT, "Shadow Mask Texture");
+
+        let light_bind_group = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Light Bind Group"),
+            layout: &self.light_bind_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&normal_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&shadow_view),
+                },
+            ],
+        });
+
+        let composite_bind_group = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Composite Bind Group"),
+            layout: &self.composite_bind_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&light_view),
+            }],
+        });
+
+        LightTargets {
+            size,
+            light_view,
+            normal_view,
+            shadow_view,
+            light_bind_group,
+            composite_bind_group,
+        }
+    }
+
+    //screen sized textures are read with textureLoad, one pixel to one pixel, so they don't need samplers
+    fn create_target_bind_layout(render_context: &RenderContext, bindings: &[u32], label: &str) -> wgpu::BindGroupLayout {
+        let entries: Vec<wgpu::BindGroupLayoutEntry> = bindings
+            .iter()
+            .map(|binding| wgpu::BindGroupLayoutEntry {
+                binding: *binding,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                },
+                count: None,
+            })
+            .collect();
+
+        render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(label),
+            entries: &entries,
+        })
+    }
+
+    fn create_pipeline(
+        render_context: &RenderContext,
+        label: &str,
+        source: &str,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        buffers: &[wgpu::VertexBufferLayout],
+        format: wgpu::TextureFormat,
+        blend: Option<wgpu::BlendState>,
+    ) -> RenderPipeline {
+        let shader = render_context
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(&format!("{} Shader", label)),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            });
+
+        let layout = render_context
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(&format!("{} Pipeline Layout", label)),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+
+        render_context
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(&format!("{} Pipeline", label)),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers,
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+    }
+}
@@ -0,0 +1,5 @@
+mod light;
+pub use light::{AmbientLight, Occluder, PointLight, SpotLight};
+
+mod lighting_pass;
+pub use lighting_pass::LightingPass;
@@ -0,0 +1,46 @@
+struct CameraUniform {
+    view_ortho: mat4x4<f32>
+}
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coord: vec2<f32>,
+    //world directions of the sprite's local x and y axes
+    @location(2) basis: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+    @location(1) basis: vec4<f32>,
+};
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coord = vertex.tex_coord;
+    out.basis = vertex.basis;
+    out.clip_position = camera.view_ortho * vec4<f32>(vertex.position, 0.0, 1.0);
+    return out;
+}
+
+@group(1) @binding(0)
+var t_normal: texture_2d<f32>;
+@group(1) @binding(1)
+var s_normal: sampler;
+
+//rotates the sampled normal into world space and packs it back into 0..1
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = textureSample(t_normal, s_normal, in.tex_coord);
+    if (texel.a < 0.5) {
+        discard;
+    }
+
+    let local = texel.xyz * 2.0 - 1.0;
+    let world = vec3<f32>(local.x * in.basis.xy + local.y * in.basis.zw, local.z);
+    return vec4<f32>(normalize(world) * 0.5 + 0.5, 1.0);
+}
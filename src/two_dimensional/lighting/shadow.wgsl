@@ -0,0 +1,17 @@
+struct CameraUniform {
+    view_ortho: mat4x4<f32>
+}
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>) -> @builtin(position) vec4<f32> {
+    return camera.view_ortho * vec4<f32>(position, 0.0, 1.0);
+}
+
+//the mask starts out white, shadows are cut out of it
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+}
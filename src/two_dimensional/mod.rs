@@ -1,5 +1,25 @@
 pub mod text;
 pub mod sprite;
+pub mod tilemap;
+pub mod particles;
+pub mod lighting;
+pub mod gizmos;
+pub mod path;
+
+mod transform;
+pub use transform::{GlobalTransform2d, Transform2d};
+
+mod hierarchy;
+pub use hierarchy::{add_child, despawn_recursive, AddChild, DespawnRecursive, Parent};
 
 mod camera;
-pub use camera::{Camera as Camera2d, CameraController as CameraController2dPan};
\ No newline at end of file
+pub use camera::{ActiveCamera, Camera as Camera2d, CameraController as CameraController2dPan};
+
+mod camera_behaviours;
+pub use camera_behaviours::{CameraBounds, CameraFollow, CameraShake, CameraTransition};
+
+mod render_layers;
+pub use render_layers::RenderLayers;
+
+mod picking;
+pub use picking::{Pickable, Picking, PickingEvent};
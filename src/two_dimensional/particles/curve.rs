@@ -0,0 +1,90 @@
+pub trait Lerp: Copy {
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for [f32; 4] {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        [
+            self[0].lerp(other[0], t),
+            self[1].lerp(other[1], t),
+            self[2].lerp(other[2], t),
+            self[3].lerp(other[3], t),
+        ]
+    }
+}
+
+//keyframes over 0..1, sampled linearly, anything before the first or after the last key holds that key
+#[derive(Clone, Debug)]
+pub struct Curve<T: Lerp> {
+    keys: Vec<(f32, T)>,
+}
+
+impl<T: Lerp> Curve<T> {
+    pub fn constant(value: T) -> Self {
+        Self {
+            keys: vec![(0f32, value)],
+        }
+    }
+
+    pub fn linear(from: T, to: T) -> Self {
+        Self {
+            keys: vec![(0f32, from), (1f32, to)],
+        }
+    }
+
+    //keys don't have to be added in order
+    pub fn with_key(mut self, t: f32, value: T) -> Self {
+        let index = self.keys.partition_point(|(key, _)| *key <= t);
+        self.keys.insert(index, (t, value));
+        self
+    }
+
+    pub fn sample(&self, t: f32) -> T {
+        let next = self.keys.partition_point(|(key, _)| *key <= t);
+        if next == 0 {
+            return self.keys[0].1;
+        }
+        if next == self.keys.len() {
+            return self.keys[next - 1].1;
+        }
+
+        let (start, from) = self.keys[next - 1];
+        let (end, to) = self.keys[next];
+        from.lerp(to, (t - start) / (end - start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_hits_the_end_keys() {
+        let curve = Curve::linear(2f32, 4f32);
+        assert_eq!(curve.sample(0f32), 2f32);
+        assert_eq!(curve.sample(1f32), 4f32);
+        assert_eq!(curve.sample(0.5f32), 3f32);
+    }
+
+    #[test]
+    fn sample_holds_outside_the_keys() {
+        let curve = Curve::constant(1f32).with_key(0.5f32, 3f32);
+        assert_eq!(curve.sample(-1f32), 1f32);
+        assert_eq!(curve.sample(0.25f32), 2f32);
+        assert_eq!(curve.sample(0.5f32), 3f32);
+        assert_eq!(curve.sample(2f32), 3f32);
+    }
+
+    #[test]
+    fn keys_added_out_of_order_are_sorted() {
+        let curve = Curve::constant([0f32; 4]).with_key(1f32, [1f32; 4]).with_key(0.5f32, [0.8f32; 4]);
+        assert_eq!(curve.sample(0.5f32), [0.8f32; 4]);
+        assert_eq!(curve.sample(0.75f32), [0.9f32; 4]);
+    }
+}
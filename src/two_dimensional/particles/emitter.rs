@@ -0,0 +1,415 @@
+use std::f32::consts::PI;
+
+use bevy_ecs::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    app::FrameTime,
+    two_dimensional::{sprite::{BlendMode, TileView}, DespawnRecursive, GlobalTransform2d},
+};
+
+use super::Curve;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameMode {
+    //every particle picks one frame when it spawns
+    Random,
+    //every particle steps through the frames over its lifetime
+    OverLifetime,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Burst {
+    //seconds after the emitter started
+    pub time: f32,
+    pub count: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Particle {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub rotation: f32,
+    pub angular_velocity: f32,
+    pub age: f32,
+    pub lifetime: f32,
+    pub frame: usize,
+    pub tint: [f32; 4],
+    //how much the emitter was scaled by when this spawned, multiplied into the size curve
+    pub scale: f32,
+}
+
+impl Particle {
+    pub fn life(&self) -> f32 {
+        (self.age / self.lifetime).min(1f32)
+    }
+}
+
+//where every emitter's randomness comes from, seeded so a replay of a recording throws the same particles
+pub struct ParticleRng(StdRng);
+
+impl ParticleRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+//spawns and simulates particles in world space, through the emitter's global transform
+//positions, directions and sizes are local to the emitter and rotated and scaled with it as particles spawn
+//once out, particles don't follow the emitter and gravity always pulls in world space
+#[derive(Component, Clone)]
+pub struct ParticleEmitter {
+    //particles per second
+    pub spawn_rate: f32,
+    pub bursts: Vec<Burst>,
+    //seconds, how long the emitter keeps emitting, forever if none
+    pub duration: Option<f32>,
+    pub max_particles: usize,
+
+    //in seconds, picked between min and max
+    pub lifetime: (f32, f32),
+    //direction in radians and speed in world units per second, picked between min and max
+    pub direction: (f32, f32),
+    pub speed: (f32, f32),
+    //radians per second
+    pub angular_velocity: (f32, f32),
+    //world units from the emitter, particles spawn somewhere in this radius
+    pub spawn_radius: f32,
+    pub gravity: [f32; 2],
+    //fraction of velocity lost per second
+    pub drag: f32,
+
+    //sampled over each particle's life, 0 at spawn and 1 at death
+    pub color: Curve<[f32; 4]>,
+    //each particle picks one of these when it spawns and multiplies it into the color curve
+    pub tints: Vec<[f32; 4]>,
+    //the width and height of a particle in world units
+    pub size: Curve<f32>,
+
+    pub blend_mode: BlendMode,
+    //only orders emitters among themselves, particles are drawn over every sprite whatever its depth
+    pub depth: u32,
+    //despawn the emitter's entity once it has stopped emitting and every particle is dead
+    pub despawn_when_finished: bool,
+
+    texture_path: Option<String>,
+    //uv regions, u0, v0, u1, v1
+    frames: Vec<[f32; 4]>,
+    frame_mode: FrameMode,
+
+    emitting: bool,
+    elapsed: f32,
+    spawn_accumulator: f32,
+    next_burst: usize,
+    particles: Vec<Particle>,
+}
+
+impl ParticleEmitter {
+    pub fn new() -> Self {
+        Self {
+            spawn_rate: 10f32,
+            bursts: Vec::new(),
+            duration: None,
+            max_particles: 1000,
+
+            lifetime: (1f32, 1f32),
+            direction: (0f32, 2f32 * PI),
+            speed: (1f32, 1f32),
+            angular_velocity: (0f32, 0f32),
+            spawn_radius: 0f32,
+            gravity: [0f32, 0f32],
+            drag: 0f32,
+
+            color: Curve::constant([1f32, 1f32, 1f32, 1f32]),
+            tints: Vec::new(),
+            size: Curve::constant(0.1f32),
+
+            blend_mode: BlendMode::Alpha,
+            depth: 0,
+            despawn_when_finished: false,
+
+            texture_path: None,
+            frames: vec![[0f32, 0f32, 1f32, 1f32]],
+            frame_mode: FrameMode::Random,
+
+            emitting: true,
+            elapsed: 0f32,
+            spawn_accumulator: 0f32,
+            next_burst: 0,
+            particles: Vec::new(),
+        }
+    }
+
+    pub fn with_texture(mut self, path: &str) -> Self {
+        self.texture_path = Some(String::from(path));
+        self.frames = vec![[0f32, 0f32, 1f32, 1f32]];
+        self
+    }
+
+    //use cells of a sheet as the particle's frames, like TileView does for sprites
+    pub fn with_tile_frames(mut self, path: &str, rows: u32, cols: u32, cells: &[(u32, u32)], frame_mode: FrameMode) -> Self {
+        let mut tile_view = TileView::new(path, rows, cols, 0, 0);
+        self.frames = cells
+            .iter()
+            .map(|(row, col)| {
+                tile_view.set_position(*row, *col);
+                tile_view.region()
+            })
+            .collect();
+        self.texture_path = Some(String::from(path));
+        self.frame_mode = frame_mode;
+        self
+    }
+
+    pub fn with_burst(mut self, time: f32, count: u32) -> Self {
+        self.bursts.push(Burst { time, count });
+        self.bursts.sort_by(|a, b| a.time.total_cmp(&b.time));
+        self
+    }
+
+    pub fn texture_path(&self) -> &Option<String> {
+        &self.texture_path
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.emitting && self.particles.is_empty()
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub(super) fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub(super) fn particle_color(&self, particle: &Particle) -> [f32; 4] {
+        let color = self.color.sample(particle.life());
+        [
+            color[0] * particle.tint[0],
+            color[1] * particle.tint[1],
+            color[2] * particle.tint[2],
+            color[3] * particle.tint[3],
+        ]
+    }
+
+    pub(super) fn frame_region(&self, particle: &Particle) -> [f32; 4] {
+        match self.frame_mode {
+            FrameMode::Random => self.frames[particle.frame],
+            FrameMode::OverLifetime => {
+                let frame = (particle.life() * self.frames.len() as f32) as usize;
+                self.frames[frame.min(self.frames.len() - 1)]
+            }
+        }
+    }
+
+    pub fn update(
+        mut commands: Commands,
+        mut emitters: Query<(Entity, &mut ParticleEmitter, Option<&GlobalTransform2d>)>,
+        frame_time: Option<Res<FrameTime>>,
+        mut rng: ResMut<ParticleRng>,
+    ) {
+        let dt = match frame_time {
+            Some(frame_time) => frame_time.0.as_secs_f32(),
+            None => return,
+        };
+
+        for (entity, mut emitter, transform) in emitters.iter_mut() {
+            emitter.simulate(dt, &transform.copied().unwrap_or_default(), &mut rng.0);
+
+            if emitter.despawn_when_finished && emitter.is_finished() {
+                commands.add(DespawnRecursive { entity });
+            }
+        }
+    }
+
+    fn simulate(&mut self, dt: f32, transform: &GlobalTransform2d, rng: &mut impl Rng) {
+        //age and move what we already have
+        let (gravity, drag) = (self.gravity, self.drag);
+        self.particles.retain_mut(|particle| {
+            particle.age += dt;
+            if particle.age >= particle.lifetime {
+                return false;
+            }
+
+            let damping = (1f32 - drag * dt).max(0f32);
+            particle.velocity = [
+                (particle.velocity[0] + gravity[0] * dt) * damping,
+                (particle.velocity[1] + gravity[1] * dt) * damping,
+            ];
+            particle.position[0] += particle.velocity[0] * dt;
+            particle.position[1] += particle.velocity[1] * dt;
+            particle.rotation += particle.angular_velocity * dt;
+            true
+        });
+
+        if !self.emitting {
+            return;
+        }
+
+        let start = self.elapsed;
+        self.elapsed += dt;
+
+        let mut to_spawn = 0;
+        self.spawn_accumulator += self.spawn_rate * dt;
+        if self.spawn_accumulator >= 1f32 {
+            to_spawn += self.spawn_accumulator as u32;
+            self.spawn_accumulator = self.spawn_accumulator.fract();
+        }
+
+        while let Some(burst) = self.bursts.get(self.next_burst) {
+            if burst.time > self.elapsed {
+                break;
+            }
+            //bursts at zero should still fire on the very first update
+            if burst.time >= start || start == 0f32 {
+                to_spawn += burst.count;
+            }
+            self.next_burst += 1;
+        }
+
+        for _ in 0..to_spawn {
+            if self.particles.len() >= self.max_particles {
+                break;
+            }
+            let particle = self.spawn_particle(transform, rng);
+            self.particles.push(particle);
+        }
+
+        if let Some(duration) = self.duration {
+            if self.elapsed >= duration {
+                self.emitting = false;
+            }
+        }
+    }
+
+    fn spawn_particle(&self, transform: &GlobalTransform2d, rng: &mut impl Rng) -> Particle {
+        let mut range = |(min, max): (f32, f32)| if max > min { rng.gen_range(min..max) } else { min };
+
+        let direction = range(self.direction);
+        let speed = range(self.speed);
+        let offset_angle = range((0f32, 2f32 * PI));
+        let offset = range((0f32, self.spawn_radius));
+
+        //velocities are directions, so they only go through the rotation and scale
+        let matrix = transform.matrix();
+        let local_velocity = [direction.cos() * speed, direction.sin() * speed];
+        let velocity = [
+            matrix.x.x * local_velocity[0] + matrix.y.x * local_velocity[1],
+            matrix.x.y * local_velocity[0] + matrix.y.y * local_velocity[1],
+        ];
+
+        Particle {
+            position: transform.transform_point([offset_angle.cos() * offset, offset_angle.sin() * offset]),
+            velocity,
+            rotation: range((0f32, 2f32 * PI)) + matrix.x.y.atan2(matrix.x.x),
+            angular_velocity: range(self.angular_velocity),
+            age: 0f32,
+            lifetime: range(self.lifetime).max(f32::EPSILON),
+            frame: range((0f32, self.frames.len() as f32)) as usize % self.frames.len(),
+            tint: match self.tints.len() {
+                0 => [1f32, 1f32, 1f32, 1f32],
+                len => self.tints[range((0f32, len as f32)) as usize % len],
+            },
+            //the geometric mean of the scales along the two axes
+            scale: (matrix.x.x * matrix.y.y - matrix.y.x * matrix.x.y).abs().sqrt(),
+        }
+    }
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//presets
+impl ParticleEmitter {
+    //a quick burst of hot sparks flying out and falling, for captures
+    pub fn sparks() -> Self {
+        Self {
+            spawn_rate: 0f32,
+            duration: Some(0f32),
+            lifetime: (0.3f32, 0.7f32),
+            speed: (2f32, 5f32),
+            gravity: [0f32, -9f32],
+            drag: 1.5f32,
+            color: Curve::linear([1f32, 0.9f32, 0.5f32, 1f32], [1f32, 0.3f32, 0f32, 0f32]),
+            size: Curve::linear(0.08f32, 0.02f32),
+            blend_mode: BlendMode::Additive,
+            despawn_when_finished: true,
+            ..Self::new()
+        }
+        .with_burst(0f32, 40)
+    }
+
+    //colored paper falling down and spinning, for checkmate
+    pub fn confetti() -> Self {
+        Self {
+            spawn_rate: 120f32,
+            duration: Some(1.5f32),
+            lifetime: (2f32, 3.5f32),
+            direction: (PI * 0.25f32, PI * 0.75f32),
+            speed: (3f32, 7f32),
+            angular_velocity: (-8f32, 8f32),
+            spawn_radius: 1f32,
+            gravity: [0f32, -5f32],
+            drag: 0.8f32,
+            color: Curve::constant([1f32, 1f32, 1f32, 1f32]).with_key(0.85f32, [1f32, 1f32, 1f32, 1f32]).with_key(1f32, [1f32, 1f32, 1f32, 0f32]),
+            tints: vec![
+                [0.95f32, 0.25f32, 0.3f32, 1f32],
+                [0.2f32, 0.6f32, 0.95f32, 1f32],
+                [0.98f32, 0.85f32, 0.2f32, 1f32],
+                [0.3f32, 0.85f32, 0.4f32, 1f32],
+                [0.7f32, 0.35f32, 0.9f32, 1f32],
+            ],
+            size: Curve::constant(0.12f32),
+            despawn_when_finished: true,
+            ..Self::new()
+        }
+    }
+
+    //slow drifting motes, for ambient effects in the world
+    pub fn dust() -> Self {
+        Self {
+            spawn_rate: 6f32,
+            lifetime: (4f32, 8f32),
+            speed: (0.05f32, 0.2f32),
+            spawn_radius: 6f32,
+            color: Curve::linear([1f32, 1f32, 0.9f32, 0f32], [1f32, 1f32, 0.9f32, 0f32]).with_key(0.5f32, [1f32, 1f32, 0.9f32, 0.4f32]),
+            size: Curve::constant(0.04f32),
+            blend_mode: BlendMode::Additive,
+            ..Self::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    //where the sparks thrown by a few frames of an emitter end up, from a world seeded with seed
+    fn sparks_after_a_few_frames(seed: u64) -> Vec<([f32; 2], [f32; 2])> {
+        let mut world = World::new();
+        world.insert_resource(FrameTime(Duration::from_millis(16)));
+        world.insert_resource(ParticleRng::seeded(seed));
+        let emitter = world.spawn().insert(ParticleEmitter::sparks()).id();
+
+        let mut particle_system = SystemStage::single(ParticleEmitter::update);
+        for _ in 0..3 {
+            particle_system.run(&mut world);
+        }
+        let emitter = world.get::<ParticleEmitter>(emitter).unwrap();
+        emitter.particles().iter().map(|particle| (particle.position, particle.velocity)).collect()
+    }
+
+    #[test]
+    fn the_same_seed_throws_the_same_particles() {
+        let sparks = sparks_after_a_few_frames(7);
+        assert!(!sparks.is_empty());
+        assert_eq!(sparks, sparks_after_a_few_frames(7));
+        assert_ne!(sparks, sparks_after_a_few_frames(8));
+    }
+}
@@ -0,0 +1,8 @@
+mod curve;
+pub use curve::Curve;
+
+mod emitter;
+pub use emitter::{FrameMode, ParticleEmitter, ParticleRng};
+
+mod particle_pass;
+pub use particle_pass::ParticlePass;
@@ -0,0 +1,65 @@
+struct CameraUniform {
+    view_ortho: mat4x4<f32>
+}
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+
+struct QuadVertex {
+    //corner of a unit quad centered on the origin
+    @location(0) corner: vec2<f32>,
+}
+
+struct ParticleInstance {
+    @location(1) position: vec2<f32>,
+    @location(2) size: f32,
+    @location(3) rotation: f32,
+    @location(4) color: vec4<f32>,
+    //u0, v0, u1, v1
+    @location(5) region: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) tex_coord: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    vertex: QuadVertex,
+    particle: ParticleInstance,
+) -> VertexOutput {
+    let c = cos(particle.rotation);
+    let s = sin(particle.rotation);
+    let corner = vertex.corner * particle.size;
+    let position = particle.position + vec2<f32>(corner.x * c - corner.y * s, corner.x * s + corner.y * c);
+
+    //the bottom of the quad samples the bottom of the region
+    let t = vertex.corner + vec2<f32>(0.5, 0.5);
+    let tex_coord = vec2<f32>(mix(particle.region.x, particle.region.z, t.x), mix(particle.region.w, particle.region.y, t.y));
+
+    var out: VertexOutput;
+    out.color = particle.color;
+    out.tex_coord = tex_coord;
+    out.clip_position = camera.view_ortho * vec4<f32>(position, 0.0, 1.0);
+    return out;
+}
+
+@group(1) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(1) @binding(1)
+var s_diffuse: sampler;
+
+//same outputs as the sprite shader, so the sprite blend modes work unchanged
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coord) * in.color;
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
+
+@fragment
+fn fs_premultiplied(in: VertexOutput) -> @location(0) vec4<f32> {
+    let tint = vec4<f32>(in.color.rgb * in.color.a, in.color.a);
+    return textureSample(t_diffuse, s_diffuse, in.tex_coord) * tint;
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use wgpu::{RenderPassDescriptor, RenderPipeline};
+
+use crate::{
+    graphics::{RenderContext, RenderPass, Subpass, TextureCache, Uniform},
+    two_dimensional::{
+        camera::{Camera, CameraMatrix},
+        sprite::{BlendMode, SpritePass},
+        ActiveCamera, RenderLayers,
+    },
+};
+
+use super::ParticleEmitter;
+
+//two triangles of a unit quad centered on the origin, shared by every particle
+const QUAD: [[f32; 2]; 6] = [[-0.5, -0.5], [0.5, -0.5], [-0.5, 0.5], [-0.5, 0.5], [0.5, -0.5], [0.5, 0.5]];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct ParticleInstance {
+    position: [f32; 2],
+    size: f32,
+    rotation: f32,
+    color: [f32; 4],
+    region: [f32; 4],
+}
+
+impl ParticleInstance {
+    const ATTRIBS: [wgpu::VertexAttribute; 5] =
+        wgpu::vertex_attr_array![1 => Float32x2, 2 => Float32, 3 => Float32, 4 => Float32x4, 5 => Float32x4];
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ParticleInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+unsafe impl bytemuck::Pod for ParticleInstance {}
+unsafe impl bytemuck::Zeroable for ParticleInstance {}
+
+const QUAD_ATTRIBS: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![0 => Float32x2];
+
+fn quad_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &QUAD_ATTRIBS,
+    }
+}
+
+//draws every emitter's particles as instances of one quad, one draw per emitter
+pub struct ParticlePass {
+    camera_uniform: Uniform,
+    render_pipelines: HashMap<BlendMode, RenderPipeline>,
+    quad_buffer: wgpu::Buffer,
+}
+
+impl RenderPass for ParticlePass {
+    fn get_name() -> &'static str {
+        "Particle Pass"
+    }
+
+    fn get_init_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::init))
+    }
+
+    fn get_render_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::render))
+    }
+}
+
+impl ParticlePass {
+    fn init(mut commands: Commands, render_context: Res<RenderContext>, texture_cache: Res<TextureCache>) {
+        let camera_uniform = Uniform::new::<CameraMatrix>(render_context.as_ref(), 0);
+
+        let shader = render_context
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Particle Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("particle.wgsl").into()),
+            });
+
+        let render_pipeline_layout =
+            render_context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Particle Pipeline Layout"),
+                    bind_group_layouts: &[&camera_uniform.bind_group_layout, texture_cache.bind_layout().bind_group_layout()],
+                    push_constant_ranges: &[],
+                });
+
+        let render_pipelines = BlendMode::ALL
+            .iter()
+            .map(|blend_mode| {
+                let buffers = [quad_desc(), ParticleInstance::desc()];
+                (*blend_mode, SpritePass::create_pipeline(&render_context, &render_pipeline_layout, &shader, &buffers, *blend_mode))
+            })
+            .collect();
+
+        let quad_buffer = wgpu::util::DeviceExt::create_buffer_init(
+            &render_context.device,
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Particle Quad Buffer"),
+                contents: bytemuck::cast_slice(&QUAD),
+                usage: wgpu::BufferUsages::VERTEX,
+            },
+        );
+
+        commands.insert_resource(Self {
+            camera_uniform,
+            render_pipelines,
+            quad_buffer,
+        });
+    }
+
+    fn render(
+        emitters: Query<(&ParticleEmitter, Option<&RenderLayers>)>,
+        cameras: Query<&Camera>,
+        active_camera: Option<Res<ActiveCamera>>,
+        mut particle_pass: ResMut<ParticlePass>,
+        mut texture_cache: ResMut<TextureCache>,
+        mut subpass: ResMut<Subpass>,
+        render_context: Res<RenderContext>,
+    ) {
+        let particle_pass = &mut *particle_pass;
+        let camera = match Camera::active(&cameras, active_camera.as_deref()) {
+            Some(camera) => camera,
+            None => return,
+        };
+
+        let empty_string = String::from("");
+
+        //back to front by depth, like sprites
+        let mut batches: Vec<(u32, BlendMode, String, wgpu::Buffer, u32)> = Vec::new();
+        for (emitter, layers) in emitters.iter() {
+            if emitter.particle_count() == 0 || !camera.sees(layers) {
+                continue;
+            }
+
+            //untextured particles sample the cache's plain white texture
+            let texture_path = emitter.texture_path().as_ref().unwrap_or(&empty_string);
+            texture_cache.load(texture_path, &render_context);
+
+            let instances: Vec<ParticleInstance> = emitter
+                .particles()
+                .iter()
+                .map(|particle| ParticleInstance {
+                    position: particle.position,
+                    size: emitter.size.sample(particle.life()) * particle.scale,
+                    rotation: particle.rotation,
+                    color: emitter.particle_color(particle),
+                    region: emitter.frame_region(particle),
+                })
+                .collect();
+
+            let instance_buffer = wgpu::util::DeviceExt::create_buffer_init(
+                &render_context.device,
+                &wgpu::util::BufferInitDescriptor {
+                    label: Some("Particle Instance Buffer"),
+                    contents: bytemuck::cast_slice(&instances),
+                    usage: wgpu::BufferUsages::VERTEX,
+                },
+            );
+            batches.push((emitter.depth, emitter.blend_mode, texture_path.clone(), instance_buffer, instances.len() as u32));
+        }
+        batches.sort_by(|batch_1, batch_2| Ord::cmp(&batch_2.0, &batch_1.0));
+
+        particle_pass.camera_uniform.set_buffer(render_context.as_ref(), camera.get_matrix());
+
+        let subpass = &mut *subpass;
+        let encoder = subpass.encoder.as_mut().unwrap();
+
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Particle Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &subpass.texture,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        camera.set_viewport(&mut render_pass);
+        render_pass.set_bind_group(0, &particle_pass.camera_uniform.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, particle_pass.quad_buffer.slice(..));
+        for (_, blend_mode, texture_path, instance_buffer, num_instances) in batches.iter() {
+            render_pass.set_pipeline(&particle_pass.render_pipelines[blend_mode]);
+            let (_, bind_group) = texture_cache.get(texture_path).expect("Particle textures are loaded before drawing");
+            render_pass.set_bind_group(1, bind_group, &[]);
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass.draw(0..QUAD.len() as u32, 0..*num_instances);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod path;
+pub use path::{Path2d, Stroke, StrokeCap, StrokeJoin};
+
+mod path_pass;
+pub use path_pass::PathPass;
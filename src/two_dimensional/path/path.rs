@@ -0,0 +1,449 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bevy_ecs::prelude::*;
+use lyon::{
+    geom::{point, vector, Angle, Arc},
+    path::{iterator::PathIterator, Path, PathEvent},
+    tessellation::{
+        BuffersBuilder, FillOptions, FillTessellator, FillVertex, LineCap, LineJoin, StrokeOptions, StrokeTessellator, StrokeVertex,
+        VertexBuffers,
+    },
+};
+
+use crate::two_dimensional::{gizmos::ShapeVertex, GlobalTransform2d};
+
+//versions come from one counter, so a path replaced by a new one never reuses the old one's version
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(0);
+
+//how far a flattened curve may be from the real one, in world units
+const TOLERANCE: f32 = 0.005;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PathCommand {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    QuadraticTo { control: [f32; 2], to: [f32; 2] },
+    CubicTo { control_1: [f32; 2], control_2: [f32; 2], to: [f32; 2] },
+    //angles in radians, counter clockwise for positive sweeps
+    Arc { center: [f32; 2], radius: f32, start_angle: f32, sweep_angle: f32 },
+    Close,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrokeJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrokeCap {
+    Butt,
+    Square,
+    Round,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub color: [f32; 4],
+    //in world units
+    pub width: f32,
+    pub join: StrokeJoin,
+    pub cap: StrokeCap,
+    //alternating on and off lengths in world units, a solid line if empty
+    pub dashes: Vec<f32>,
+    pub dash_offset: f32,
+}
+
+impl Stroke {
+    pub fn new(color: [f32; 4], width: f32) -> Self {
+        Self {
+            color,
+            width,
+            join: StrokeJoin::Miter,
+            cap: StrokeCap::Butt,
+            dashes: Vec::new(),
+            dash_offset: 0f32,
+        }
+    }
+
+    pub fn with_join(mut self, join: StrokeJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: StrokeCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_dashes(mut self, dashes: &[f32], offset: f32) -> Self {
+        self.dashes = dashes.to_vec();
+        self.dash_offset = offset;
+        self
+    }
+}
+
+//a vector shape made of lines, curves and arcs in the entity's local space
+//it is tessellated into triangles by the path pass, which keeps the mesh until the path or its transform changes
+#[derive(Component, Clone, Debug)]
+pub struct Path2d {
+    commands: Vec<PathCommand>,
+    fill: Option<[f32; 4]>,
+    stroke: Option<Stroke>,
+    pub depth: u32,
+
+    //bumped on every change so the render pass knows when to tessellate again
+    version: u64,
+}
+
+impl Path2d {
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            fill: None,
+            stroke: None,
+            depth: 0,
+
+            version: NEXT_VERSION.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    pub fn circle(center: [f32; 2], radius: f32) -> Self {
+        Self::new()
+            .move_to([center[0] + radius, center[1]])
+            .arc(center, radius, 0f32, 2f32 * std::f32::consts::PI)
+            .close()
+    }
+
+    pub fn rounded_rect(position: [f32; 2], dimensions: [f32; 2], radius: f32) -> Self {
+        use std::f32::consts::FRAC_PI_2;
+
+        let radius = radius.min(dimensions[0] / 2f32).min(dimensions[1] / 2f32).max(0f32);
+        let [x0, y0] = position;
+        let (x1, y1) = (x0 + dimensions[0], y0 + dimensions[1]);
+
+        Self::new()
+            .move_to([x0 + radius, y0])
+            .line_to([x1 - radius, y0])
+            .arc([x1 - radius, y0 + radius], radius, -FRAC_PI_2, FRAC_PI_2)
+            .line_to([x1, y1 - radius])
+            .arc([x1 - radius, y1 - radius], radius, 0f32, FRAC_PI_2)
+            .line_to([x0 + radius, y1])
+            .arc([x0 + radius, y1 - radius], radius, FRAC_PI_2, FRAC_PI_2)
+            .line_to([x0, y0 + radius])
+            .arc([x0 + radius, y0 + radius], radius, 2f32 * FRAC_PI_2, FRAC_PI_2)
+            .close()
+    }
+
+    //starts a new sub path
+    pub fn move_to(mut self, to: [f32; 2]) -> Self {
+        self.push(PathCommand::MoveTo(to));
+        self
+    }
+
+    pub fn line_to(mut self, to: [f32; 2]) -> Self {
+        self.push(PathCommand::LineTo(to));
+        self
+    }
+
+    pub fn quadratic_to(mut self, control: [f32; 2], to: [f32; 2]) -> Self {
+        self.push(PathCommand::QuadraticTo { control, to });
+        self
+    }
+
+    pub fn cubic_to(mut self, control_1: [f32; 2], control_2: [f32; 2], to: [f32; 2]) -> Self {
+        self.push(PathCommand::CubicTo { control_1, control_2, to });
+        self
+    }
+
+    //a circular arc, joined to the current point by a straight line if it doesn't start there
+    pub fn arc(mut self, center: [f32; 2], radius: f32, start_angle: f32, sweep_angle: f32) -> Self {
+        self.push(PathCommand::Arc { center, radius, start_angle, sweep_angle });
+        self
+    }
+
+    //joins the current sub path back to where it started
+    pub fn close(mut self) -> Self {
+        self.push(PathCommand::Close);
+        self
+    }
+
+    pub fn with_fill(mut self, color: [f32; 4]) -> Self {
+        self.set_fill(Some(color));
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self {
+        self.set_stroke(Some(stroke));
+        self
+    }
+
+    pub fn with_depth(mut self, depth: u32) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn set_fill(&mut self, fill: Option<[f32; 4]>) {
+        if self.fill != fill {
+            self.fill = fill;
+            self.changed();
+        }
+    }
+
+    pub fn set_stroke(&mut self, stroke: Option<Stroke>) {
+        if self.stroke != stroke {
+            self.stroke = stroke;
+            self.changed();
+        }
+    }
+
+    pub fn fill(&self) -> Option<[f32; 4]> {
+        self.fill
+    }
+
+    pub fn stroke(&self) -> Option<&Stroke> {
+        self.stroke.as_ref()
+    }
+
+    //removes every segment, keeping the styles
+    pub fn clear(&mut self) {
+        self.commands.clear();
+        self.changed();
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn push(&mut self, command: PathCommand) {
+        self.commands.push(command);
+        self.changed();
+    }
+
+    fn changed(&mut self) {
+        self.version = NEXT_VERSION.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn build_path(&self) -> Path {
+        let mut builder = Path::builder();
+        let mut current: Option<[f32; 2]> = None;
+
+        for command in self.commands.iter() {
+            //where this segment starts, anything other than a move carries on from the current point or the origin
+            let from = match *command {
+                PathCommand::Arc { center, radius, start_angle, .. } => {
+                    [center[0] + start_angle.cos() * radius, center[1] + start_angle.sin() * radius]
+                }
+                _ => current.unwrap_or([0f32, 0f32]),
+            };
+
+            match command {
+                PathCommand::MoveTo(_) | PathCommand::Close => {}
+                _ => match current {
+                    //arcs work their start out with sin and cos, so they rarely land exactly on the current point
+                    Some(current) if (current[0] - from[0]).abs() < 1e-5 && (current[1] - from[1]).abs() < 1e-5 => {}
+                    Some(_) => {
+                        builder.line_to(point(from[0], from[1]));
+                    }
+                    None => {
+                        builder.begin(point(from[0], from[1]));
+                    }
+                },
+            }
+
+            match *command {
+                PathCommand::MoveTo(to) => {
+                    if current.is_some() {
+                        builder.end(false);
+                    }
+                    builder.begin(point(to[0], to[1]));
+                    current = Some(to);
+                }
+                PathCommand::LineTo(to) => {
+                    builder.line_to(point(to[0], to[1]));
+                    current = Some(to);
+                }
+                PathCommand::QuadraticTo { control, to } => {
+                    builder.quadratic_bezier_to(point(control[0], control[1]), point(to[0], to[1]));
+                    current = Some(to);
+                }
+                PathCommand::CubicTo { control_1, control_2, to } => {
+                    builder.cubic_bezier_to(point(control_1[0], control_1[1]), point(control_2[0], control_2[1]), point(to[0], to[1]));
+                    current = Some(to);
+                }
+                PathCommand::Arc { center, radius, start_angle, sweep_angle } => {
+                    let arc = Arc {
+                        center: point(center[0], center[1]),
+                        radii: vector(radius, radius),
+                        start_angle: Angle::radians(start_angle),
+                        sweep_angle: Angle::radians(sweep_angle),
+                        x_rotation: Angle::radians(0f32),
+                    };
+                    arc.for_each_quadratic_bezier(&mut |segment| {
+                        builder.quadratic_bezier_to(segment.ctrl, segment.to);
+                    });
+                    let to = arc.to();
+                    current = Some([to.x, to.y]);
+                }
+                PathCommand::Close => {
+                    if current.take().is_some() {
+                        builder.end(true);
+                    }
+                }
+            }
+        }
+
+        if current.is_some() {
+            builder.end(false);
+        }
+        builder.build()
+    }
+
+    //triangles for the fill and then the stroke, in world space
+    pub(super) fn tessellate(&self, transform: &GlobalTransform2d) -> (Vec<ShapeVertex>, Vec<u32>) {
+        let path = self.build_path();
+        let mut geometry: VertexBuffers<ShapeVertex, u32> = VertexBuffers::new();
+
+        if let Some(color) = self.fill {
+            let result = FillTessellator::new().tessellate_path(
+                &path,
+                &FillOptions::tolerance(TOLERANCE),
+                &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| ShapeVertex {
+                    position: transform.transform_point(vertex.position().to_array()),
+                    color,
+                }),
+            );
+            if let Err(error) = result {
+                eprintln!("Failed to fill path: {:?}", error);
+            }
+        }
+
+        if let Some(stroke) = self.stroke.as_ref() {
+            let options = StrokeOptions::tolerance(TOLERANCE)
+                .with_line_width(stroke.width)
+                .with_line_join(match stroke.join {
+                    StrokeJoin::Miter => LineJoin::Miter,
+                    StrokeJoin::Round => LineJoin::Round,
+                    StrokeJoin::Bevel => LineJoin::Bevel,
+                })
+                .with_line_cap(match stroke.cap {
+                    StrokeCap::Butt => LineCap::Butt,
+                    StrokeCap::Square => LineCap::Square,
+                    StrokeCap::Round => LineCap::Round,
+                });
+
+            let dashed;
+            let stroked_path = if stroke.dashes.iter().any(|length| *length > 0f32) {
+                dashed = dash_path(&path, &stroke.dashes, stroke.dash_offset);
+                &dashed
+            } else {
+                &path
+            };
+
+            let color = stroke.color;
+            let result = StrokeTessellator::new().tessellate_path(
+                stroked_path,
+                &options,
+                &mut BuffersBuilder::new(&mut geometry, |vertex: StrokeVertex| ShapeVertex {
+                    position: transform.transform_point(vertex.position().to_array()),
+                    color,
+                }),
+            );
+            if let Err(error) = result {
+                eprintln!("Failed to stroke path: {:?}", error);
+            }
+        }
+
+        (geometry.vertices, geometry.indices)
+    }
+}
+
+impl Default for Path2d {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//cuts a path into the "on" pieces of a dash pattern, following the flattened path so curves are dashed too
+fn dash_path(path: &Path, dashes: &[f32], offset: f32) -> Path {
+    //an odd pattern repeats twice so on and off keep alternating
+    let mut pattern: Vec<f32> = dashes.iter().map(|length| length.max(0f32)).collect();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_within(..);
+    }
+    let total: f32 = pattern.iter().sum();
+
+    let mut builder = Path::builder();
+    //where we are in the pattern and how much of that dash is left
+    let mut index = 0;
+    let mut remaining = pattern[0];
+    let mut drawing = false;
+
+    let mut skip = offset.rem_euclid(total);
+    while skip > 0f32 {
+        if skip < remaining {
+            remaining -= skip;
+            break;
+        }
+        skip -= remaining;
+        index = (index + 1) % pattern.len();
+        remaining = pattern[index];
+    }
+
+    for event in path.iter().flattened(TOLERANCE) {
+        let (from, to) = match event {
+            PathEvent::Line { from, to } => (from, to),
+            PathEvent::End { last, first, close: true } => (last, first),
+            PathEvent::End { .. } => {
+                if drawing {
+                    builder.end(false);
+                    drawing = false;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let mut start = from;
+        let mut length = (to - start).length();
+        while length > 0f32 {
+            let on = index % 2 == 0;
+            let step = remaining.min(length);
+            let end = start + (to - start) * (step / length);
+
+            if on {
+                if !drawing {
+                    builder.begin(start);
+                    drawing = true;
+                }
+                builder.line_to(end);
+            }
+
+            remaining -= step;
+            length -= step;
+            start = end;
+
+            if remaining <= 0f32 {
+                if drawing {
+                    builder.end(false);
+                    drawing = false;
+                }
+                index = (index + 1) % pattern.len();
+                remaining = pattern[index];
+            }
+        }
+
+        if let PathEvent::End { .. } = event {
+            if drawing {
+                builder.end(false);
+                drawing = false;
+            }
+        }
+    }
+
+    if drawing {
+        builder.end(false);
+    }
+    builder.build()
+}
@@ -0,0 +1,179 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy_ecs::prelude::*;
+use wgpu::{RenderPassDescriptor, RenderPipeline};
+
+use crate::{
+    graphics::{RenderContext, RenderPass, Subpass, Uniform},
+    two_dimensional::{
+        camera::{Camera, CameraMatrix},
+        gizmos::GizmoPass,
+        ActiveCamera, GlobalTransform2d, RenderLayers,
+    },
+};
+
+use super::Path2d;
+
+//a path's triangles, kept around until the path or its transform changes
+struct CachedPath {
+    version: u64,
+    transform: GlobalTransform2d,
+
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
+pub struct PathPass {
+    camera_uniform: Uniform,
+    render_pipeline: RenderPipeline,
+
+    paths: HashMap<Entity, CachedPath>,
+}
+
+impl RenderPass for PathPass {
+    fn get_name() -> &'static str {
+        "Path Pass"
+    }
+
+    fn get_init_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::init))
+    }
+
+    fn get_render_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::render))
+    }
+}
+
+impl PathPass {
+    fn init(mut commands: Commands, render_context: Res<RenderContext>) {
+        let camera_uniform = Uniform::new::<CameraMatrix>(render_context.as_ref(), 0);
+
+        //paths end up as the same colored triangles gizmos draw
+        let shader = render_context
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Path Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("../gizmos/shape.wgsl").into()),
+            });
+
+        let render_pipeline_layout =
+            render_context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Path Pipeline Layout"),
+                    bind_group_layouts: &[&camera_uniform.bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        let render_pipeline = GizmoPass::create_pipeline(&render_context, &render_pipeline_layout, &shader);
+
+        commands.insert_resource(Self {
+            camera_uniform,
+            render_pipeline,
+
+            paths: HashMap::new(),
+        });
+    }
+
+    fn render(
+        paths: Query<(Entity, &Path2d, Option<&GlobalTransform2d>, Option<&RenderLayers>)>,
+        cameras: Query<&Camera>,
+        active_camera: Option<Res<ActiveCamera>>,
+        mut path_pass: ResMut<PathPass>,
+        mut subpass: ResMut<Subpass>,
+        render_context: Res<RenderContext>,
+    ) {
+        let path_pass = &mut *path_pass;
+        let identity = GlobalTransform2d::default();
+        let camera = match Camera::active(&cameras, active_camera.as_deref()) {
+            Some(camera) => camera,
+            None => return,
+        };
+
+        //paths are cached for every camera, only the ones this camera sees get drawn
+        let mut visible_paths: Vec<(u32, Entity, bool)> = Vec::new();
+        for (entity, path, transform, layers) in paths.iter() {
+            let transform = transform.unwrap_or(&identity);
+
+            let stale = match path_pass.paths.get(&entity) {
+                Some(cached) => cached.version != path.version() || cached.transform != *transform,
+                None => true,
+            };
+
+            if stale {
+                let (vertices, indices) = path.tessellate(transform);
+                if indices.is_empty() {
+                    path_pass.paths.remove(&entity);
+                    continue;
+                }
+
+                let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
+                    &render_context.device,
+                    &wgpu::util::BufferInitDescriptor {
+                        label: Some("Path Vertex Buffer"),
+                        contents: bytemuck::cast_slice(&vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    },
+                );
+                let index_buffer = wgpu::util::DeviceExt::create_buffer_init(
+                    &render_context.device,
+                    &wgpu::util::BufferInitDescriptor {
+                        label: Some("Path Index Buffer"),
+                        contents: bytemuck::cast_slice(&indices),
+                        usage: wgpu::BufferUsages::INDEX,
+                    },
+                );
+
+                path_pass.paths.insert(
+                    entity,
+                    CachedPath {
+                        version: path.version(),
+                        transform: *transform,
+
+                        vertex_buffer,
+                        index_buffer,
+                        num_indices: indices.len() as u32,
+                    },
+                );
+            }
+
+            visible_paths.push((path.depth, entity, camera.sees(layers)));
+        }
+
+        //drop the meshes of paths that are gone
+        let visible_entities: HashSet<Entity> = visible_paths.iter().map(|(_, entity, _)| *entity).collect();
+        path_pass.paths.retain(|entity, _| visible_entities.contains(entity));
+
+        //higher depths are drawn first, like sprites
+        visible_paths.sort_by(|(depth_1, ..), (depth_2, ..)| Ord::cmp(depth_2, depth_1));
+
+        path_pass.camera_uniform.set_buffer(render_context.as_ref(), camera.get_matrix());
+
+        let subpass = &mut *subpass;
+        let encoder = subpass.encoder.as_mut().unwrap();
+
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Path Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &subpass.texture,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        camera.set_viewport(&mut render_pass);
+        render_pass.set_pipeline(&path_pass.render_pipeline);
+        render_pass.set_bind_group(0, &path_pass.camera_uniform.bind_group, &[]);
+        for (_, entity, _) in visible_paths.iter().filter(|(.., seen)| *seen) {
+            let cached = &path_pass.paths[entity];
+            render_pass.set_vertex_buffer(0, cached.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(cached.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..cached.num_indices, 0, 0..1);
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use bevy_ecs::prelude::*;
+use winit::event::MouseButton;
+
+use crate::core::{Event, InputFocus, InputOwner};
+
+use super::{sprite::Sprite, Camera2d as Camera, GlobalTransform2d, RenderLayers};
+
+//sprites with this can be hovered, clicked and, when draggable, dragged around
+//entities without a sprite can be picked too, by giving them an area
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pickable {
+    pub draggable: bool,
+    //a rectangle in the entity's own space, x, y, width, height, hit tested in place of the sprite's quad
+    pub area: Option<[f32; 4]>,
+}
+
+impl Pickable {
+    pub fn new() -> Self {
+        Self { draggable: false, area: None }
+    }
+
+    pub fn draggable() -> Self {
+        Self { draggable: true, area: None }
+    }
+
+    pub fn with_area(mut self, position: [f32; 2], dimensions: [f32; 2]) -> Self {
+        self.area = Some([position[0], position[1], dimensions[0], dimensions[1]]);
+        self
+    }
+
+    fn contains_point(&self, sprite: Option<&Sprite>, transform: &GlobalTransform2d, point: [f32; 2]) -> bool {
+        match (self.area, sprite) {
+            (Some([x, y, width, height]), _) => transform
+                .inverse_transform_point(point)
+                .is_some_and(|local| local[0] >= x && local[0] <= x + width && local[1] >= y && local[1] <= y + height),
+            (None, Some(sprite)) => sprite.contains_point(transform, point),
+            (None, None) => false,
+        }
+    }
+}
+
+//points are in world space, as seen by the camera the press started in
+#[derive(Clone, Debug, PartialEq)]
+pub enum PickingEvent {
+    //pressed and released on the same entity without dragging it
+    Clicked(Entity),
+    HoverEnter(Entity),
+    HoverExit(Entity),
+    //where the press started
+    DragStart((Entity, (f32, f32))),
+    //where the cursor is now
+    Drag((Entity, (f32, f32))),
+    //where it was let go and the pickable under it, not counting the dragged entity
+    Drop((Entity, (f32, f32), Option<Entity>)),
+}
+
+//every pickable with what hit testing needs from it
+type PickableQuery<'w, 's> = Query<'w, 's, (Entity, Option<&'static Sprite>, Option<&'static GlobalTransform2d>, &'static Pickable, Option<&'static RenderLayers>)>;
+
+struct Press {
+    entity: Entity,
+    camera: Entity,
+    screen_start: (f32, f32),
+    draggable: bool,
+    dragging: bool,
+}
+
+//hit tests the cursor against pickables and turns mouse events into picking events
+//cameras are tried from the highest priority down, so a hud camera with nothing under the cursor lets clicks through
+//within a camera the sprite drawn on top wins
+pub struct Picking {
+    //the button that clicks and drags, keep it apart from CameraController2dPan::pan_button
+    pub button: MouseButton,
+    //how many screen pixels the cursor moves with the button down before a press becomes a drag
+    pub drag_threshold: f32,
+
+    cursor_pos: (f32, f32),
+    hovered: Option<Entity>,
+    press: Option<Press>,
+}
+
+impl Picking {
+    pub fn new() -> Self {
+        Self {
+            button: MouseButton::Left,
+            drag_threshold: 4f32,
+
+            cursor_pos: (0f32, 0f32),
+            hovered: None,
+            press: None,
+        }
+    }
+
+    pub fn init(world: &mut World) {
+        world.insert_resource(Self::new());
+        world.insert_resource(Events::<PickingEvent>::default());
+    }
+
+    pub fn hovered(&self) -> Option<Entity> {
+        self.hovered
+    }
+
+    //the entity being dragged, if a drag is under way
+    pub fn dragged(&self) -> Option<Entity> {
+        self.press.as_ref().filter(|press| press.dragging).map(|press| press.entity)
+    }
+
+    //runs after the cameras and transforms have been updated, so hits match what's about to be drawn
+    pub fn update(
+        mut picking: ResMut<Picking>,
+        mut reader: EventReader<Event>,
+        cameras: Query<(Entity, &Camera)>,
+        pickables: PickableQuery,
+        focus: Option<Res<InputFocus>>,
+        mut writer: EventWriter<PickingEvent>,
+    ) {
+        let picking = &mut *picking;
+        let world_point = |camera: Entity, screen_point: (f32, f32)| cameras.get(camera).ok().map(|(_, camera)| camera.screen_to_world(screen_point));
+
+        for event in reader.iter() {
+            match event {
+                Event::MousePressed((button, position)) if *button == picking.button => {
+                    let position = (position.0 as f32, position.1 as f32);
+                    picking.press = pick(&cameras, &pickables, position, None).map(|(entity, camera)| Press {
+                        entity,
+                        camera,
+                        screen_start: position,
+                        draggable: pickables.get(entity).is_ok_and(|(.., pickable, _)| pickable.draggable),
+                        dragging: false,
+                    });
+                }
+                Event::CursorMoved(position) => {
+                    let position = (position.0 as f32, position.1 as f32);
+                    picking.cursor_pos = position;
+
+                    let threshold = picking.drag_threshold;
+                    let press = match picking.press.as_mut() {
+                        Some(press) if press.draggable => press,
+                        _ => continue,
+                    };
+                    if !press.dragging {
+                        let moved = (position.0 - press.screen_start.0, position.1 - press.screen_start.1);
+                        if (moved.0 * moved.0 + moved.1 * moved.1).sqrt() < threshold {
+                            continue;
+                        }
+                        press.dragging = true;
+                        if let Some(start) = world_point(press.camera, press.screen_start) {
+                            writer.send(PickingEvent::DragStart((press.entity, start)));
+                        }
+                    }
+                    if let Some(point) = world_point(press.camera, position) {
+                        writer.send(PickingEvent::Drag((press.entity, point)));
+                    }
+                }
+                Event::MouseReleased((button, position)) if *button == picking.button => {
+                    let position = (position.0 as f32, position.1 as f32);
+                    let press = match picking.press.take() {
+                        Some(press) => press,
+                        None => continue,
+                    };
+
+                    if press.dragging {
+                        let target = pick(&cameras, &pickables, position, Some(press.entity)).map(|(target, _)| target);
+                        if let Some(point) = world_point(press.camera, position) {
+                            writer.send(PickingEvent::Drop((press.entity, point, target)));
+                        }
+                    } else if pick(&cameras, &pickables, position, None).map(|(entity, _)| entity) == Some(press.entity) {
+                        writer.send(PickingEvent::Clicked(press.entity));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        //hover is checked every frame since sprites and cameras move under a still cursor too
+        //while dragging it's whatever is under the dragged entity, where it would be dropped
+        //nothing is hovered while the cursor is over the ui, unless a drag is carrying something under it
+        let ui_has_mouse = focus.is_some_and(|focus| focus.mouse() == InputOwner::Ui);
+        let hovered = if ui_has_mouse && picking.dragged().is_none() {
+            None
+        } else {
+            pick(&cameras, &pickables, picking.cursor_pos, picking.dragged()).map(|(entity, _)| entity)
+        };
+        if hovered != picking.hovered {
+            if let Some(exited) = picking.hovered {
+                writer.send(PickingEvent::HoverExit(exited));
+            }
+            if let Some(entered) = hovered {
+                writer.send(PickingEvent::HoverEnter(entered));
+            }
+            picking.hovered = hovered;
+        }
+    }
+}
+
+impl Default for Picking {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//the topmost pickable under a screen point and the camera it was found in
+fn pick(
+    cameras: &Query<(Entity, &Camera)>,
+    pickables: &PickableQuery,
+    screen_point: (f32, f32),
+    ignore: Option<Entity>,
+) -> Option<(Entity, Entity)> {
+    let identity = GlobalTransform2d::default();
+    let mut cameras: Vec<(Entity, &Camera)> = cameras.iter().filter(|(_, camera)| camera.contains_screen_point(screen_point)).collect();
+    cameras.sort_by_key(|(_, camera)| std::cmp::Reverse(camera.priority));
+
+    cameras.into_iter().find_map(|(camera_entity, camera)| {
+        let world_point = camera.screen_to_world(screen_point);
+        pickables
+            .iter()
+            .filter(|(entity, sprite, transform, pickable, layers)| {
+                Some(*entity) != ignore && camera.sees(*layers) && pickable.contains_point(*sprite, transform.unwrap_or(&identity), [world_point.0, world_point.1])
+            })
+            //lower depths are drawn last, and within a depth translucent sprites go over opaque ones
+            //pickables without a sprite are under every sprite, like the tilemap squares they usually stand in for
+            .min_by_key(|(_, sprite, ..)| sprite.map_or((u32::MAX, true), |sprite| (sprite.depth, !sprite.is_translucent())))
+            .map(|(entity, ..)| (entity, camera_entity))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::two_dimensional::Transform2d;
+
+    #[test]
+    fn areas_are_hit_tested_in_the_entitys_own_space() {
+        let square = Pickable::new().with_area([0f32, 0f32], [1f32, 1f32]);
+        let transform = GlobalTransform2d::from(&Transform2d::from_translation([3f32, 2f32]));
+
+        assert!(square.contains_point(None, &transform, [3.5f32, 2.5f32]));
+        assert!(!square.contains_point(None, &transform, [0.5f32, 0.5f32]));
+        //without an area or a sprite there's nothing to hit
+        assert!(!Pickable::new().contains_point(None, &transform, [3.5f32, 2.5f32]));
+    }
+
+    #[test]
+    fn an_area_takes_the_place_of_the_sprite() {
+        let sprite = Sprite::new([0f32, 0f32], [1f32, 1f32], [1f32, 1f32, 1f32, 1f32]);
+        let identity = GlobalTransform2d::default();
+
+        assert!(Pickable::new().contains_point(Some(&sprite), &identity, [0.5f32, 0.5f32]));
+        let smaller = Pickable::new().with_area([0.25f32, 0.25f32], [0.5f32, 0.5f32]);
+        assert!(!smaller.contains_point(Some(&sprite), &identity, [0.1f32, 0.1f32]));
+        assert!(smaller.contains_point(Some(&sprite), &identity, [0.5f32, 0.5f32]));
+    }
+}
@@ -0,0 +1,46 @@
+use bevy_ecs::prelude::*;
+
+//which of 32 layers an entity is drawn on, a camera only draws what shares a layer with it
+//entities and cameras without render layers are on layer 0
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RenderLayers(u32);
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        RenderLayers::DEFAULT
+    }
+}
+
+impl RenderLayers {
+    pub const DEFAULT: RenderLayers = RenderLayers(1);
+    pub const ALL: RenderLayers = RenderLayers(u32::MAX);
+    pub const NONE: RenderLayers = RenderLayers(0);
+    pub const COUNT: u8 = 32;
+
+    pub fn layer(layer: u8) -> Self {
+        Self::NONE.with(layer)
+    }
+
+    pub fn with(self, layer: u8) -> Self {
+        assert!(layer < Self::COUNT, "There are only {} render layers", Self::COUNT);
+        RenderLayers(self.0 | (1 << layer))
+    }
+
+    pub fn without(self, layer: u8) -> Self {
+        assert!(layer < Self::COUNT, "There are only {} render layers", Self::COUNT);
+        RenderLayers(self.0 & !(1 << layer))
+    }
+
+    pub fn contains(&self, layer: u8) -> bool {
+        layer < Self::COUNT && self.0 & (1 << layer) != 0
+    }
+
+    pub fn intersects(&self, other: &RenderLayers) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    //for the optional layers that come out of queries
+    pub fn of(layers: Option<&RenderLayers>) -> RenderLayers {
+        layers.copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,263 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy_ecs::prelude::*;
+
+use crate::app::FrameTime;
+
+use super::Sprite;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationMode {
+    //play through once and stop on the last frame
+    Once,
+    Loop,
+    //play forwards then backwards, without repeating the end frames
+    PingPong,
+}
+
+//a list of cells in a sprite's tile view, each shown for its own duration
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    frames: Vec<((u32, u32), Duration)>,
+    pub mode: AnimationMode,
+}
+
+impl AnimationClip {
+    pub fn new(mode: AnimationMode) -> Self {
+        Self {
+            frames: Vec::new(),
+            mode,
+        }
+    }
+
+    //every frame shown for the same amount of time
+    pub fn uniform(cells: &[(u32, u32)], frame_duration: Duration, mode: AnimationMode) -> Self {
+        Self {
+            frames: cells.iter().map(|cell| (*cell, frame_duration)).collect(),
+            mode,
+        }
+    }
+
+    pub fn with_frame(mut self, row: u32, col: u32, duration: Duration) -> Self {
+        self.frames.push(((row, col), duration));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn cell(&self, frame: usize) -> (u32, u32) {
+        self.frames[frame].0
+    }
+
+    pub fn duration(&self, frame: usize) -> Duration {
+        self.frames[frame].1
+    }
+}
+
+//clips are shared between animators, and looked up by name
+#[derive(Default)]
+pub struct AnimationClips(HashMap<String, AnimationClip>);
+
+impl AnimationClips {
+    pub fn insert(&mut self, name: &str, clip: AnimationClip) {
+        self.0.insert(String::from(name), clip);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AnimationClip> {
+        self.0.get(name)
+    }
+}
+
+//sent when a clip that doesn't repeat reaches its last frame
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub clip: String,
+}
+
+//steps the tile view of the sprite on the same entity through a clip
+#[derive(Component, Debug)]
+pub struct SpriteAnimator {
+    clip: String,
+    frame: usize,
+    elapsed: Duration,
+    reversing: bool,
+    playing: bool,
+
+    pub speed: f32,
+}
+
+impl SpriteAnimator {
+    pub fn new(clip: &str) -> Self {
+        Self {
+            clip: String::from(clip),
+            frame: 0,
+            elapsed: Duration::ZERO,
+            reversing: false,
+            playing: true,
+
+            speed: 1f32,
+        }
+    }
+
+    pub fn init(world: &mut World) {
+        world.insert_resource(AnimationClips::default());
+        world.insert_resource(Events::<AnimationFinished>::default());
+    }
+
+    //switch clips, starting from the first frame
+    pub fn play(&mut self, clip: &str) {
+        *self = Self {
+            speed: self.speed,
+            ..Self::new(clip)
+        };
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn update(
+        mut animated: Query<(Entity, &mut Sprite, &mut SpriteAnimator)>,
+        clips: Res<AnimationClips>,
+        frame_time: Option<Res<FrameTime>>,
+        mut finished: EventWriter<AnimationFinished>,
+    ) {
+        let frame_time = match frame_time {
+            Some(frame_time) => frame_time.0,
+            None => return,
+        };
+
+        for (entity, mut sprite, mut animator) in animated.iter_mut() {
+            if !animator.playing {
+                continue;
+            }
+
+            let clip = match clips.get(&animator.clip) {
+                Some(clip) if !clip.is_empty() => clip,
+                _ => continue,
+            };
+
+            //clamp in case the clip was swapped out from under us for a shorter one
+            animator.frame = animator.frame.min(clip.len() - 1);
+            let speed = animator.speed.max(0f32);
+            animator.elapsed += frame_time.mul_f32(speed);
+
+            //never step through more than a couple of cycles in one update, zero length frames would spin here forever
+            let mut steps = 0;
+            while animator.playing && animator.elapsed >= clip.duration(animator.frame) {
+                if steps > 2 * clip.len() {
+                    animator.elapsed = Duration::ZERO;
+                    break;
+                }
+                steps += 1;
+
+                let frame_duration = clip.duration(animator.frame);
+                animator.elapsed -= frame_duration;
+
+                if animator.step(clip) {
+                    animator.playing = false;
+                    animator.elapsed = Duration::ZERO;
+                    finished.send(AnimationFinished {
+                        entity,
+                        clip: animator.clip.clone(),
+                    });
+                }
+            }
+
+            let (row, col) = clip.cell(animator.frame);
+            if let Some(tile_view) = sprite.tile_view_mut() {
+                tile_view.set_position(row, col);
+            }
+        }
+    }
+
+    //advance to the next frame, returns true when the clip has finished
+    fn step(&mut self, clip: &AnimationClip) -> bool {
+        let last = clip.len() - 1;
+        match clip.mode {
+            AnimationMode::Once => {
+                if self.frame == last {
+                    return true;
+                }
+                self.frame += 1;
+            }
+            AnimationMode::Loop => {
+                self.frame = if self.frame == last { 0 } else { self.frame + 1 };
+            }
+            AnimationMode::PingPong => {
+                if last == 0 {
+                    return false;
+                }
+                if self.reversing && self.frame == 0 {
+                    self.reversing = false;
+                } else if !self.reversing && self.frame == last {
+                    self.reversing = true;
+                }
+                self.frame = if self.reversing { self.frame - 1 } else { self.frame + 1 };
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(mode: AnimationMode, len: u32, steps: usize) -> Vec<usize> {
+        let clip = AnimationClip::uniform(&(0..len).map(|col| (0, col)).collect::<Vec<_>>(), Duration::from_millis(100), mode);
+        let mut animator = SpriteAnimator::new("clip");
+        (0..steps)
+            .map(|_| {
+                animator.step(&clip);
+                animator.frame
+            })
+            .collect()
+    }
+
+    #[test]
+    fn once_stops_on_the_last_frame() {
+        let clip = AnimationClip::uniform(&[(0, 0), (0, 1), (0, 2)], Duration::from_millis(100), AnimationMode::Once);
+        let mut animator = SpriteAnimator::new("clip");
+        assert!(!animator.step(&clip));
+        assert!(!animator.step(&clip));
+        assert!(animator.step(&clip));
+        assert_eq!(animator.frame, 2);
+    }
+
+    #[test]
+    fn loop_wraps_to_the_first_frame() {
+        assert_eq!(frames(AnimationMode::Loop, 3, 5), [1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_both_ends() {
+        assert_eq!(frames(AnimationMode::PingPong, 3, 8), [1, 2, 1, 0, 1, 2, 1, 0]);
+        assert_eq!(frames(AnimationMode::PingPong, 2, 4), [1, 0, 1, 0]);
+    }
+
+    #[test]
+    fn ping_pong_holds_a_single_frame() {
+        assert_eq!(frames(AnimationMode::PingPong, 1, 3), [0, 0, 0]);
+    }
+
+    #[test]
+    fn clips_built_frame_by_frame_keep_their_durations() {
+        let clip = AnimationClip::new(AnimationMode::Once)
+            .with_frame(0, 1, Duration::from_millis(50))
+            .with_frame(1, 1, Duration::from_millis(20));
+        assert_eq!(clip.len(), 2);
+        assert_eq!(clip.cell(1), (1, 1));
+        assert_eq!(clip.duration(0), Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,52 @@
+//how a sprite is combined with what has already been drawn
+//the sprite shader always outputs premultiplied color, so every blend state here expects premultiplied input
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BlendMode {
+    #[default]
+    Alpha,
+    Additive,
+    Multiply,
+    //for textures whose color has already been multiplied by alpha
+    Premultiplied,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 4] = [BlendMode::Alpha, BlendMode::Additive, BlendMode::Multiply, BlendMode::Premultiplied];
+
+    pub fn blend_state(&self) -> wgpu::BlendState {
+        //leave the destination alpha alone for the modes that only tint what's underneath
+        let keep_alpha = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        match self {
+            BlendMode::Alpha | BlendMode::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+            //dst * src * a + dst * (1 - a), so a fully transparent sprite leaves the destination untouched
+            BlendMode::Multiply => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Dst,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+        }
+    }
+
+    pub fn fragment_entry_point(&self) -> &'static str {
+        match self {
+            BlendMode::Premultiplied => "fs_premultiplied",
+            _ => "fs_main",
+        }
+    }
+}
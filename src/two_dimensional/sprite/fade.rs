@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+
+use crate::app::FrameTime;
+
+use super::Sprite;
+
+//linearly moves a sprite's opacity between two values, and removes itself when it gets there
+#[derive(Component, Debug)]
+pub struct Fade {
+    from: f32,
+    to: f32,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+impl Fade {
+    pub fn new(from: f32, to: f32, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn fade_in(duration: Duration) -> Self {
+        Self::new(0f32, 1f32, duration)
+    }
+
+    pub fn fade_out(duration: Duration) -> Self {
+        Self::new(1f32, 0f32, duration)
+    }
+
+    pub fn update(
+        mut commands: Commands,
+        mut fading: Query<(Entity, &mut Sprite, &mut Fade)>,
+        frame_time: Option<Res<FrameTime>>,
+    ) {
+        let frame_time = match frame_time {
+            Some(frame_time) => frame_time.0,
+            None => return,
+        };
+
+        for (entity, mut sprite, mut fade) in fading.iter_mut() {
+            fade.elapsed += frame_time;
+
+            let t = if fade.duration.is_zero() {
+                1f32
+            } else {
+                (fade.elapsed.as_secs_f32() / fade.duration.as_secs_f32()).min(1f32)
+            };
+            sprite.opacity = fade.from + (fade.to - fade.from) * t;
+
+            if t >= 1f32 {
+                commands.entity(entity).remove::<Fade>();
+            }
+        }
+    }
+}
@@ -4,8 +4,20 @@ pub use sprite_pass::SpritePass;
 mod sprite;
 pub use sprite::Sprite;
 
+mod blend_mode;
+pub use blend_mode::BlendMode;
+
+mod fade;
+pub use fade::Fade;
+
+mod nine_slice;
+pub use nine_slice::{NineSlice, SliceMode};
+
 mod tile_view;
 pub use tile_view::TileView;
 
+mod animation;
+pub use animation::{AnimationClip, AnimationClips, AnimationFinished, AnimationMode, SpriteAnimator};
+
 mod sprite_vertex;
-use sprite_vertex::SpriteVertex;
\ No newline at end of file
+pub(crate) use sprite_vertex::SpriteVertex;
\ No newline at end of file
@@ -0,0 +1,214 @@
+use crate::two_dimensional::GlobalTransform2d;
+
+use super::SpriteVertex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SliceMode {
+    Stretch,
+    //repeat the source at its natural size, cutting the last repeat short
+    Tile,
+}
+
+//splits a sprite into a 3x3 grid, the corners keep their size while the edges and center fill the rest
+#[derive(Clone, Debug)]
+pub struct NineSlice {
+    //borders in texture pixels, left, right, top, bottom
+    insets: [f32; 4],
+    //world units per texture pixel, this is what keeps the corners the same size however big the sprite is
+    pixel_size: f32,
+    pub edge_mode: SliceMode,
+    pub center_mode: SliceMode,
+
+    //a region of an atlas in texture pixels, x, y, width, height from the top left
+    region: Option<[u32; 4]>,
+}
+
+impl NineSlice {
+    pub fn new(insets: [f32; 4], pixel_size: f32) -> Self {
+        Self {
+            insets,
+            pixel_size,
+            edge_mode: SliceMode::Stretch,
+            center_mode: SliceMode::Stretch,
+
+            region: None,
+        }
+    }
+
+    pub fn with_modes(mut self, edge_mode: SliceMode, center_mode: SliceMode) -> Self {
+        self.edge_mode = edge_mode;
+        self.center_mode = center_mode;
+        self
+    }
+
+    pub fn with_region(mut self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        self.region = Some([x, y, width, height]);
+        self
+    }
+
+    //uvs are u0, v0, u1, v1 with v pointing down the image, the sprite's own region is used if we don't have one
+    //texture_size is the size in pixels of the texture the sprite is drawn with
+    pub(super) fn get_vertex_buffer(
+        &self,
+        position: [f32; 2],
+        dimensions: [f32; 2],
+        sprite_region: [f32; 4],
+        texture_size: (u32, u32),
+        transform: &GlobalTransform2d,
+        color: [f32; 4],
+    ) -> Vec<SpriteVertex> {
+        let (texture_width, texture_height) = (texture_size.0 as f32, texture_size.1 as f32);
+        let [u0, v0, u1, v1] = match self.region {
+            Some([x, y, width, height]) => [
+                x as f32 / texture_width,
+                y as f32 / texture_height,
+                (x + width) as f32 / texture_width,
+                (y + height) as f32 / texture_height,
+            ],
+            None => sprite_region,
+        };
+
+        let [left, right, top, bottom] = self.insets;
+
+        //if the sprite is smaller than its borders, shrink the borders so they still meet
+        let world_borders = [left, right, top, bottom].map(|inset| inset * self.pixel_size);
+        let x_fit = (dimensions[0] / (world_borders[0] + world_borders[1])).min(1f32);
+        let y_fit = (dimensions[1] / (world_borders[2] + world_borders[3])).min(1f32);
+
+        //mirrored left to right like TileView::tex_coords, so the image's right border ends up on the sprite's left
+        let xs = [
+            position[0],
+            position[0] + world_borders[1] * x_fit,
+            position[0] + dimensions[0] - world_borders[0] * x_fit,
+            position[0] + dimensions[0],
+        ];
+        //bottom to top
+        let ys = [
+            position[1],
+            position[1] + world_borders[3] * y_fit,
+            position[1] + dimensions[1] - world_borders[2] * y_fit,
+            position[1] + dimensions[1],
+        ];
+
+        let us = [u1, u1 - right / texture_width, u0 + left / texture_width, u0];
+        //the bottom of the sprite samples the bottom of the image
+        let vs = [v1, v1 - bottom / texture_height, v0 + top / texture_height, v0];
+
+        let mut vertices = Vec::new();
+        for row in 0..3 {
+            for col in 0..3 {
+                let mode = match (row, col) {
+                    (1, 1) => self.center_mode,
+                    _ => self.edge_mode,
+                };
+
+                let rect = [xs[col], ys[row], xs[col + 1], ys[row + 1]];
+                let uv = [us[col], vs[row], us[col + 1], vs[row + 1]];
+
+                //corners never repeat, the edges only repeat along their length
+                let tile_x = mode == SliceMode::Tile && col == 1;
+                let tile_y = mode == SliceMode::Tile && row == 1;
+
+                let tile_size = [
+                    if tile_x { (uv[2] - uv[0]).abs() * texture_width * self.pixel_size } else { 0f32 },
+                    if tile_y { (uv[3] - uv[1]).abs() * texture_height * self.pixel_size } else { 0f32 },
+                ];
+
+                push_tiled_quads(&mut vertices, rect, uv, tile_size, transform, color);
+            }
+        }
+
+        vertices
+    }
+}
+
+//fills rect with copies of uv, each tile_size big, a tile size of zero stretches along that axis
+fn push_tiled_quads(
+    vertices: &mut Vec<SpriteVertex>,
+    rect: [f32; 4],
+    uv: [f32; 4],
+    tile_size: [f32; 2],
+    transform: &GlobalTransform2d,
+    color: [f32; 4],
+) {
+    if rect[2] <= rect[0] || rect[3] <= rect[1] {
+        return;
+    }
+
+    let steps = |start: f32, end: f32, size: f32| -> Vec<(f32, f32)> {
+        if size <= 0f32 {
+            return vec![(start, end)];
+        }
+
+        let mut steps = Vec::new();
+        let mut current = start;
+        while current < end {
+            let next = (current + size).min(end);
+            steps.push((current, next));
+            current = next;
+        }
+        steps
+    };
+
+    for (x0, x1) in steps(rect[0], rect[2], tile_size[0]) {
+        for (y0, y1) in steps(rect[1], rect[3], tile_size[1]) {
+            //a cut short tile only samples as much of the source as it covers
+            let u_end = if tile_size[0] > 0f32 { uv[0] + (uv[2] - uv[0]) * (x1 - x0) / tile_size[0] } else { uv[2] };
+            let v_end = if tile_size[1] > 0f32 { uv[1] + (uv[3] - uv[1]) * (y1 - y0) / tile_size[1] } else { uv[3] };
+
+            let corner = |x: f32, y: f32, u: f32, v: f32| SpriteVertex {
+                position: transform.transform_point([x, y]),
+                tex_coord: [u, v],
+                color,
+            };
+
+            let bl = corner(x0, y0, uv[0], uv[1]);
+            let br = corner(x1, y0, u_end, uv[1]);
+            let tl = corner(x0, y1, uv[0], v_end);
+            let tr = corner(x1, y1, u_end, v_end);
+
+            vertices.extend_from_slice(&[bl, br, tl, tl, br, tr]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [f32; 4] = [1f32, 1f32, 1f32, 1f32];
+
+    //an 8x8 texture with 2 pixel borders, one world unit a pixel
+    fn vertices(nine_slice: NineSlice, dimensions: [f32; 2]) -> Vec<SpriteVertex> {
+        nine_slice.get_vertex_buffer([0f32, 0f32], dimensions, [0f32, 0f32, 1f32, 1f32], (8, 8), &GlobalTransform2d::default(), WHITE)
+    }
+
+    //the bottom left and top right corners of a quad
+    fn corners(quad: &[SpriteVertex]) -> [([f32; 2], [f32; 2]); 2] {
+        [(quad[0].position, quad[0].tex_coord), (quad[5].position, quad[5].tex_coord)]
+    }
+
+    #[test]
+    fn stretching_keeps_the_corners_and_fills_the_rest() {
+        let vertices = vertices(NineSlice::new([2f32, 2f32, 2f32, 2f32], 1f32), [10f32, 12f32]);
+        assert_eq!(vertices.len(), 9 * 6);
+
+        //the bottom left corner samples the image's bottom right, see the mirroring in get_vertex_buffer
+        assert_eq!(corners(&vertices[0..6]), [([0f32, 0f32], [1f32, 1f32]), ([2f32, 2f32], [0.75f32, 0.75f32])]);
+        //the center stretches over everything between the borders
+        assert_eq!(corners(&vertices[24..30]), [([2f32, 2f32], [0.75f32, 0.75f32]), ([8f32, 10f32], [0.25f32, 0.25f32])]);
+        assert_eq!(corners(&vertices[48..54]), [([8f32, 10f32], [0.25f32, 0.25f32]), ([10f32, 12f32], [0f32, 0f32])]);
+    }
+
+    #[test]
+    fn tiled_edges_repeat_and_cut_the_last_repeat_short() {
+        let vertices = vertices(NineSlice::new([2f32, 2f32, 2f32, 2f32], 1f32).with_modes(SliceMode::Tile, SliceMode::Stretch), [10f32, 4f32]);
+        //the edges between the corners are 6 units long, so they take one whole 4 unit repeat and half of another
+        //the borders take all 4 units of height, which leaves nothing of the sides and the center
+        assert_eq!(vertices.len(), (1 + 2 + 1 + 1 + 2 + 1) * 6);
+
+        let bottom_edge = &vertices[6..18];
+        assert_eq!(corners(&bottom_edge[0..6]), [([2f32, 0f32], [0.75f32, 1f32]), ([6f32, 2f32], [0.25f32, 0.75f32])]);
+        assert_eq!(corners(&bottom_edge[6..12]), [([6f32, 0f32], [0.75f32, 1f32]), ([8f32, 2f32], [0.5f32, 0.75f32])]);
+    }
+}
@@ -1,29 +1,42 @@
 use bevy_ecs::prelude::*;
 
-use super::{SpriteVertex, TileView};
+use crate::two_dimensional::GlobalTransform2d;
+
+use super::{BlendMode, NineSlice, SpriteVertex, TileView};
 
 #[derive(Component, Debug)]
 pub struct Sprite {
     pub position: [f32; 2],
     pub dimensions: [f32; 2],
-    pub color: [f32; 3],
+    //rgba tint, multiplied with the texture
+    pub color: [f32; 4],
+    //multiplied into the tint's alpha, so fades don't have to touch the color
+    pub opacity: f32,
+    pub blend_mode: BlendMode,
 
     pub depth: u32,
 
     texture_path: Option<String>,
-    tile_view: Option<TileView>
+    //laid out like the texture, the green channel points along the sprite's local y axis
+    normal_map_path: Option<String>,
+    tile_view: Option<TileView>,
+    nine_slice: Option<NineSlice>,
 }
 
 impl Sprite {
-    pub fn new(position: [f32; 2], dimensions: [f32; 2], color: [f32; 3]) -> Self {
+    pub fn new(position: [f32; 2], dimensions: [f32; 2], color: [f32; 4]) -> Self {
         Self {
             position,
             dimensions,
             color,
+            opacity: 1f32,
+            blend_mode: BlendMode::Alpha,
             depth: 0,
 
             texture_path: None,
-            tile_view: None
+            normal_map_path: None,
+            tile_view: None,
+            nine_slice: None,
         }
     }
 
@@ -32,22 +45,76 @@ impl Sprite {
         self
     }
 
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    //anything that lets what's underneath show through has to be drawn after the opaque sprites at its depth
+    pub fn is_translucent(&self) -> bool {
+        self.blend_mode != BlendMode::Alpha || self.opacity < 1f32 || self.color[3] < 1f32
+    }
+
     pub fn with_texture(mut self, path: &str) -> Self {
         self.texture_path = Some(String::from(path));
         self
     }
 
+    //only used by the lighting pass, sprites without one are lit as if they were flat
+    pub fn with_normal_map(mut self, path: &str) -> Self {
+        self.normal_map_path = Some(String::from(path));
+        self
+    }
+
     pub fn with_tile_in_texture(self, path: &str, rows: u32, cols: u32, row: u32, col: u32) -> Self {
         let mut texture_sprite = self.with_texture(path);
         texture_sprite.tile_view = Some(TileView::new(path, rows, cols, row, col));
         texture_sprite
     }
 
+    //draw the texture (or the tile view's cell) as a nine slice, see NineSlice
+    pub fn with_nine_slice(mut self, nine_slice: NineSlice) -> Self {
+        self.nine_slice = Some(nine_slice);
+        self
+    }
+
+    pub fn tile_view_mut(&mut self) -> Option<&mut TileView> {
+        self.tile_view.as_mut()
+    }
+
     pub fn texture_path(&self) -> &Option<String> {
         &self.texture_path
     }
 
-    pub fn get_vertex_buffer(&self) -> Vec<SpriteVertex> {
+    pub fn normal_map_path(&self) -> &Option<String> {
+        &self.normal_map_path
+    }
+
+    //hit test a world space point against the sprite's quad
+    pub fn contains_point(&self, transform: &GlobalTransform2d, point: [f32; 2]) -> bool {
+        if let Some(local) = transform.inverse_transform_point(point) {
+            local[0] >= self.position[0]
+                && local[0] <= self.position[0] + self.dimensions[0]
+                && local[1] >= self.position[1]
+                && local[1] <= self.position[1] + self.dimensions[1]
+        } else {
+            false
+        }
+    }
+
+    //texture_size is in pixels, of the texture the sprite is drawn with, only nine slices need it
+    pub fn get_vertex_buffer(&self, transform: &GlobalTransform2d, texture_size: (u32, u32)) -> Vec<SpriteVertex> {
+        let color = [self.color[0], self.color[1], self.color[2], self.color[3] * self.opacity];
+
+        if let Some(nine_slice) = self.nine_slice.as_ref() {
+            let region = self.tile_view.as_ref().map(|tile_view| tile_view.region()).unwrap_or([0f32, 0f32, 1f32, 1f32]);
+            return nine_slice.get_vertex_buffer(self.position, self.dimensions, region, texture_size, transform, color);
+        }
         let tex_coords = if let Some(tile_view) = self.tile_view.as_ref() {
             tile_view.tex_coords()
         } else {
@@ -56,27 +123,27 @@ impl Sprite {
         };
 
         let bl = SpriteVertex {
-            position: self.position,
+            position: transform.transform_point(self.position),
             tex_coord: tex_coords[0],
-            color: self.color
+            color
         };
 
         let br = SpriteVertex {
-            position: [self.position[0] + self.dimensions[0], self.position[1]],
+            position: transform.transform_point([self.position[0] + self.dimensions[0], self.position[1]]),
             tex_coord: tex_coords[1],
-            color: self.color
+            color
         };
 
         let tl = SpriteVertex {
-            position: [self.position[0], self.position[1] + self.dimensions[1]],
+            position: transform.transform_point([self.position[0], self.position[1] + self.dimensions[1]]),
             tex_coord: tex_coords[3],
-            color: self.color
+            color
         };
 
         let tr = SpriteVertex {
-            position: [self.position[0] + self.dimensions[0], self.position[1] + self.dimensions[1]],
+            position: transform.transform_point([self.position[0] + self.dimensions[0], self.position[1] + self.dimensions[1]]),
             tex_coord: tex_coords[2],
-            color: self.color
+            color
         };
 
         vec![bl, br, tl, tl, br, tr] 
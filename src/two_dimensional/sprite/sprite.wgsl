@@ -4,20 +4,16 @@ struct CameraUniform {
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
-struct CameraUniform {
-    view_ortho: mat4x4<f32>
-}
-
 
 struct VertexInput {
     @location(0) position: vec2<f32>,
     @location(1) tex_coord: vec2<f32>,
-    @location(2) color: vec3<f32>,
+    @location(2) color: vec4<f32>,
 }
 
 struct VertexOutput1 {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec3<f32>,
+    @location(0) color: vec4<f32>,
     @location(1) tex_coord: vec2<f32>,
 };
 
@@ -40,7 +36,16 @@ var t_diffuse: texture_2d<f32>;
 @group(1) @binding(1)
 var s_diffuse: sampler;
 
+//every blend mode expects premultiplied output
 @fragment
 fn fs_main(in: VertexOutput1) -> @location(0) vec4<f32> {
-    return  textureSample(t_diffuse, s_diffuse, in.tex_coord) * vec4<f32>(in.color, 1.0);
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coord) * in.color;
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
+
+//the texture is already premultiplied, so only the tint needs to be
+@fragment
+fn fs_premultiplied(in: VertexOutput1) -> @location(0) vec4<f32> {
+    let tint = vec4<f32>(in.color.rgb * in.color.a, in.color.a);
+    return textureSample(t_diffuse, s_diffuse, in.tex_coord) * tint;
 }
\ No newline at end of file
@@ -1,22 +1,18 @@
 use std::collections::HashMap;
 
 use bevy_ecs::prelude::*;
-use image::Rgba;
 use wgpu::{RenderPassDescriptor, RenderPipeline};
 
-use crate::{graphics::{RenderContext, RenderPass, Subpass, Uniform, Texture, TextureBindLayout}, two_dimensional::{camera::{CameraMatrix, Camera}}};
+use crate::{graphics::{RenderContext, RenderPass, Subpass, Uniform, TextureCache}, two_dimensional::{camera::{CameraMatrix, Camera}, text::{FontRegistry, TextBox, TextPass, TextSpace}, ActiveCamera, GlobalTransform2d, RenderLayers}};
 
-use super::{sprite_vertex::SpriteVertex, Sprite};
+use super::{sprite_vertex::SpriteVertex, BlendMode, Sprite};
 
 use itertools::Itertools;
 pub struct SpritePass {
     camera_uniform: Uniform,
-    render_pipeline: RenderPipeline,
-    texture_bind_layout: TextureBindLayout,
+    render_pipelines: HashMap<BlendMode, RenderPipeline>,
 }
 
-pub struct TextureCache(HashMap<String, (Texture, wgpu::BindGroup)>);
-
 impl RenderPass for SpritePass {
     fn get_name() -> &'static str {
         "Sprite Pass"
@@ -32,13 +28,10 @@ impl RenderPass for SpritePass {
 }
 
 impl SpritePass {
-    fn init(mut commands: Commands, render_context: Res<RenderContext>) {
+    fn init(mut commands: Commands, render_context: Res<RenderContext>, texture_cache: Res<TextureCache>) {
     //create a camera uniform
     let camera_uniform = Uniform::new::<CameraMatrix>(render_context.as_ref(), 0);
 
-    //create our texture layout here, and store it
-    let texture_bind_layout = TextureBindLayout::new(0, 1, &render_context);
-
     let shader = render_context
         .device
         .create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -51,29 +44,186 @@ impl SpritePass {
             .device
             .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&camera_uniform.bind_group_layout, texture_bind_layout.bind_group_layout()],
+                bind_group_layouts: &[&camera_uniform.bind_group_layout, texture_cache.bind_layout().bind_group_layout()],
                 push_constant_ranges: &[],
             });
-    //create our pipeline here
-    let render_pipeline =
+    //one pipeline per blend mode, they only differ in their blend state and fragment entry point
+    let render_pipelines = BlendMode::ALL
+        .iter()
+        .map(|blend_mode| (*blend_mode, Self::create_pipeline(&render_context, &render_pipeline_layout, &shader, &[SpriteVertex::desc()], *blend_mode)))
+        .collect();
+
+        commands.insert_resource(Self { camera_uniform, render_pipelines });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render(
+        sprites: Query<(Entity, &Sprite, Option<&GlobalTransform2d>, Option<&RenderLayers>)>,
+        text_boxes: Query<(&TextBox, Option<&GlobalTransform2d>, Option<&RenderLayers>)>,
+        cameras: Query<&Camera>,
+        active_camera: Option<Res<ActiveCamera>>,
+        mut sprite_pass: ResMut<SpritePass>,
+        mut texture_cache: ResMut<TextureCache>,
+        mut text_pass: Option<ResMut<TextPass>>,
+        font_registry: Option<Res<FontRegistry>>,
+        mut subpass: ResMut<Subpass>,
+        render_context: Res<RenderContext>,
+    ) {
+
+        //construct a vertex buffer from sprites
+        //may want to do this kind of caching in an update function, idk?
+        for (_, sprite, ..) in sprites.iter() { 
+            let empty_string = String::from("");
+            let sprite_texture = sprite.texture_path().as_ref().unwrap_or(&empty_string);
+            texture_cache.load(sprite_texture, &render_context);
+        }
+
+        let camera = match Camera::active(&cameras, active_camera.as_deref()) {
+            Some(camera) => camera,
+            None => return,
+        };
+
+        let empty_string = String::from("");
+        let seen_sprites = sprites
+            .iter()
+            .filter(|(.., layers)| camera.sees(*layers))
+            .map(|(entity, sprite, transform, _)| (entity, sprite, transform));
+        //sort back to front by depth, within a depth opaque sprites go first so translucent ones blend over them
+        //only opaque sprites are reordered to batch by blend mode and texture
+        //anything still tied goes by entity, query order changes as entities come and go and blended sprites would flicker
+        let sorted_sprites = seen_sprites
+            .sorted_by(|(entity_1, sprite_1, _), (entity_2, sprite_2, _)| {
+                Ord::cmp(&sprite_2.depth, &sprite_1.depth)
+                    .then(Ord::cmp(&sprite_1.is_translucent(), &sprite_2.is_translucent()))
+                    .then_with(|| {
+                        if sprite_1.is_translucent() || sprite_2.is_translucent() {
+                            std::cmp::Ordering::Equal
+                        } else {
+                            Ord::cmp(&sprite_1.blend_mode, &sprite_2.blend_mode)
+                                .then(Ord::cmp(sprite_1.texture_path(), sprite_2.texture_path()))
+                        }
+                    })
+                    .then(Ord::cmp(&entity_1.id(), &entity_2.id()))
+            })
+            .map(|(_, sprite, transform)| (sprite, transform));
+
+        //consecutive sprites that share a blend mode and texture can go out in one draw
+        let batched_sprites = sorted_sprites.group_by(|(sprite, _)| {
+            (sprite.depth, sprite.blend_mode, sprite.texture_path().as_ref().unwrap_or(&empty_string).clone())
+        });
+
+        //sprites that aren't part of the transform hierarchy are drawn as is
+        let identity = GlobalTransform2d::default();
+        let mut render_batches: Vec<(u32, BlendMode, &wgpu::BindGroup, wgpu::Buffer, u32)> = Vec::new();
+        for ((depth, blend_mode, sprite_texture), sprites) in batched_sprites.into_iter() {
+            let (texture, bind_group) = match texture_cache.get(&sprite_texture) {
+                Some((texture, bind_group)) => (texture, bind_group),
+                None => continue,
+            };
+
+            let vertices: Vec<SpriteVertex> = sprites
+                .flat_map(|(sprite, transform)| sprite.get_vertex_buffer(transform.unwrap_or(&identity), (texture.width, texture.height)))
+                .collect();
+
+            let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
+                &render_context.device,
+                &wgpu::util::BufferInitDescriptor {
+                    label: Some("Sprite Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                },
+            );
+            render_batches.push((depth, blend_mode, bind_group, vertex_buffer, vertices.len() as u32));
+        }
+
+        //world space text sorts in with the sprites, back to front
+        let world_text = text_boxes
+            .iter()
+            .filter(|(text_box, _, layers)| text_box.space == TextSpace::World && camera.sees(*layers))
+            .map(|(text_box, transform, _)| (text_box, transform))
+            .sorted_by(|(text_box_1, _), (text_box_2, _)| Ord::cmp(&text_box_2.depth, &text_box_1.depth));
+
+        //update our camera uniform
+        sprite_pass.camera_uniform.set_buffer(render_context.as_ref(), camera.get_matrix());
+
+        let subpass = &mut *subpass;
+        let encoder = subpass.encoder.as_mut().unwrap();
+
+        //the glyph brush needs its own render pass, so sprites are drawn in runs between the depths that have text
+        let mut batches = render_batches.iter().peekable();
+        let mut world_text = world_text.peekable();
+        loop {
+            let text_depth = world_text.peek().map(|(text_box, _)| text_box.depth);
+
+            {
+                let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                    label: Some("Sprite Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &subpass.texture,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+
+                camera.set_viewport(&mut render_pass);
+                render_pass.set_bind_group(0, &sprite_pass.camera_uniform.bind_group, &[]);
+                let mut current_blend_mode = None;
+                let run = batches.peeking_take_while(|(depth, ..)| text_depth.is_none_or(|text_depth| *depth >= text_depth));
+                for (_, blend_mode, texture_bind_group, vertex_buffer, num_vertices) in run {
+                    if current_blend_mode != Some(*blend_mode) {
+                        render_pass.set_pipeline(&sprite_pass.render_pipelines[blend_mode]);
+                        current_blend_mode = Some(*blend_mode);
+                    }
+                    render_pass.set_bind_group(1, texture_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.draw(0..*num_vertices, 0..1);
+                }
+            }
+
+            let text_depth = match text_depth {
+                Some(text_depth) => text_depth,
+                None => break,
+            };
+            let text_run = world_text.peeking_take_while(|(text_box, _)| text_box.depth == text_depth);
+            match (text_pass.as_deref_mut(), font_registry.as_deref()) {
+                (Some(text_pass), Some(font_registry)) => {
+                    text_pass.draw_world_text(text_run, font_registry, camera, &render_context, encoder, &subpass.texture)
+                }
+                _ => text_run.for_each(drop),
+            }
+        }
+    }
+
+    //also used by the tilemap and particle passes, which only differ from sprites in their shader and vertex buffers
+    pub(crate) fn create_pipeline(
+        render_context: &RenderContext,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        buffers: &[wgpu::VertexBufferLayout],
+        blend_mode: BlendMode,
+    ) -> RenderPipeline {
         render_context
             .device
             .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
                 label: Some("Render Pipeline"),
-                layout: Some(&render_pipeline_layout),
+                layout: Some(layout),
                 vertex: wgpu::VertexState {
-                    module: &shader,
+                    module: shader,
                     entry_point: "vs_main",           // 1.
-                    buffers: &[SpriteVertex::desc()], // 2.
+                    buffers,
                 },
                 fragment: Some(wgpu::FragmentState {
                     // 3.
-                    module: &shader,
-                    entry_point: "fs_main",
+                    module: shader,
+                    entry_point: blend_mode.fragment_entry_point(),
                     targets: &[Some(wgpu::ColorTargetState {
                         // 4.
                         format: render_context.config.format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        blend: Some(blend_mode.blend_state()),
                         write_mask: wgpu::ColorWrites::ALL,
                     })],
                 }),
@@ -96,130 +246,6 @@ impl SpritePass {
                     alpha_to_coverage_enabled: false, // 4.
                 },
                 multiview: None, // 5.
-            });
-
-        let blank_texture = Texture::new::<Rgba<u8>>(10, 10, vec![255, 255, 255, 255], &render_context);
-        let blank_texture_bind_group = texture_bind_layout.create_bind_group(&blank_texture, &render_context);
-        
-        let mut texture_cache = HashMap::new();
-        texture_cache.insert(String::from(""), (blank_texture, blank_texture_bind_group));
-
-        commands.insert_resource(Self { camera_uniform, render_pipeline, texture_bind_layout });
-        commands.insert_resource(TextureCache(texture_cache));
-    }
-
-    fn render(
-        sprites: Query<&Sprite>,
-        cameras: Query<&Camera>,
-        mut sprite_pass: ResMut<SpritePass>,
-        mut texture_cache: ResMut<TextureCache>,
-        mut subpass: ResMut<Subpass>,
-        render_context: Res<RenderContext>,
-    ) {
-
-        let mut staging_render_sets: Vec<HashMap<String, (&wgpu::BindGroup, Vec<SpriteVertex>)>> = Vec::new();
-
-        //construct our vertex buffers for each sprite with the same texture
-
-        //construct a vertex buffer from sprites
-        //may want to do this kind of caching in an update function, idk?
-        for sprite in sprites.iter() { 
-            let empty_string = String::from("");
-            let sprite_texture = sprite.texture_path().as_ref().unwrap_or(&empty_string);
-            if !texture_cache.0.contains_key(sprite_texture) {
-                let texture = Texture::load(sprite_texture, &render_context);
-                let bind_group = sprite_pass.texture_bind_layout.create_bind_group(&texture, &render_context);
-                texture_cache.0.insert(sprite_texture.clone(), (texture, bind_group));
-            }
-        }
-
-        //sort by depth here
-        let depth_sorted_sprites = sprites.iter().sorted_by(|sprite_1, sprite_2| Ord::cmp(&sprite_2.depth, &sprite_1.depth)).group_by(|sprite| sprite.depth);
-        /*for sprite in depth_sorted_sprites.into_iter() {
-            println!("{} {:?}", sprite.0, sprite.1.collect::<Vec<&Sprite>>());
-        }*/
-
-        for (depth, sprites) in depth_sorted_sprites.into_iter() {
-            let mut staging_buffers: HashMap<String, (&wgpu::BindGroup, Vec<SpriteVertex>)> = HashMap::new();
-            let sprites: Vec<&Sprite> = sprites.collect();
-
-            for sprite in sprites {
-                let empty_string = String::from("");
-                let sprite_texture = sprite.texture_path().as_ref().unwrap_or(&empty_string);
-                if let Some((_, vertices)) = staging_buffers.get_mut(sprite_texture) {
-                    let mut sprite_vertices = sprite.get_vertex_buffer();
-                    vertices.append(&mut sprite_vertices);
-                    continue;
-                } else if let Some((_, bind_group)) = texture_cache.0.get(sprite_texture) {
-                    staging_buffers.insert(sprite_texture.clone(), (bind_group, sprite.get_vertex_buffer()));
-                    continue;
-                }
-            }
-
-            staging_render_sets.push(staging_buffers);
-        }
-
-        /*for sprite in sprites.iter() {
-            let empty_string = String::from("");
-            let sprite_texture = sprite.texture_path().as_ref().unwrap_or(&empty_string);
-            if let Some((_, vertices)) = staging_render_sets.get_mut(sprite_texture) {
-                let mut sprite_vertices = sprite.get_vertex_buffer();
-                vertices.append(&mut sprite_vertices);
-                continue;
-            } else if let Some((_, bind_group)) = texture_cache.0.get(sprite_texture) {
-                staging_render_sets.insert(sprite_texture.clone(), (bind_group, sprite.get_vertex_buffer()));
-                continue;
-            }
-        }*/
-
-        let mut render_sets: Vec<Vec<(&wgpu::BindGroup, wgpu::Buffer, u32)>> = Vec::new();
-        for staged_render_set in staging_render_sets.into_iter() {
-            let mut depth_set = Vec::new();
-            //generate our vertex buff
-            for (_, (bind_group, vertex_vec)) in staged_render_set.into_iter() {
-                let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
-                    &render_context.device,
-                    &wgpu::util::BufferInitDescriptor {
-                        label: Some("Sprite Vertex Buffer"),
-                        contents: bytemuck::cast_slice(&vertex_vec),
-                        usage: wgpu::BufferUsages::VERTEX,
-                    },
-                );
-                depth_set.push((bind_group, vertex_buffer, vertex_vec.len() as u32));
-            }
-
-            render_sets.push(depth_set);
-        }
-
-        let camera = cameras.get_single().expect("There should be a camera in the scene!");
-        //update our camera uniform
-        sprite_pass.camera_uniform.set_buffer(render_context.as_ref(), camera.get_matrix());
-
-        let subpass = &mut *subpass;
-        let encoder = subpass.encoder.as_mut().unwrap();
-
-        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
-            label: Some("Sprite Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &subpass.texture,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Load,
-                    store: true,
-                },
-            })],
-            depth_stencil_attachment: None,
-        });
-
-
-        render_pass.set_pipeline(&sprite_pass.render_pipeline);
-        render_pass.set_bind_group(0, &sprite_pass.camera_uniform.bind_group, &[]);
-        for depth_set in render_sets.iter() {
-            for (texture_bind_group, vertex_buffer, num_vertices) in depth_set.iter() {
-                render_pass.set_bind_group(1, texture_bind_group, &[]);
-                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-                render_pass.draw(0..*num_vertices, 0..1);
-            }
-        }
+            })
     }
 }
@@ -3,12 +3,12 @@
 pub struct SpriteVertex {
     pub position: [f32; 2],
     pub tex_coord: [f32; 2],
-    pub color: [f32; 3],
+    pub color: [f32; 4],
 }
 
 impl SpriteVertex {
     const ATTRIBS: [wgpu::VertexAttribute; 3] =
-        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x3];
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
 
     pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
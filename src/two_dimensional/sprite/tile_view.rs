@@ -40,4 +40,14 @@ impl TileView {
         let tex_coords = [[x_start + x_step, y_start], [x_start, y_start], [x_start, y_start - y_step], [x_start + x_step, y_start - y_step]];
         tex_coords
     }
-}
\ No newline at end of file
+
+    //the cell as u0, v0, u1, v1 with v pointing down the image, rows are counted up from the bottom
+    pub fn region(&self) -> [f32; 4] {
+        let x_step = 1f32 / self.cols as f32;
+        let y_step = 1f32 / self.rows as f32;
+
+        let u0 = self.col as f32 * x_step;
+        let v1 = 1f32 - self.row as f32 * y_step;
+        [u0, v1 - y_step, u0 + x_step, v1]
+    }
+}
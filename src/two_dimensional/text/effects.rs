@@ -0,0 +1,39 @@
+//how a TextBox's glyphs are drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextRendering {
+    //rasterized by the glyph brush at the size it appears on screen
+    Raster,
+    //drawn from a signed distance field atlas, stays crisp at any scale and supports effects
+    Sdf,
+}
+
+//effects are measured in the same units as the box's scale, and only apply to sdf text
+//they reach at most a sixth of the text's scale past the edge of a glyph, anything further is cut off
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextEffects {
+    pub outline: Option<Outline>,
+    pub shadow: Option<Shadow>,
+    pub glow: Option<Glow>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outline {
+    pub color: [f32; 4],
+    pub width: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    pub color: [f32; 4],
+    //right and down from the text
+    pub offset: (f32, f32),
+    //how far the edge of the shadow is blurred, 0 is a hard edge
+    pub softness: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glow {
+    pub color: [f32; 4],
+    //how far the glow fades out over
+    pub radius: f32,
+}
@@ -0,0 +1,144 @@
+use std::{collections::HashMap, fmt, path::PathBuf};
+
+use wgpu_glyph::{
+    ab_glyph::{Font, FontArc},
+    FontId,
+};
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(PathBuf, std::io::Error),
+    Invalid(PathBuf),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontError::Io(path, error) => write!(f, "couldn't read {}: {}", path.display(), error),
+            FontError::Invalid(path) => write!(f, "{} is not a font we can read", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+//a loaded font, handles stay valid for as long as the registry exists
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FontHandle(usize);
+
+impl FontHandle {
+    //the embedded Inconsolata, always there
+    pub const DEFAULT: FontHandle = FontHandle(0);
+
+    pub(super) fn font_id(&self) -> FontId {
+        FontId(self.0)
+    }
+}
+
+//which font a TextBox asks for
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum FontRef {
+    #[default]
+    Default,
+    //looked up every frame, so the font can be loaded after the text box was made
+    Name(String),
+}
+
+//every font text can be drawn with, the text pass picks up newly loaded fonts before it draws
+pub struct FontRegistry {
+    fonts: Vec<FontArc>,
+    names: HashMap<String, FontHandle>,
+    //tried in order for characters the font doesn't have, the default font is always tried last
+    fallbacks: HashMap<FontHandle, Vec<FontHandle>>,
+}
+
+impl FontRegistry {
+    pub(super) fn new(default_font: FontArc) -> Self {
+        let mut names = HashMap::new();
+        names.insert(String::from("default"), FontHandle::DEFAULT);
+
+        Self {
+            fonts: vec![default_font],
+            names,
+            fallbacks: HashMap::new(),
+        }
+    }
+
+    //loads a ttf or otf file, a font already loaded under the same name is replaced for new text
+    pub fn load(&mut self, name: &str, path: &str) -> Result<FontHandle, FontError> {
+        let path = PathBuf::from(path);
+        let bytes = std::fs::read(&path).map_err(|error| FontError::Io(path.clone(), error))?;
+        let font = FontArc::try_from_vec(bytes).map_err(|_| FontError::Invalid(path))?;
+        Ok(self.add(name, font))
+    }
+
+    pub fn add(&mut self, name: &str, font: FontArc) -> FontHandle {
+        let handle = FontHandle(self.fonts.len());
+        self.fonts.push(font);
+        self.names.insert(String::from(name), handle);
+        handle
+    }
+
+    pub fn get(&self, name: &str) -> Option<FontHandle> {
+        self.names.get(name).copied()
+    }
+
+    pub fn set_fallbacks(&mut self, font: FontHandle, fallbacks: &[FontHandle]) {
+        self.fallbacks.insert(font, fallbacks.to_vec());
+    }
+
+    //unknown names fall back to the default font
+    pub fn resolve(&self, font: &FontRef) -> FontHandle {
+        match font {
+            FontRef::Default => FontHandle::DEFAULT,
+            FontRef::Name(name) => self.get(name).unwrap_or(FontHandle::DEFAULT),
+        }
+    }
+
+    pub(super) fn fonts(&self) -> &[FontArc] {
+        &self.fonts
+    }
+
+    //splits text into runs that can each be drawn with one font, picking the first font in the chain that has each character
+    pub(super) fn runs<'a>(&self, text: &'a str, font: FontHandle) -> Vec<(&'a str, FontHandle)> {
+        let mut chain = vec![font];
+        if let Some(fallbacks) = self.fallbacks.get(&font) {
+            chain.extend(fallbacks.iter().copied());
+        }
+        chain.push(FontHandle::DEFAULT);
+
+        let font_for = |character: char| {
+            //whitespace and control characters stay with the run they are in
+            if character.is_whitespace() || character.is_control() {
+                return None;
+            }
+            chain
+                .iter()
+                .copied()
+                .find(|handle| self.fonts.get(handle.0).is_some_and(|font| font.glyph_id(character).0 != 0))
+                .or(Some(font))
+        };
+
+        let mut runs: Vec<(&'a str, FontHandle)> = Vec::new();
+        let mut run_start = 0;
+        let mut run_font = font;
+        for (index, character) in text.char_indices() {
+            let character_font = match font_for(character) {
+                Some(character_font) => character_font,
+                None => continue,
+            };
+
+            if character_font != run_font {
+                if index > run_start {
+                    runs.push((&text[run_start..index], run_font));
+                }
+                run_start = index;
+                run_font = character_font;
+            }
+        }
+        if run_start < text.len() {
+            runs.push((&text[run_start..], run_font));
+        }
+        runs
+    }
+}
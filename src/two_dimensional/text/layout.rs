@@ -0,0 +1,126 @@
+use std::hash::{Hash, Hasher};
+
+use glyph_brush::{
+    ab_glyph::{Font, Rect, ScaleFont},
+    BuiltInLineBreaker, GlyphPositioner, Layout, SectionGeometry, SectionGlyph, ToSectionText, VerticalAlign,
+};
+
+use super::FontRef;
+
+//part of a rich text box, anything left as none comes from the box
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextSpan {
+    pub text: String,
+    pub color: Option<[f32; 4]>,
+    pub scale: Option<f32>,
+    pub font: Option<FontRef>,
+}
+
+impl TextSpan {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: String::from(text),
+            color: None,
+            scale: None,
+            font: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    pub fn with_font_name(mut self, name: &str) -> Self {
+        self.font = Some(FontRef::Name(String::from(name)));
+        self
+    }
+}
+
+//a built in glyph brush layout with extra space between lines, which glyph brush doesn't do on its own
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct SpacedLayout {
+    pub layout: Layout<BuiltInLineBreaker>,
+    //multiplies the line height, 1 is the font's own spacing
+    pub line_spacing: f32,
+    pub line_height: f32,
+}
+
+impl Hash for SpacedLayout {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.layout.hash(state);
+        self.line_spacing.to_bits().hash(state);
+        self.line_height.to_bits().hash(state);
+    }
+}
+
+impl GlyphPositioner for SpacedLayout {
+    fn calculate_glyphs<F, S>(&self, fonts: &[F], geometry: &SectionGeometry, sections: &[S]) -> Vec<SectionGlyph>
+    where
+        F: Font,
+        S: ToSectionText,
+    {
+        let mut glyphs = self.layout.calculate_glyphs(fonts, geometry, sections);
+        let extra = (self.line_spacing - 1f32) * self.line_height;
+        if extra == 0f32 || glyphs.is_empty() {
+            return glyphs;
+        }
+
+        //every glyph on a line shares its baseline, so the baselines tell us which line a glyph is on
+        let mut baselines: Vec<f32> = glyphs.iter().map(|glyph| glyph.glyph.position.y).collect();
+        baselines.sort_by(|a, b| a.total_cmp(b));
+        baselines.dedup_by(|a, b| (*a - *b).abs() < 0.5f32);
+
+        //keep the block where the vertical alignment put it
+        let v_align = match self.layout {
+            Layout::SingleLine { v_align, .. } | Layout::Wrap { v_align, .. } => v_align,
+        };
+        let total = extra * (baselines.len() - 1) as f32;
+        let shift = match v_align {
+            VerticalAlign::Top => 0f32,
+            VerticalAlign::Center => -total / 2f32,
+            VerticalAlign::Bottom => -total,
+        };
+
+        for glyph in glyphs.iter_mut() {
+            let line = baselines.partition_point(|baseline| *baseline < glyph.glyph.position.y - 0.5f32);
+            glyph.glyph.position.y += extra * line as f32 + shift;
+        }
+        glyphs
+    }
+
+    fn bounds_rect(&self, geometry: &SectionGeometry) -> Rect {
+        self.layout.bounds_rect(geometry)
+    }
+}
+
+//the size of laid out glyphs, from the left of the first to the end of the widest line
+//and from the top of the first line to the bottom of the last
+pub(super) fn glyph_extent<F: Font>(fonts: &[F], glyphs: &[SectionGlyph]) -> (f32, f32) {
+    let mut min = (f32::INFINITY, f32::INFINITY);
+    let mut max = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+
+    for section_glyph in glyphs {
+        let glyph = &section_glyph.glyph;
+        let font = match fonts.get(section_glyph.font_id.0) {
+            Some(font) => font.as_scaled(glyph.scale),
+            None => continue,
+        };
+
+        min.0 = min.0.min(glyph.position.x);
+        max.0 = max.0.max(glyph.position.x + font.h_advance(glyph.id));
+        min.1 = min.1.min(glyph.position.y - font.ascent());
+        max.1 = max.1.max(glyph.position.y - font.descent());
+    }
+
+    if min.0 > max.0 {
+        (0f32, 0f32)
+    } else {
+        (max.0 - min.0, max.1 - min.1)
+    }
+}
@@ -1,3 +1,16 @@
 mod text_pass;
+pub use text_pass::{TextPass, TextBox, TextSpace};
 
-pub use text_pass::{TextPass, TextBox};
\ No newline at end of file
+mod font_registry;
+pub use font_registry::{FontHandle, FontRef, FontRegistry};
+
+mod effects;
+pub use effects::{Glow, Outline, Shadow, TextEffects, TextRendering};
+
+mod sdf_atlas;
+mod sdf_text;
+
+mod layout;
+pub use layout::TextSpan;
+
+pub use wgpu_glyph::{HorizontalAlign, VerticalAlign};
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use wgpu_glyph::{
+    ab_glyph::{point, Font, FontArc, GlyphId, Rect},
+    FontId,
+};
+
+use crate::graphics::{RenderContext, Texture, TextureBindLayout};
+
+//glyphs are rasterized at this many pixels per line before their distance field is taken
+pub(super) const RASTER_SCALE: f32 = 48f32;
+//how far past a glyph's edge the distance field reaches, in raster pixels
+pub(super) const SPREAD: f32 = 8f32;
+const ATLAS_SIZE: u32 = 2048;
+//room between glyphs so linear filtering doesn't bleed one into the next
+const GUTTER: u32 = 1;
+//stand in for infinity that stays finite through the distance transform
+const FAR: f64 = 1e20;
+
+//where a glyph is in the atlas and where its quad goes relative to the glyph's origin
+#[derive(Clone, Copy, Debug)]
+pub(super) struct SdfGlyph {
+    //in raster pixels, y pointing down, includes the spread
+    pub bounds: Rect,
+    //u0, v0, u1, v1
+    pub region: [f32; 4],
+}
+
+//distance fields of every glyph drawn so far, one channel with 0.5 on the outline and more than that inside
+//glyphs don't depend on the size text is drawn at, so each one is only made once
+pub(super) struct SdfAtlas {
+    texture: Texture,
+    pub bind_group: wgpu::BindGroup,
+
+    //glyphs without an outline, like spaces, are stored as none
+    glyphs: HashMap<(FontId, GlyphId), Option<SdfGlyph>>,
+    //glyphs are packed into shelves left to right, a new shelf is started when a row is full
+    cursor: (u32, u32),
+    shelf_height: u32,
+    //set when a glyph didn't fit, the atlas starts over once the frame is done
+    overflowed: bool,
+}
+
+impl SdfAtlas {
+    pub fn new(texture_bind_layout: &TextureBindLayout, render_context: &RenderContext) -> Self {
+        let texture = Texture::empty(ATLAS_SIZE, ATLAS_SIZE, wgpu::TextureFormat::R8Unorm, render_context);
+        let bind_group = texture_bind_layout.create_bind_group(&texture, render_context);
+
+        Self {
+            texture,
+            bind_group,
+
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            shelf_height: 0,
+            overflowed: false,
+        }
+    }
+
+    pub fn glyph(&mut self, fonts: &[FontArc], font_id: FontId, glyph_id: GlyphId, render_context: &RenderContext) -> Option<SdfGlyph> {
+        if let Some(glyph) = self.glyphs.get(&(font_id, glyph_id)) {
+            return *glyph;
+        }
+
+        let outline = fonts.get(font_id.0)?.outline_glyph(glyph_id.with_scale(RASTER_SCALE));
+        let outline = match outline {
+            Some(outline) => outline,
+            None => {
+                self.glyphs.insert((font_id, glyph_id), None);
+                return None;
+            }
+        };
+
+        let glyph_bounds = outline.px_bounds();
+        let glyph_size = (glyph_bounds.width() as usize, glyph_bounds.height() as usize);
+        let mut coverage = vec![0f32; glyph_size.0 * glyph_size.1];
+        outline.draw(|x, y, value| {
+            if let Some(pixel) = coverage.get_mut(y as usize * glyph_size.0 + x as usize) {
+                *pixel = value;
+            }
+        });
+
+        let padding = SPREAD as usize;
+        let size = (glyph_size.0 + 2 * padding, glyph_size.1 + 2 * padding);
+        let origin = match self.allocate((size.0 as u32, size.1 as u32)) {
+            Some(origin) => origin,
+            None => {
+                self.overflowed = true;
+                return None;
+            }
+        };
+
+        let field = distance_field(&coverage, glyph_size, padding);
+        self.texture.write_region(origin, (size.0 as u32, size.1 as u32), &field, render_context);
+
+        let atlas_size = ATLAS_SIZE as f32;
+        let glyph = SdfGlyph {
+            bounds: Rect {
+                min: point(glyph_bounds.min.x - SPREAD, glyph_bounds.min.y - SPREAD),
+                max: point(glyph_bounds.max.x + SPREAD, glyph_bounds.max.y + SPREAD),
+            },
+            region: [
+                origin.0 as f32 / atlas_size,
+                origin.1 as f32 / atlas_size,
+                (origin.0 + size.0 as u32) as f32 / atlas_size,
+                (origin.1 + size.1 as u32) as f32 / atlas_size,
+            ],
+        };
+        self.glyphs.insert((font_id, glyph_id), Some(glyph));
+        Some(glyph)
+    }
+
+    //glyphs that were drawn this frame are still in the texture, so the atlas is only emptied between frames
+    //the next frame then fills it with just the glyphs it uses
+    pub fn end_frame(&mut self) {
+        if self.overflowed {
+            self.glyphs.clear();
+            self.cursor = (0, 0);
+            self.shelf_height = 0;
+            self.overflowed = false;
+        }
+    }
+
+    fn allocate(&mut self, size: (u32, u32)) -> Option<(u32, u32)> {
+        if self.cursor.0 + size.0 > ATLAS_SIZE {
+            self.cursor = (0, self.cursor.1 + self.shelf_height + GUTTER);
+            self.shelf_height = 0;
+        }
+        if size.0 > ATLAS_SIZE || self.cursor.1 + size.1 > ATLAS_SIZE {
+            return None;
+        }
+
+        let origin = self.cursor;
+        self.cursor.0 += size.0 + GUTTER;
+        self.shelf_height = self.shelf_height.max(size.1);
+        Some(origin)
+    }
+}
+
+//signed distance from every pixel of the padded glyph to the outline, mapped so 0.5 is on the outline
+//partly covered pixels count as being part way to the outline, which keeps edges smooth
+fn distance_field(coverage: &[f32], glyph_size: (usize, usize), padding: usize) -> Vec<u8> {
+    let size = (glyph_size.0 + 2 * padding, glyph_size.1 + 2 * padding);
+
+    //squared distance to the nearest outside pixel and to the nearest inside pixel
+    let mut to_outside = vec![0f64; size.0 * size.1];
+    let mut to_inside = vec![FAR; size.0 * size.1];
+    for y in 0..glyph_size.1 {
+        for x in 0..glyph_size.0 {
+            let value = coverage[y * glyph_size.0 + x].clamp(0f32, 1f32) as f64;
+            let index = (y + padding) * size.0 + x + padding;
+            (to_outside[index], to_inside[index]) = if value >= 1f64 {
+                (FAR, 0f64)
+            } else if value <= 0f64 {
+                (0f64, FAR)
+            } else {
+                ((value - 0.5f64).max(0f64).powi(2), (0.5f64 - value).max(0f64).powi(2))
+            };
+        }
+    }
+
+    distance_transform(&mut to_outside, size);
+    distance_transform(&mut to_inside, size);
+
+    to_outside
+        .iter()
+        .zip(to_inside.iter())
+        .map(|(outside, inside)| {
+            let distance = (outside.sqrt() - inside.sqrt()) as f32;
+            ((0.5f32 + distance / (2f32 * SPREAD)).clamp(0f32, 1f32) * 255f32).round() as u8
+        })
+        .collect()
+}
+
+//exact squared euclidean distance transform, one dimension at a time (Felzenszwalb and Huttenlocher)
+fn distance_transform(grid: &mut [f64], size: (usize, usize)) {
+    let length = size.0.max(size.1);
+    let mut line = vec![0f64; length];
+    let mut result = vec![0f64; length];
+    let mut parabolas = vec![0usize; length];
+    let mut boundaries = vec![0f64; length + 1];
+
+    for x in 0..size.0 {
+        for (y, value) in line.iter_mut().enumerate().take(size.1) {
+            *value = grid[y * size.0 + x];
+        }
+        distance_transform_line(&line[..size.1], &mut result, &mut parabolas, &mut boundaries);
+        for (y, value) in result.iter().enumerate().take(size.1) {
+            grid[y * size.0 + x] = *value;
+        }
+    }
+
+    for y in 0..size.1 {
+        line[..size.0].copy_from_slice(&grid[y * size.0..(y + 1) * size.0]);
+        distance_transform_line(&line[..size.0], &mut result, &mut parabolas, &mut boundaries);
+        grid[y * size.0..(y + 1) * size.0].copy_from_slice(&result[..size.0]);
+    }
+}
+
+//the lower envelope of the parabolas rooted at each sample
+fn distance_transform_line(line: &[f64], result: &mut [f64], parabolas: &mut [usize], boundaries: &mut [f64]) {
+    let intersection = |q: usize, p: usize| {
+        let (q_position, p_position) = (q as f64, p as f64);
+        ((line[q] + q_position * q_position) - (line[p] + p_position * p_position)) / (2f64 * (q_position - p_position))
+    };
+
+    let mut k = 0;
+    parabolas[0] = 0;
+    boundaries[0] = -FAR;
+    boundaries[1] = FAR;
+    for q in 1..line.len() {
+        let mut s = intersection(q, parabolas[k]);
+        while s <= boundaries[k] {
+            k -= 1;
+            s = intersection(q, parabolas[k]);
+        }
+        k += 1;
+        parabolas[k] = q;
+        boundaries[k] = s;
+        boundaries[k + 1] = FAR;
+    }
+
+    k = 0;
+    for (q, value) in result.iter_mut().enumerate().take(line.len()) {
+        while boundaries[k + 1] < q as f64 {
+            k += 1;
+        }
+        let offset = q as f64 - parabolas[k] as f64;
+        *value = offset * offset + line[parabolas[k]];
+    }
+}
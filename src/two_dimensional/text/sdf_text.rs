@@ -0,0 +1,281 @@
+use cgmath::{Matrix4, Vector4};
+use wgpu::{RenderPassDescriptor, RenderPipeline};
+use wgpu_glyph::{GlyphPositioner, SectionGeometry};
+
+use crate::{
+    graphics::{RenderContext, TextureBindLayout, Uniform},
+    two_dimensional::camera::{Camera, CameraMatrix},
+};
+
+use super::{
+    sdf_atlas::{SdfAtlas, RASTER_SCALE, SPREAD},
+    FontRegistry, TextBox, TextSpace,
+};
+
+//two triangles of a unit quad, the corner is how far along the glyph's axes a vertex is
+const QUAD: [[f32; 2]; 6] = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]];
+
+//effects can't reach further than the distance field does
+const MAX_EFFECT_SIZE: f32 = SPREAD - 1f32;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub(super) struct SdfGlyphInstance {
+    //the glyph's quad is origin + corner.x * axis_x + corner.y * axis_y, already in world or screen space
+    origin: [f32; 2],
+    axis_x: [f32; 2],
+    axis_y: [f32; 2],
+    region: [f32; 4],
+    color: [f32; 4],
+    outline_color: [f32; 4],
+    shadow_color: [f32; 4],
+    glow_color: [f32; 4],
+    //outline width, glow radius and shadow softness, in distance field pixels
+    effect_sizes: [f32; 3],
+    //in texture coordinates
+    shadow_offset: [f32; 2],
+}
+
+impl SdfGlyphInstance {
+    const ATTRIBS: [wgpu::VertexAttribute; 10] = wgpu::vertex_attr_array![
+        1 => Float32x2, 2 => Float32x2, 3 => Float32x2, 4 => Float32x4, 5 => Float32x4,
+        6 => Float32x4, 7 => Float32x4, 8 => Float32x4, 9 => Float32x3, 10 => Float32x2
+    ];
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SdfGlyphInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+unsafe impl bytemuck::Pod for SdfGlyphInstance {}
+unsafe impl bytemuck::Zeroable for SdfGlyphInstance {}
+
+const QUAD_ATTRIBS: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![0 => Float32x2];
+
+fn quad_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &QUAD_ATTRIBS,
+    }
+}
+
+//draws sdf text boxes as instanced quads, glyphs are laid out by the same layout the glyph brush uses
+pub(super) struct SdfText {
+    //world text is drawn with the camera, screen text with a pixel projection
+    world_uniform: Uniform,
+    screen_uniform: Uniform,
+    render_pipeline: RenderPipeline,
+    quad_buffer: wgpu::Buffer,
+
+    atlas: SdfAtlas,
+}
+
+impl SdfText {
+    pub fn new(render_context: &RenderContext) -> Self {
+        let world_uniform = Uniform::new::<CameraMatrix>(render_context, 0);
+        let screen_uniform = Uniform::new::<CameraMatrix>(render_context, 0);
+        let texture_bind_layout = TextureBindLayout::new(0, 1, render_context);
+
+        let shader = render_context
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Sdf Text Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("sdf_text.wgsl").into()),
+            });
+
+        let render_pipeline_layout =
+            render_context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Sdf Text Pipeline Layout"),
+                    bind_group_layouts: &[&world_uniform.bind_group_layout, texture_bind_layout.bind_group_layout()],
+                    push_constant_ranges: &[],
+                });
+
+        let render_pipeline = render_context
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Sdf Text Pipeline"),
+                layout: Some(&render_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[quad_desc(), SdfGlyphInstance::desc()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: render_context.config.format,
+                        blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            });
+
+        let quad_buffer = wgpu::util::DeviceExt::create_buffer_init(
+            &render_context.device,
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Sdf Text Quad Buffer"),
+                contents: bytemuck::cast_slice(&QUAD),
+                usage: wgpu::BufferUsages::VERTEX,
+            },
+        );
+
+        let atlas = SdfAtlas::new(&texture_bind_layout, render_context);
+
+        Self {
+            world_uniform,
+            screen_uniform,
+            render_pipeline,
+            quad_buffer,
+
+            atlas,
+        }
+    }
+
+    pub fn set_matrix(&mut self, space: TextSpace, matrix: CameraMatrix, render_context: &RenderContext) {
+        match space {
+            TextSpace::World => self.world_uniform.set_buffer(render_context, matrix),
+            TextSpace::Screen => self.screen_uniform.set_buffer(render_context, matrix),
+        }
+    }
+
+    //lays the box out at its own scale, so nothing depends on the size it ends up on screen
+    //layout_to_target takes the layout's coordinates, y pointing down, into world or screen space
+    #[allow(clippy::too_many_arguments)]
+    pub fn queue(
+        &mut self,
+        text_box: &TextBox,
+        origin: (f32, f32),
+        default_bounds: (f32, f32),
+        layout_to_target: Matrix4<f32>,
+        font_registry: &FontRegistry,
+        render_context: &RenderContext,
+        instances: &mut Vec<SdfGlyphInstance>,
+    ) {
+        let section = text_box.section(origin, default_bounds, 1f32, font_registry);
+        let geometry = SectionGeometry {
+            screen_position: section.screen_position,
+            bounds: section.bounds,
+        };
+        let glyphs = text_box.layout(1f32).calculate_glyphs(font_registry.fonts(), &geometry, &section.text);
+
+        let effects = &text_box.effects;
+        let transparent = [0f32; 4];
+        for section_glyph in glyphs {
+            let glyph = &section_glyph.glyph;
+            let sdf_glyph = match self.atlas.glyph(font_registry.fonts(), section_glyph.font_id, glyph.id, render_context) {
+                Some(sdf_glyph) => sdf_glyph,
+                None => continue,
+            };
+
+            let raster_per_unit = RASTER_SCALE / glyph.scale.y;
+            let scale = (glyph.scale.x / RASTER_SCALE, glyph.scale.y / RASTER_SCALE);
+            let bounds = sdf_glyph.bounds;
+            let top_left = (glyph.position.x + bounds.min.x * scale.0, glyph.position.y + bounds.min.y * scale.1);
+            let size = (bounds.width() * scale.0, bounds.height() * scale.1);
+
+            let origin = layout_to_target * Vector4::new(top_left.0, top_left.1, 0f32, 1f32);
+            let axis_x = layout_to_target * Vector4::new(size.0, 0f32, 0f32, 0f32);
+            let axis_y = layout_to_target * Vector4::new(0f32, size.1, 0f32, 0f32);
+
+            let effect_size = |size: f32| (size * raster_per_unit).clamp(0f32, MAX_EFFECT_SIZE);
+            let uv_per_raster = (sdf_glyph.region[2] - sdf_glyph.region[0]) / bounds.width();
+            let shadow_offset = effects.shadow.map_or([0f32; 2], |shadow| {
+                let offset = |offset: f32| (offset * raster_per_unit).clamp(-MAX_EFFECT_SIZE, MAX_EFFECT_SIZE) * uv_per_raster;
+                [offset(shadow.offset.0), offset(shadow.offset.1)]
+            });
+
+            instances.push(SdfGlyphInstance {
+                origin: [origin.x, origin.y],
+                axis_x: [axis_x.x, axis_x.y],
+                axis_y: [axis_y.x, axis_y.y],
+                region: sdf_glyph.region,
+                color: section.text[section_glyph.section_index].extra.color,
+                outline_color: effects.outline.map_or(transparent, |outline| outline.color),
+                shadow_color: effects.shadow.map_or(transparent, |shadow| shadow.color),
+                glow_color: effects.glow.map_or(transparent, |glow| glow.color),
+                effect_sizes: [
+                    effects.outline.map_or(0f32, |outline| effect_size(outline.width)),
+                    effects.glow.map_or(0f32, |glow| effect_size(glow.radius)),
+                    effects.shadow.map_or(0f32, |shadow| effect_size(shadow.softness)),
+                ],
+                shadow_offset,
+            });
+        }
+    }
+
+    pub fn draw(
+        &self,
+        instances: &[SdfGlyphInstance],
+        space: TextSpace,
+        camera: &Camera,
+        render_context: &RenderContext,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+    ) {
+        if instances.is_empty() {
+            return;
+        }
+
+        let instance_buffer = wgpu::util::DeviceExt::create_buffer_init(
+            &render_context.device,
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Sdf Text Instance Buffer"),
+                contents: bytemuck::cast_slice(instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            },
+        );
+        let uniform = match space {
+            TextSpace::World => &self.world_uniform,
+            TextSpace::Screen => &self.screen_uniform,
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Sdf Text Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        camera.set_viewport(&mut render_pass);
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &uniform.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.atlas.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.quad_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+        render_pass.draw(0..QUAD.len() as u32, 0..instances.len() as u32);
+    }
+
+    pub fn end_frame(&mut self) {
+        self.atlas.end_frame();
+    }
+}
@@ -0,0 +1,110 @@
+struct CameraUniform {
+    view_ortho: mat4x4<f32>
+}
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+
+struct QuadVertex {
+    //0 to 1 along each of the glyph's axes, the top left of the glyph is 0, 0
+    @location(0) corner: vec2<f32>,
+}
+
+struct GlyphInstance {
+    @location(1) origin: vec2<f32>,
+    @location(2) axis_x: vec2<f32>,
+    @location(3) axis_y: vec2<f32>,
+    //u0, v0, u1, v1
+    @location(4) region: vec4<f32>,
+    @location(5) color: vec4<f32>,
+    @location(6) outline_color: vec4<f32>,
+    @location(7) shadow_color: vec4<f32>,
+    @location(8) glow_color: vec4<f32>,
+    //outline width, glow radius and shadow softness, in distance field pixels
+    @location(9) effect_sizes: vec3<f32>,
+    @location(10) shadow_offset: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+    @location(1) region: vec4<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) outline_color: vec4<f32>,
+    @location(4) shadow_color: vec4<f32>,
+    @location(5) glow_color: vec4<f32>,
+    @location(6) effect_sizes: vec3<f32>,
+    @location(7) shadow_offset: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    vertex: QuadVertex,
+    glyph: GlyphInstance,
+) -> VertexOutput {
+    let position = glyph.origin + vertex.corner.x * glyph.axis_x + vertex.corner.y * glyph.axis_y;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_ortho * vec4<f32>(position, 0.0, 1.0);
+    out.tex_coord = mix(glyph.region.xy, glyph.region.zw, vertex.corner);
+    out.region = glyph.region;
+    out.color = glyph.color;
+    out.outline_color = glyph.outline_color;
+    out.shadow_color = glyph.shadow_color;
+    out.glow_color = glyph.glow_color;
+    out.effect_sizes = glyph.effect_sizes;
+    out.shadow_offset = glyph.shadow_offset;
+    return out;
+}
+
+@group(1) @binding(0)
+var t_field: texture_2d<f32>;
+@group(1) @binding(1)
+var s_field: sampler;
+
+//must match SPREAD in sdf_atlas.rs
+let SPREAD: f32 = 8.0;
+
+//distance field pixels from the outline, positive inside the glyph
+fn signed_distance(field: f32) -> f32 {
+    return (field - 0.5) * 2.0 * SPREAD;
+}
+
+fn coverage(distance: f32, edge_width: f32) -> f32 {
+    return clamp(distance / edge_width + 0.5, 0.0, 1.0);
+}
+
+fn layer(color: vec4<f32>, amount: f32) -> vec4<f32> {
+    let alpha = color.a * amount;
+    return vec4<f32>(color.rgb * alpha, alpha);
+}
+
+fn over(top: vec4<f32>, bottom: vec4<f32>) -> vec4<f32> {
+    return top + bottom * (1.0 - top.a);
+}
+
+//outputs premultiplied color, layered back to front as shadow, glow, outline and then the glyph
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = signed_distance(textureSample(t_field, s_field, in.tex_coord).r);
+    //kept inside the glyph's own region so the shadow never picks up the glyph next to it in the atlas
+    let shadow_coord = clamp(in.tex_coord - in.shadow_offset, in.region.xy, in.region.zw);
+    let shadow_distance = signed_distance(textureSample(t_field, s_field, shadow_coord).r);
+
+    //how much the distance changes over a screen pixel, so edges are a pixel wide at any scale
+    let edge_width = max(fwidth(distance), 0.0001);
+
+    let outline_width = in.effect_sizes.x;
+    let glow_radius = in.effect_sizes.y;
+    let shadow_softness = in.effect_sizes.z;
+
+    //the outline and shadow grow out from the glyph's edge, the glow fades out from the outline's edge
+    let outer_distance = distance + outline_width;
+    let glow = clamp(1.0 + outer_distance / max(glow_radius, edge_width), 0.0, 1.0);
+
+    var color = layer(in.shadow_color, coverage(shadow_distance + outline_width, edge_width + shadow_softness));
+    color = over(layer(in.glow_color, glow * glow), color);
+    color = over(layer(in.outline_color, coverage(outer_distance, edge_width)), color);
+    color = over(layer(in.color, coverage(distance, edge_width)), color);
+    return color;
+}
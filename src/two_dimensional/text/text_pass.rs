@@ -5,21 +5,61 @@ use std::{
 
 use bevy_ecs::prelude::*;
 
-use crate::graphics::{RenderContext, RenderPass, Subpass};
-use wgpu_glyph::{ab_glyph, GlyphBrush, GlyphBrushBuilder, Section, Text};
+use cgmath::{Matrix4, Vector3};
+
+use crate::{graphics::{RenderContext, RenderPass, Subpass}, two_dimensional::{camera::{Camera, CameraMatrix}, ActiveCamera, GlobalTransform2d, RenderLayers}};
+use wgpu_glyph::{ab_glyph, GlyphBrush, GlyphBrushBuilder, GlyphPositioner, HorizontalAlign, Layout, Region, Section, SectionGeometry, Text, VerticalAlign};
+
+use super::{
+    layout::{glyph_extent, SpacedLayout},
+    sdf_text::SdfText,
+    FontRef, FontRegistry, Glow, Outline, Shadow, TextEffects, TextRendering, TextSpan,
+};
 
 //update this text pass every frame
 pub struct TextPass {
     glyph_brush: GlyphBrush<()>,
-    staging_belt: Arc<Mutex<wgpu::util::StagingBelt>>
+    staging_belt: Arc<Mutex<wgpu::util::StagingBelt>>,
+    //how many of the registry's fonts the glyph brush knows about, font ids match registry handles
+    fonts_in_brush: usize,
+    sdf_text: SdfText,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextSpace {
+    //positions and scales are in screen pixels from the top left of the camera's viewport
+    //a transform on the same entity is applied in screen pixels too
+    Screen,
+    //positions and scales are in world units, the text moves with the camera and is drawn by the sprite pass
+    //so it sorts with sprites by depth, text goes over sprites of the same depth
+    World,
 }
 
 #[derive(Component)]
 pub struct TextBox {
     pub text: String,
+    //spans drawn after the text, for rich text
+    pub spans: Vec<TextSpan>,
+    //the top left of the box, or the point the text is aligned to when the box has no bounds
     pub position: (f32, f32),
     pub color: [f32; 4],
+    //the height of a line
     pub scale: f32,
+    pub space: TextSpace,
+    //only used by world space text, see Sprite::depth
+    pub depth: u32,
+    pub font: FontRef,
+    pub rendering: TextRendering,
+    pub effects: TextEffects,
+
+    //the width and height of the box, text is wrapped to the width and aligned inside the box
+    //screen text without bounds is clipped to the viewport
+    pub bounds: Option<(f32, f32)>,
+    pub h_align: HorizontalAlign,
+    pub v_align: VerticalAlign,
+    pub wrap: bool,
+    //multiplies the distance between lines
+    pub line_spacing: f32,
 }
 
 impl RenderPass for TextPass {
@@ -42,44 +82,44 @@ impl TextPass {
             ab_glyph::FontArc::try_from_slice(include_bytes!("Inconsolata-Regular.ttf"))
                 .expect("Couldn't build a font");
 
-        let glyph_brush = GlyphBrushBuilder::using_font(inconsolata)
+        let glyph_brush = GlyphBrushBuilder::using_font(inconsolata.clone())
             .build(&render_context.device, render_context.config.format);
 
         let staging_belt = Arc::new(Mutex::new(wgpu::util::StagingBelt::new(1024)));
 
         commands.insert_resource(Self {
             glyph_brush,
-            staging_belt
+            staging_belt,
+            fonts_in_brush: 1,
+            sdf_text: SdfText::new(&render_context),
         });
+        commands.insert_resource(FontRegistry::new(inconsolata));
     }
 
     fn render(
-        text_boxes: Query<&TextBox>,
+        text_boxes: Query<(&TextBox, Option<&GlobalTransform2d>, Option<&RenderLayers>)>,
+        cameras: Query<&Camera>,
+        active_camera: Option<Res<ActiveCamera>>,
         mut text_pass: ResMut<TextPass>,
+        font_registry: Res<FontRegistry>,
         mut subpass: ResMut<Subpass>,
         render_context: Res<RenderContext>,
     ) {
+        let camera = match Camera::active(&cameras, active_camera.as_deref()) {
+            Some(camera) => camera,
+            None => return,
+        };
+
+        text_pass.sync_fonts(&font_registry);
         {
             let mut staging_belt_lock = text_pass.staging_belt.lock().unwrap();
             staging_belt_lock.recall();
         }
 
-        //we can't reference text pass later, because it is behind a mut reference
-        let bounds = (
-            render_context.size.width as f32,
-            render_context.size.height as f32,
-        );
-
-        text_boxes.for_each(|text_box| {
-            text_pass.glyph_brush.queue(Section {
-                screen_position: text_box.position,
-                bounds,
-                text: vec![Text::new(&text_box.text)
-                    .with_color(text_box.color)
-                    .with_scale(text_box.scale)],
-                ..Section::default()
-            })
-        });
+        //screen text is laid out in the viewport, then the glyph brush is moved over to where the viewport is in the window
+        let bounds = camera.screen_size;
+        let projection = screen_projection(bounds);
+        let window_projection = camera.viewport_matrix() * projection;
 
         //what this is doing actually makes a lot of sense, but rust is a little stupid
         let text_pass = &mut *text_pass;
@@ -87,20 +127,296 @@ impl TextPass {
 
         let mut staging_belt_lock = text_pass.staging_belt.lock().unwrap();
 
+        //untransformed text all goes out in one draw, transformed text needs a draw per box
+        //since the glyph brush only takes a single transform for everything that is queued
+        let drawn_here = |text_box: &TextBox, layers: Option<&RenderLayers>, rendering: TextRendering| {
+            text_box.space == TextSpace::Screen && text_box.rendering == rendering && camera.sees(layers)
+        };
+        for (text_box, ..) in text_boxes.iter().filter(|(text_box, transform, layers)| drawn_here(text_box, *layers, TextRendering::Raster) && transform.is_none()) {
+            let section = text_box.section(text_box.position, bounds, 1f32, &font_registry);
+            text_pass.glyph_brush.queue_custom_layout(section, &text_box.layout(1f32));
+        }
+
         // Draw the text!
         text_pass
             .glyph_brush
-            .draw_queued(
+            .draw_queued_with_transform_and_scissoring(
                 &render_context.device,
                 staging_belt_lock.deref_mut(),
                 &mut *subpass.encoder.as_mut().expect("Cannot access an invalid subpass"),
                 &subpass.texture,
-                bounds.0 as u32,
-                bounds.1 as u32,
+                glyph_transform(window_projection),
+                scissor_region(camera),
             )
             .expect("Draw queued");
 
+        for (text_box, transform, _) in text_boxes.iter().filter(|(text_box, _, layers)| drawn_here(text_box, *layers, TextRendering::Raster)) {
+            if let Some(transform) = transform {
+                let section = text_box.section(text_box.position, bounds, 1f32, &font_registry);
+                text_pass.glyph_brush.queue_custom_layout(section, &text_box.layout(1f32));
+                text_pass
+                    .glyph_brush
+                    .draw_queued_with_transform_and_scissoring(
+                        &render_context.device,
+                        staging_belt_lock.deref_mut(),
+                        &mut *subpass.encoder.as_mut().expect("Cannot access an invalid subpass"),
+                        &subpass.texture,
+                        glyph_transform(window_projection * transform.matrix4()),
+                        scissor_region(camera),
+                    )
+                    .expect("Draw queued");
+            }
+        }
+
+        //sdf text carries its transform in its vertices, so it all goes out in one draw
+        let mut sdf_instances = Vec::new();
+        for (text_box, transform, _) in text_boxes.iter().filter(|(text_box, _, layers)| drawn_here(text_box, *layers, TextRendering::Sdf)) {
+            let transform = transform.map(|transform| transform.matrix4()).unwrap_or_else(|| Matrix4::from_scale(1f32));
+            text_pass.sdf_text.queue(text_box, text_box.position, bounds, transform, &font_registry, &render_context, &mut sdf_instances);
+        }
+        text_pass.sdf_text.set_matrix(TextSpace::Screen, CameraMatrix { view: projection.into() }, &render_context);
+        text_pass.sdf_text.draw(
+            &sdf_instances,
+            TextSpace::Screen,
+            camera,
+            &render_context,
+            subpass.encoder.as_mut().expect("Cannot access an invalid subpass"),
+            &subpass.texture,
+        );
+        text_pass.sdf_text.end_frame();
+
         // Submit the work!
         staging_belt_lock.finish();
     }
+
+    //draws world space text into the target, each raster box goes out in its own draw since each has its own transform
+    //raster glyphs are laid out at the size they end up on screen so they stay sharp at any zoom
+    //sdf boxes are all drawn together after them
+    pub(crate) fn draw_world_text<'a>(
+        &mut self,
+        text_boxes: impl Iterator<Item = (&'a TextBox, Option<&'a GlobalTransform2d>)>,
+        font_registry: &FontRegistry,
+        camera: &Camera,
+        render_context: &RenderContext,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+    ) {
+        //the glyph brush can't set a viewport, so the view is moved over to the viewport and scissored to it instead
+        let view = camera.viewport_matrix() * Matrix4::from(camera.get_matrix().view);
+        let pixels_per_unit = camera.pixels_per_unit();
+        //glyph layout has y pointing down, world space has it pointing up
+        let unit_scale = Matrix4::from_nonuniform_scale(1f32 / pixels_per_unit, -1f32 / pixels_per_unit, 1f32);
+
+        self.sync_fonts(font_registry);
+        let mut staging_belt_lock = self.staging_belt.lock().unwrap();
+        let mut sdf_instances = Vec::new();
+        for (text_box, transform) in text_boxes {
+            let transform = transform.map(|transform| transform.matrix4()).unwrap_or_else(|| Matrix4::from_scale(1f32));
+            let translation = Matrix4::from_translation(Vector3::new(text_box.position.0, text_box.position.1, 0f32));
+            let unbounded = (f32::INFINITY, f32::INFINITY);
+
+            //sdf text is laid out in world units, flipped so y points up
+            if text_box.rendering == TextRendering::Sdf {
+                let flip = Matrix4::from_nonuniform_scale(1f32, -1f32, 1f32);
+                self.sdf_text.queue(text_box, (0f32, 0f32), unbounded, transform * translation * flip, font_registry, render_context, &mut sdf_instances);
+                continue;
+            }
+
+            self.glyph_brush.queue_custom_layout(
+                text_box.section((0f32, 0f32), unbounded, pixels_per_unit, font_registry),
+                &text_box.layout(pixels_per_unit),
+            );
+            self.glyph_brush
+                .draw_queued_with_transform_and_scissoring(
+                    &render_context.device,
+                    staging_belt_lock.deref_mut(),
+                    encoder,
+                    target,
+                    glyph_transform(view * transform * translation * unit_scale),
+                    scissor_region(camera),
+                )
+                .expect("Draw queued");
+        }
+
+        self.sdf_text.set_matrix(TextSpace::World, camera.get_matrix(), render_context);
+        self.sdf_text.draw(&sdf_instances, TextSpace::World, camera, render_context, encoder, target);
+    }
+
+    //fonts loaded since the last frame go into the glyph brush in the same order, so their ids line up
+    fn sync_fonts(&mut self, font_registry: &FontRegistry) {
+        for font in font_registry.fonts().iter().skip(self.fonts_in_brush) {
+            self.glyph_brush.add_font(font.clone());
+            self.fonts_in_brush += 1;
+        }
+    }
+}
+
+impl TextBox {
+    pub fn new(text: &str, position: (f32, f32), color: [f32; 4], scale: f32) -> Self {
+        Self {
+            text: String::from(text),
+            position,
+            color,
+            scale,
+            space: TextSpace::Screen,
+            depth: 0,
+            font: FontRef::Default,
+            rendering: TextRendering::Raster,
+            effects: TextEffects::default(),
+
+            spans: Vec::new(),
+            bounds: None,
+            h_align: HorizontalAlign::Left,
+            v_align: VerticalAlign::Top,
+            wrap: true,
+            line_spacing: 1f32,
+        }
+    }
+
+    //world text is drawn from distance fields so it stays sharp however far the camera zooms
+    pub fn world(text: &str, position: (f32, f32), color: [f32; 4], scale: f32) -> Self {
+        Self {
+            space: TextSpace::World,
+            rendering: TextRendering::Sdf,
+            ..Self::new(text, position, color, scale)
+        }
+    }
+
+    pub fn with_font_name(mut self, name: &str) -> Self {
+        self.font = FontRef::Name(String::from(name));
+        self
+    }
+
+    //effects need sdf rendering, so adding one switches the box over to it
+    pub fn with_outline(mut self, color: [f32; 4], width: f32) -> Self {
+        self.effects.outline = Some(Outline { color, width });
+        self.rendering = TextRendering::Sdf;
+        self
+    }
+
+    pub fn with_shadow(mut self, color: [f32; 4], offset: (f32, f32), softness: f32) -> Self {
+        self.effects.shadow = Some(Shadow { color, offset, softness });
+        self.rendering = TextRendering::Sdf;
+        self
+    }
+
+    pub fn with_glow(mut self, color: [f32; 4], radius: f32) -> Self {
+        self.effects.glow = Some(Glow { color, radius });
+        self.rendering = TextRendering::Sdf;
+        self
+    }
+
+    pub fn with_bounds(mut self, width: f32, height: f32) -> Self {
+        self.bounds = Some((width, height));
+        self
+    }
+
+    pub fn with_align(mut self, h_align: HorizontalAlign, v_align: VerticalAlign) -> Self {
+        self.h_align = h_align;
+        self.v_align = v_align;
+        self
+    }
+
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    //the size of the laid out text, in the same units as the box
+    pub fn measure(&self, font_registry: &FontRegistry) -> (f32, f32) {
+        let section = self.section((0f32, 0f32), (f32::INFINITY, f32::INFINITY), 1f32, font_registry);
+        let geometry = SectionGeometry {
+            screen_position: section.screen_position,
+            bounds: section.bounds,
+        };
+        let glyphs = self.layout(1f32).calculate_glyphs(font_registry.fonts(), &geometry, &section.text);
+        glyph_extent(font_registry.fonts(), &glyphs)
+    }
+
+    //units scales everything in the box, world text is laid out in screen pixels
+    pub(super) fn layout(&self, units: f32) -> SpacedLayout {
+        let layout = if self.wrap {
+            Layout::default_wrap()
+        } else {
+            Layout::default_single_line()
+        };
+
+        SpacedLayout {
+            layout: layout.h_align(self.h_align).v_align(self.v_align),
+            line_spacing: self.line_spacing,
+            line_height: self.scale * units,
+        }
+    }
+
+    //the text is split into runs so characters missing from a span's font are drawn with its fallbacks
+    //glyph brush aligns around the screen position, so it's moved to the matching point of the bounds
+    pub(super) fn section<'a>(&'a self, origin: (f32, f32), default_bounds: (f32, f32), units: f32, font_registry: &FontRegistry) -> Section<'a> {
+        let (bounds, anchor) = match self.bounds {
+            Some((width, height)) => {
+                let (width, height) = (width * units, height * units);
+                let x = match self.h_align {
+                    HorizontalAlign::Left => 0f32,
+                    HorizontalAlign::Center => width / 2f32,
+                    HorizontalAlign::Right => width,
+                };
+                let y = match self.v_align {
+                    VerticalAlign::Top => 0f32,
+                    VerticalAlign::Center => height / 2f32,
+                    VerticalAlign::Bottom => height,
+                };
+                ((width, height), (x, y))
+            }
+            None => (default_bounds, (0f32, 0f32)),
+        };
+
+        let box_font = font_registry.resolve(&self.font);
+        let spans = std::iter::once((self.text.as_str(), self.color, self.scale, box_font)).chain(self.spans.iter().map(|span| {
+            (
+                span.text.as_str(),
+                span.color.unwrap_or(self.color),
+                span.scale.unwrap_or(self.scale),
+                span.font.as_ref().map(|font| font_registry.resolve(font)).unwrap_or(box_font),
+            )
+        }));
+
+        let mut text = Vec::new();
+        for (span_text, color, scale, font) in spans {
+            for (run, font) in font_registry.runs(span_text, font) {
+                text.push(Text::new(run).with_color(color).with_scale(scale * units).with_font_id(font.font_id()));
+            }
+        }
+
+        Section {
+            screen_position: (origin.0 + anchor.0, origin.1 + anchor.1),
+            bounds,
+            text,
+            ..Section::default()
+        }
+    }
+}
+
+//the same projection wgpu_glyph uses by default, pixels with y pointing down
+#[rustfmt::skip]
+fn screen_projection(bounds: (f32, f32)) -> Matrix4<f32> {
+    Matrix4::new(
+        2f32 / bounds.0, 0f32, 0f32, 0f32,
+        0f32, -2f32 / bounds.1, 0f32, 0f32,
+        0f32, 0f32, 1f32, 0f32,
+        -1f32, 1f32, 0f32, 1f32,
+    )
+}
+
+//the camera's viewport in window pixels, kept at least a pixel wide since the scissor can't be empty
+fn scissor_region(camera: &Camera) -> Region {
+    let (x, y, width, height) = camera.viewport_rect();
+    Region {
+        x: x as u32,
+        y: y as u32,
+        width: (width as u32).max(1),
+        height: (height as u32).max(1),
+    }
+}
+
+fn glyph_transform(matrix: Matrix4<f32>) -> [f32; 16] {
+    *AsRef::<[f32; 16]>::as_ref(&matrix)
 }
@@ -0,0 +1,8 @@
+mod tilemap;
+pub use tilemap::{PropertyValue, Tile, TileProperties, Tilemap, TilemapLayer, Tileset};
+
+mod tilemap_pass;
+pub use tilemap_pass::TilemapPass;
+
+mod tiled;
+pub use tiled::load_tiled_map;
@@ -0,0 +1,524 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use super::{PropertyValue, Tile, TileProperties, Tilemap, TilemapLayer, Tileset};
+
+const FLIP_X: u32 = 0x80000000;
+const FLIP_Y: u32 = 0x40000000;
+const FLIP_DIAGONAL: u32 = 0x20000000;
+//the hexagonal rotation flag, which we don't support, but still need to mask off
+const ROTATE_HEX: u32 = 0x10000000;
+
+#[derive(Debug)]
+pub enum TiledError {
+    Io(PathBuf, std::io::Error),
+    //a tileset's image couldn't be read
+    Image(PathBuf, image::ImageError),
+    Json(serde_json::Error),
+    Xml(roxmltree::Error),
+    Invalid(String),
+    Unsupported(String),
+}
+
+impl fmt::Display for TiledError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TiledError::Io(path, error) => write!(f, "couldn't read {}: {}", path.display(), error),
+            TiledError::Image(path, error) => write!(f, "couldn't read the tileset image {}: {}", path.display(), error),
+            TiledError::Json(error) => write!(f, "invalid Tiled json: {}", error),
+            TiledError::Xml(error) => write!(f, "invalid Tiled xml: {}", error),
+            TiledError::Invalid(message) => write!(f, "invalid Tiled map: {}", message),
+            TiledError::Unsupported(message) => write!(f, "unsupported Tiled feature: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for TiledError {}
+
+//everything we take from a map, whichever format it came from
+struct MapData {
+    height: u32,
+    tilesets: Vec<(u32, Tileset)>,
+    layers: Vec<LayerData>,
+}
+
+struct LayerData {
+    name: String,
+    visible: bool,
+    opacity: f32,
+    //x, y, width, height and gids, a finite map is just one chunk covering the whole map
+    chunks: Vec<(i32, i32, u32, u32, Vec<u32>)>,
+}
+
+//loads a map made in the Tiled editor, either json (.json, .tmj) or xml (.tmx)
+//tile_size is the size of a tile in world units
+pub fn load_tiled_map(path: &str, tile_size: [f32; 2]) -> Result<Tilemap, TiledError> {
+    let path = Path::new(path);
+    let source = read_file(path)?;
+
+    let map = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("tmx") => parse_tmx(&source, path)?,
+        _ => parse_json(&source, path)?,
+    };
+
+    build_tilemap(map, tile_size)
+}
+
+fn build_tilemap(map: MapData, tile_size: [f32; 2]) -> Result<Tilemap, TiledError> {
+    let mut tilesets = map.tilesets.into_iter();
+    let (first_gid, tileset) = match (tilesets.next(), tilesets.next()) {
+        (Some(tileset), None) => tileset,
+        (None, _) => return Err(TiledError::Invalid(String::from("the map has no tileset"))),
+        (Some(_), Some(_)) => return Err(TiledError::Unsupported(String::from("maps with more than one tileset"))),
+    };
+
+    let mut tilemap = Tilemap::new(tileset, tile_size);
+    for layer_data in map.layers {
+        let mut layer = TilemapLayer::new(&layer_data.name);
+        layer.visible = layer_data.visible;
+        layer.opacity = layer_data.opacity;
+
+        for (chunk_x, chunk_y, width, height, gids) in layer_data.chunks {
+            if gids.len() != (width * height) as usize {
+                return Err(TiledError::Invalid(format!("layer {} has the wrong number of tiles", layer_data.name)));
+            }
+
+            for (index, gid) in gids.into_iter().enumerate() {
+                let x = chunk_x + (index as u32 % width) as i32;
+                let y = chunk_y + (index as u32 / width) as i32;
+                //Tiled counts rows down from the top, we count them up from the bottom
+                let y = map.height as i32 - 1 - y;
+
+                layer.set_tile(x, y, decode_gid(gid, first_gid));
+            }
+        }
+
+        tilemap = tilemap.with_layer(layer);
+    }
+
+    Ok(tilemap)
+}
+
+fn decode_gid(gid: u32, first_gid: u32) -> Option<Tile> {
+    let id = gid & !(FLIP_X | FLIP_Y | FLIP_DIAGONAL | ROTATE_HEX);
+    if id < first_gid {
+        return None;
+    }
+
+    Some(Tile {
+        index: id - first_gid,
+        flip_x: gid & FLIP_X != 0,
+        flip_y: gid & FLIP_Y != 0,
+        flip_diagonal: gid & FLIP_DIAGONAL != 0,
+    })
+}
+
+fn read_file(path: &Path) -> Result<String, TiledError> {
+    std::fs::read_to_string(path).map_err(|error| TiledError::Io(path.to_path_buf(), error))
+}
+
+//paths inside Tiled files are relative to the file they are in
+fn relative_path(file: &Path, path: &str) -> PathBuf {
+    file.parent().unwrap_or_else(|| Path::new("")).join(path)
+}
+
+//the image and layout of a tileset, as found in either format
+struct TilesetData<'a> {
+    image: &'a str,
+    tile_size: (u32, u32),
+    columns: u32,
+    tile_count: u32,
+    spacing: u32,
+    margin: u32,
+    properties: Vec<(u32, TileProperties)>,
+}
+
+fn build_tileset(file: &Path, data: TilesetData) -> Result<Tileset, TiledError> {
+    let image_path = relative_path(file, data.image);
+    let mut tileset = Tileset::new(&image_path.to_string_lossy(), data.tile_size).map_err(|error| TiledError::Image(image_path.clone(), error))?;
+    tileset.columns = data.columns;
+    tileset.tile_count = data.tile_count;
+    tileset.spacing = data.spacing;
+    tileset.margin = data.margin;
+
+    for (id, tile_properties) in data.properties {
+        tileset.set_properties(id, tile_properties);
+    }
+
+    Ok(tileset)
+}
+
+fn parse_property(kind: &str, value: &str) -> PropertyValue {
+    match kind {
+        "bool" => PropertyValue::Bool(value == "true"),
+        "int" => value.parse().map(PropertyValue::Int).unwrap_or_else(|_| PropertyValue::String(String::from(value))),
+        "float" => value.parse().map(PropertyValue::Float).unwrap_or_else(|_| PropertyValue::String(String::from(value))),
+        //strings, colors, files and object references are all kept as strings
+        _ => PropertyValue::String(String::from(value)),
+    }
+}
+
+//json
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_opacity() -> f32 {
+    1f32
+}
+
+#[derive(Deserialize)]
+struct JsonMap {
+    height: u32,
+    layers: Vec<JsonLayer>,
+    tilesets: Vec<JsonTileset>,
+}
+
+#[derive(Deserialize)]
+struct JsonLayer {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default = "default_true")]
+    visible: bool,
+    #[serde(default = "default_opacity")]
+    opacity: f32,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    encoding: Option<String>,
+    data: Option<Vec<u32>>,
+    chunks: Option<Vec<JsonChunk>>,
+    //the children of a group layer
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+#[derive(Deserialize)]
+struct JsonChunk {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    data: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+struct JsonTileset {
+    firstgid: Option<u32>,
+    //set for tilesets kept in their own file
+    source: Option<String>,
+
+    image: Option<String>,
+    #[serde(default)]
+    tilewidth: u32,
+    #[serde(default)]
+    tileheight: u32,
+    #[serde(default)]
+    columns: u32,
+    #[serde(default)]
+    tilecount: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(default)]
+    margin: u32,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: u32,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonProperty {
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    value: serde_json::Value,
+}
+
+fn parse_json(source: &str, path: &Path) -> Result<MapData, TiledError> {
+    let map: JsonMap = serde_json::from_str(source).map_err(TiledError::Json)?;
+
+    let mut tilesets = Vec::new();
+    for tileset in map.tilesets {
+        let first_gid = tileset.firstgid.unwrap_or(1);
+        let tileset = match tileset.source.clone() {
+            Some(source) => {
+                let tileset_path = relative_path(path, &source);
+                let tileset_source = read_file(&tileset_path)?;
+                match tileset_path.extension().and_then(|extension| extension.to_str()) {
+                    Some("tsx") => parse_tsx(&tileset_source, &tileset_path)?,
+                    _ => json_tileset(serde_json::from_str(&tileset_source).map_err(TiledError::Json)?, &tileset_path)?,
+                }
+            }
+            None => json_tileset(tileset, path)?,
+        };
+        tilesets.push((first_gid, tileset));
+    }
+
+    let mut layers = Vec::new();
+    json_layers(map.layers, &mut layers)?;
+
+    Ok(MapData {
+        height: map.height,
+        tilesets,
+        layers,
+    })
+}
+
+fn json_tileset(tileset: JsonTileset, path: &Path) -> Result<Tileset, TiledError> {
+    let image = tileset
+        .image
+        .as_ref()
+        .ok_or_else(|| TiledError::Unsupported(String::from("image collection tilesets")))?;
+
+    let properties: Vec<(u32, TileProperties)> = tileset
+        .tiles
+        .iter()
+        .map(|tile| {
+            let properties: TileProperties = tile
+                .properties
+                .iter()
+                .map(|property| {
+                    let value = match &property.value {
+                        serde_json::Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    (property.name.clone(), parse_property(&property.kind, &value))
+                })
+                .collect();
+            (tile.id, properties)
+        })
+        .collect();
+
+    build_tileset(
+        path,
+        TilesetData {
+            image,
+            tile_size: (tileset.tilewidth, tileset.tileheight),
+            columns: tileset.columns,
+            tile_count: tileset.tilecount,
+            spacing: tileset.spacing,
+            margin: tileset.margin,
+            properties,
+        },
+    )
+}
+
+//flattens group layers, object and image layers are skipped
+fn json_layers(source: Vec<JsonLayer>, layers: &mut Vec<LayerData>) -> Result<(), TiledError> {
+    for layer in source {
+        match layer.kind.as_str() {
+            "group" => json_layers(layer.layers, layers)?,
+            "tilelayer" => {
+                if layer.encoding.as_deref().unwrap_or("csv") != "csv" {
+                    return Err(TiledError::Unsupported(String::from("base64 encoded layers, save the map with csv layer data")));
+                }
+
+                let chunks = match (layer.data, layer.chunks) {
+                    (_, Some(chunks)) => chunks
+                        .into_iter()
+                        .map(|chunk| (chunk.x, chunk.y, chunk.width, chunk.height, chunk.data))
+                        .collect(),
+                    (Some(data), None) => vec![(0, 0, layer.width, layer.height, data)],
+                    (None, None) => Vec::new(),
+                };
+
+                layers.push(LayerData {
+                    name: layer.name,
+                    visible: layer.visible,
+                    opacity: layer.opacity,
+                    chunks,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+//xml
+
+fn attribute<T: std::str::FromStr>(node: &roxmltree::Node, name: &str) -> Option<T> {
+    node.attribute(name).and_then(|value| value.parse().ok())
+}
+
+fn required_attribute<T: std::str::FromStr>(node: &roxmltree::Node, name: &str) -> Result<T, TiledError> {
+    attribute(node, name)
+        .ok_or_else(|| TiledError::Invalid(format!("<{}> is missing {}", node.tag_name().name(), name)))
+}
+
+fn parse_tmx(source: &str, path: &Path) -> Result<MapData, TiledError> {
+    let document = roxmltree::Document::parse(source).map_err(TiledError::Xml)?;
+    let map = document.root_element();
+
+    let mut tilesets = Vec::new();
+    for tileset in map.children().filter(|node| node.has_tag_name("tileset")) {
+        let first_gid = attribute(&tileset, "firstgid").unwrap_or(1);
+        let tileset = match tileset.attribute("source") {
+            Some(source) => {
+                let tileset_path = relative_path(path, source);
+                let tileset_source = read_file(&tileset_path)?;
+                match tileset_path.extension().and_then(|extension| extension.to_str()) {
+                    Some("tsx") => parse_tsx(&tileset_source, &tileset_path)?,
+                    _ => json_tileset(serde_json::from_str(&tileset_source).map_err(TiledError::Json)?, &tileset_path)?,
+                }
+            }
+            None => xml_tileset(&tileset, path)?,
+        };
+        tilesets.push((first_gid, tileset));
+    }
+
+    let mut layers = Vec::new();
+    xml_layers(&map, &mut layers)?;
+
+    Ok(MapData {
+        height: required_attribute(&map, "height")?,
+        tilesets,
+        layers,
+    })
+}
+
+fn parse_tsx(source: &str, path: &Path) -> Result<Tileset, TiledError> {
+    let document = roxmltree::Document::parse(source).map_err(TiledError::Xml)?;
+    xml_tileset(&document.root_element(), path)
+}
+
+fn xml_tileset(tileset: &roxmltree::Node, path: &Path) -> Result<Tileset, TiledError> {
+    let image = tileset
+        .children()
+        .find(|node| node.has_tag_name("image"))
+        .and_then(|image| image.attribute("source"))
+        .ok_or_else(|| TiledError::Unsupported(String::from("image collection tilesets")))?;
+
+    let properties = tileset
+        .children()
+        .filter(|node| node.has_tag_name("tile"))
+        .map(|tile| {
+            let properties: TileProperties = tile
+                .children()
+                .filter(|node| node.has_tag_name("properties"))
+                .flat_map(|properties| properties.children().filter(|node| node.has_tag_name("property")))
+                .map(|property| {
+                    let name = property.attribute("name").unwrap_or_default();
+                    //long string values are stored as the element's text instead of an attribute
+                    let value = property.attribute("value").or_else(|| property.text()).unwrap_or_default();
+                    (String::from(name), parse_property(property.attribute("type").unwrap_or("string"), value))
+                })
+                .collect();
+            Ok((required_attribute(&tile, "id")?, properties))
+        })
+        .collect::<Result<Vec<_>, TiledError>>()?;
+
+    build_tileset(
+        path,
+        TilesetData {
+            image,
+            tile_size: (required_attribute(tileset, "tilewidth")?, required_attribute(tileset, "tileheight")?),
+            columns: required_attribute(tileset, "columns")?,
+            tile_count: required_attribute(tileset, "tilecount")?,
+            spacing: attribute(tileset, "spacing").unwrap_or(0),
+            margin: attribute(tileset, "margin").unwrap_or(0),
+            properties,
+        },
+    )
+}
+
+fn xml_layers(parent: &roxmltree::Node, layers: &mut Vec<LayerData>) -> Result<(), TiledError> {
+    for node in parent.children().filter(|node| node.is_element()) {
+        match node.tag_name().name() {
+            "group" => xml_layers(&node, layers)?,
+            "layer" => {
+                let data = match node.children().find(|child| child.has_tag_name("data")) {
+                    Some(data) => data,
+                    None => continue,
+                };
+
+                let chunks = if data.children().any(|child| child.has_tag_name("chunk")) {
+                    data.children()
+                        .filter(|child| child.has_tag_name("chunk"))
+                        .map(|chunk| {
+                            Ok((
+                                required_attribute(&chunk, "x")?,
+                                required_attribute(&chunk, "y")?,
+                                required_attribute(&chunk, "width")?,
+                                required_attribute(&chunk, "height")?,
+                                xml_gids(&data, &chunk)?,
+                            ))
+                        })
+                        .collect::<Result<Vec<_>, TiledError>>()?
+                } else {
+                    vec![(0, 0, required_attribute(&node, "width")?, required_attribute(&node, "height")?, xml_gids(&data, &data)?)]
+                };
+
+                layers.push(LayerData {
+                    name: String::from(node.attribute("name").unwrap_or_default()),
+                    visible: node.attribute("visible") != Some("0"),
+                    opacity: attribute(&node, "opacity").unwrap_or(1f32),
+                    chunks,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+//tiles are either csv text or a list of <tile gid=".."/> elements
+fn xml_gids(data: &roxmltree::Node, container: &roxmltree::Node) -> Result<Vec<u32>, TiledError> {
+    match data.attribute("encoding") {
+        Some("csv") => container
+            .text()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| gid.parse().map_err(|_| TiledError::Invalid(format!("bad tile id {}", gid))))
+            .collect(),
+        None => Ok(container
+            .children()
+            .filter(|child| child.has_tag_name("tile"))
+            .map(|tile| attribute(&tile, "gid").unwrap_or(0))
+            .collect()),
+        Some(encoding) => Err(TiledError::Unsupported(format!("{} encoded layers, save the map with csv layer data", encoding))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_gid_reads_the_flip_bits() {
+        let tile = decode_gid(5 | FLIP_X | FLIP_DIAGONAL, 1).unwrap();
+        assert_eq!(tile.index, 4);
+        assert!(tile.flip_x);
+        assert!(!tile.flip_y);
+        assert!(tile.flip_diagonal);
+
+        let tile = decode_gid(3 | FLIP_Y | ROTATE_HEX, 3).unwrap();
+        assert_eq!(tile.index, 0);
+        assert!(!tile.flip_x && tile.flip_y && !tile.flip_diagonal);
+    }
+
+    #[test]
+    fn decode_gid_skips_empty_and_other_tilesets() {
+        assert_eq!(decode_gid(0, 1), None);
+        assert_eq!(decode_gid(FLIP_X, 1), None);
+        assert_eq!(decode_gid(4, 10), None);
+    }
+}
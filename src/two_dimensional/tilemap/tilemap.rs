@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use bevy_ecs::prelude::*;
+
+use crate::two_dimensional::{sprite::SpriteVertex, GlobalTransform2d};
+
+pub const CHUNK_SIZE: i32 = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+pub type TileProperties = HashMap<String, PropertyValue>;
+
+//every tilemap gets its own id, so cached meshes can tell when an entity's map was swapped for another
+static NEXT_TILEMAP_ID: AtomicU64 = AtomicU64::new(0);
+
+//a grid of tiles in a single image
+#[derive(Clone, Debug)]
+pub struct Tileset {
+    pub texture_path: String,
+    pub texture_size: (u32, u32),
+    //in texture pixels
+    pub tile_size: (u32, u32),
+    pub columns: u32,
+    pub tile_count: u32,
+    pub spacing: u32,
+    pub margin: u32,
+
+    properties: HashMap<u32, TileProperties>,
+}
+
+impl Tileset {
+    //reads the size of the image, so it fails if the image can't be opened
+    pub fn new(texture_path: &str, tile_size: (u32, u32)) -> Result<Self, image::ImageError> {
+        let texture_size = image::image_dimensions(texture_path)?;
+        let columns = texture_size.0 / tile_size.0.max(1);
+        let rows = texture_size.1 / tile_size.1.max(1);
+
+        Ok(Self {
+            texture_path: String::from(texture_path),
+            texture_size,
+            tile_size,
+            columns,
+            tile_count: columns * rows,
+            spacing: 0,
+            margin: 0,
+
+            properties: HashMap::new(),
+        })
+    }
+
+    pub fn set_properties(&mut self, index: u32, properties: TileProperties) {
+        self.properties.insert(index, properties);
+    }
+
+    pub fn properties(&self, index: u32) -> Option<&TileProperties> {
+        self.properties.get(&index)
+    }
+
+    //u0, v0, u1, v1 with v pointing down the image
+    pub fn region(&self, index: u32) -> [f32; 4] {
+        let col = index % self.columns.max(1);
+        let row = index / self.columns.max(1);
+
+        let x = self.margin + col * (self.tile_size.0 + self.spacing);
+        let y = self.margin + row * (self.tile_size.1 + self.spacing);
+
+        let (width, height) = (self.texture_size.0 as f32, self.texture_size.1 as f32);
+        [
+            x as f32 / width,
+            y as f32 / height,
+            (x + self.tile_size.0) as f32 / width,
+            (y + self.tile_size.1) as f32 / height,
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub index: u32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    //swap x and y, together with the flips this covers all the rotations Tiled can do
+    pub flip_diagonal: bool,
+}
+
+pub struct Chunk {
+    tiles: Vec<Option<Tile>>,
+    //bumped on every change so the render pass knows when to rebuild its mesh
+    version: u64,
+}
+
+impl Chunk {
+    fn new() -> Self {
+        Self {
+            tiles: vec![None; (CHUNK_SIZE * CHUNK_SIZE) as usize],
+            version: 0,
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+pub struct TilemapLayer {
+    pub name: String,
+    pub visible: bool,
+    pub opacity: f32,
+
+    chunks: HashMap<(i32, i32), Chunk>,
+}
+
+impl TilemapLayer {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            visible: true,
+            opacity: 1f32,
+
+            chunks: HashMap::new(),
+        }
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = (&(i32, i32), &Chunk)> {
+        self.chunks.iter()
+    }
+
+    pub fn get_tile(&self, x: i32, y: i32) -> Option<Tile> {
+        let (chunk, index) = chunk_coords(x, y);
+        self.chunks.get(&chunk).and_then(|chunk| chunk.tiles[index])
+    }
+
+    pub fn set_tile(&mut self, x: i32, y: i32, tile: Option<Tile>) {
+        let (chunk, index) = chunk_coords(x, y);
+        if tile.is_none() && !self.chunks.contains_key(&chunk) {
+            return;
+        }
+
+        let chunk = self.chunks.entry(chunk).or_insert_with(Chunk::new);
+        if chunk.tiles[index] != tile {
+            chunk.tiles[index] = tile;
+            chunk.version += 1;
+        }
+    }
+}
+
+//which chunk a tile is in, and where in that chunk
+fn chunk_coords(x: i32, y: i32) -> ((i32, i32), usize) {
+    let chunk = (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE));
+    let local = (x.rem_euclid(CHUNK_SIZE), y.rem_euclid(CHUNK_SIZE));
+    (chunk, (local.1 * CHUNK_SIZE + local.0) as usize)
+}
+
+//layers of tiles stored in chunks, each chunk is drawn as one mesh that is only rebuilt when the chunk changes
+//tile (0, 0) sits at the origin and y points up
+#[derive(Component)]
+pub struct Tilemap {
+    pub tileset: Tileset,
+    //in world units
+    pub tile_size: [f32; 2],
+    pub layers: Vec<TilemapLayer>,
+    id: u64,
+}
+
+impl Tilemap {
+    pub fn new(tileset: Tileset, tile_size: [f32; 2]) -> Self {
+        Self {
+            tileset,
+            tile_size,
+            layers: Vec::new(),
+            id: NEXT_TILEMAP_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    pub(super) fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn with_layer(mut self, layer: TilemapLayer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&TilemapLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn tile_properties(&self, tile: Tile) -> Option<&TileProperties> {
+        self.tileset.properties(tile.index)
+    }
+
+    pub(super) fn build_chunk_mesh(
+        &self,
+        layer: &TilemapLayer,
+        chunk_position: (i32, i32),
+        chunk: &Chunk,
+        transform: &GlobalTransform2d,
+    ) -> Vec<SpriteVertex> {
+        let color = [1f32, 1f32, 1f32, layer.opacity];
+        let mut vertices = Vec::new();
+
+        for (index, tile) in chunk.tiles.iter().enumerate() {
+            let tile = match tile {
+                Some(tile) if tile.index < self.tileset.tile_count => tile,
+                _ => continue,
+            };
+
+            let x = chunk_position.0 * CHUNK_SIZE + index as i32 % CHUNK_SIZE;
+            let y = chunk_position.1 * CHUNK_SIZE + index as i32 / CHUNK_SIZE;
+            let x0 = x as f32 * self.tile_size[0];
+            let y0 = y as f32 * self.tile_size[1];
+            let (x1, y1) = (x0 + self.tile_size[0], y0 + self.tile_size[1]);
+
+            //corner uvs in bl, br, tl, tr order, the bottom of the tile samples the bottom of the image
+            let [u0, v0, u1, v1] = self.tileset.region(tile.index);
+            let mut uvs = [[u0, v1], [u1, v1], [u0, v0], [u1, v0]];
+            //the diagonal flip goes first, it keeps the top left and bottom right corners where they are
+            if tile.flip_diagonal {
+                uvs.swap(0, 3);
+            }
+            if tile.flip_x {
+                uvs.swap(0, 1);
+                uvs.swap(2, 3);
+            }
+            if tile.flip_y {
+                uvs.swap(0, 2);
+                uvs.swap(1, 3);
+            }
+
+            let corner = |x: f32, y: f32, tex_coord: [f32; 2]| SpriteVertex {
+                position: transform.transform_point([x, y]),
+                tex_coord,
+                color,
+            };
+
+            let bl = corner(x0, y0, uvs[0]);
+            let br = corner(x1, y0, uvs[1]);
+            let tl = corner(x0, y1, uvs[2]);
+            let tr = corner(x1, y1, uvs[3]);
+
+            vertices.extend_from_slice(&[bl, br, tl, tl, br, tr]);
+        }
+
+        vertices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(index: u32) -> Tile {
+        Tile {
+            index,
+            flip_x: false,
+            flip_y: false,
+            flip_diagonal: false,
+        }
+    }
+
+    #[test]
+    fn chunk_coords_round_down_for_negative_tiles() {
+        assert_eq!(chunk_coords(0, 0), ((0, 0), 0));
+        assert_eq!(chunk_coords(CHUNK_SIZE - 1, 1), ((0, 0), (CHUNK_SIZE + CHUNK_SIZE - 1) as usize));
+        assert_eq!(chunk_coords(-1, -1), ((-1, -1), (CHUNK_SIZE * CHUNK_SIZE - 1) as usize));
+        assert_eq!(chunk_coords(-CHUNK_SIZE, -CHUNK_SIZE - 1), ((-1, -2), ((CHUNK_SIZE - 1) * CHUNK_SIZE) as usize));
+    }
+
+    #[test]
+    fn tiles_either_side_of_the_origin_stay_apart() {
+        let mut layer = TilemapLayer::new("ground");
+        layer.set_tile(0, 0, Some(tile(1)));
+        layer.set_tile(-1, 0, Some(tile(2)));
+        layer.set_tile(0, -1, Some(tile(3)));
+
+        assert_eq!(layer.get_tile(0, 0), Some(tile(1)));
+        assert_eq!(layer.get_tile(-1, 0), Some(tile(2)));
+        assert_eq!(layer.get_tile(0, -1), Some(tile(3)));
+        assert_eq!(layer.get_tile(-1, -1), None);
+        assert_eq!(layer.chunks().count(), 3);
+    }
+
+    #[test]
+    fn clearing_an_empty_chunk_doesnt_create_it() {
+        let mut layer = TilemapLayer::new("ground");
+        layer.set_tile(-40, 7, None);
+        assert_eq!(layer.chunks().count(), 0);
+    }
+}
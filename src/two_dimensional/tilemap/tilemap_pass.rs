@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy_ecs::prelude::*;
+use wgpu::{RenderPassDescriptor, RenderPipeline};
+
+use crate::{
+    graphics::{RenderContext, RenderPass, Subpass, TextureCache, Uniform},
+    two_dimensional::{
+        camera::{Camera, CameraMatrix},
+        sprite::{BlendMode, SpritePass, SpriteVertex},
+        ActiveCamera, GlobalTransform2d, RenderLayers,
+    },
+};
+
+use super::Tilemap;
+
+//a chunk's mesh, kept around until the chunk, its layer or the map's transform changes, or the map is replaced
+struct CachedChunk {
+    tilemap_id: u64,
+    version: u64,
+    opacity: f32,
+    transform: GlobalTransform2d,
+
+    vertex_buffer: wgpu::Buffer,
+    num_vertices: u32,
+}
+
+//tilemap entity, layer index and chunk position
+type ChunkKey = (Entity, usize, (i32, i32));
+
+pub struct TilemapPass {
+    camera_uniform: Uniform,
+    render_pipeline: RenderPipeline,
+
+    chunks: HashMap<ChunkKey, CachedChunk>,
+}
+
+impl RenderPass for TilemapPass {
+    fn get_name() -> &'static str {
+        "Tilemap Pass"
+    }
+
+    fn get_init_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::init))
+    }
+
+    fn get_render_system() -> Box<dyn bevy_ecs::system::System<In = (), Out = ()>> {
+        Box::new(IntoSystem::into_system(Self::render))
+    }
+}
+
+impl TilemapPass {
+    fn init(mut commands: Commands, render_context: Res<RenderContext>, texture_cache: Res<TextureCache>) {
+        let camera_uniform = Uniform::new::<CameraMatrix>(render_context.as_ref(), 0);
+
+        //tiles are drawn exactly like sprites, so we share the sprite shader and pipeline setup
+        let shader = render_context
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Tilemap Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("../sprite/sprite.wgsl").into()),
+            });
+
+        let render_pipeline_layout =
+            render_context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Tilemap Pipeline Layout"),
+                    bind_group_layouts: &[&camera_uniform.bind_group_layout, texture_cache.bind_layout().bind_group_layout()],
+                    push_constant_ranges: &[],
+                });
+
+        let render_pipeline = SpritePass::create_pipeline(&render_context, &render_pipeline_layout, &shader, &[SpriteVertex::desc()], BlendMode::Alpha);
+
+        commands.insert_resource(Self {
+            camera_uniform,
+            render_pipeline,
+
+            chunks: HashMap::new(),
+        });
+    }
+
+    fn render(
+        tilemaps: Query<(Entity, &Tilemap, Option<&GlobalTransform2d>, Option<&RenderLayers>)>,
+        cameras: Query<&Camera>,
+        active_camera: Option<Res<ActiveCamera>>,
+        mut tilemap_pass: ResMut<TilemapPass>,
+        mut texture_cache: ResMut<TextureCache>,
+        mut subpass: ResMut<Subpass>,
+        render_context: Res<RenderContext>,
+    ) {
+        let tilemap_pass = &mut *tilemap_pass;
+        let identity = GlobalTransform2d::default();
+
+        let camera = match Camera::active(&cameras, active_camera.as_deref()) {
+            Some(camera) => camera,
+            None => return,
+        };
+
+        //chunks are cached for every camera, only the ones this camera sees get drawn
+        let mut visible_chunks: Vec<(String, ChunkKey, bool)> = Vec::new();
+        for (entity, tilemap, transform, layers) in tilemaps.iter() {
+            let seen = camera.sees(layers);
+            let transform = transform.unwrap_or(&identity);
+
+            let texture_path = &tilemap.tileset.texture_path;
+            texture_cache.load(texture_path, &render_context);
+
+            for (layer_index, layer) in tilemap.layers.iter().enumerate() {
+                if !layer.visible {
+                    continue;
+                }
+
+                for (chunk_position, chunk) in layer.chunks() {
+                    let key = (entity, layer_index, *chunk_position);
+
+                    let stale = match tilemap_pass.chunks.get(&key) {
+                        Some(cached) => cached.tilemap_id != tilemap.id() || cached.version != chunk.version() || cached.opacity != layer.opacity || cached.transform != *transform,
+                        None => true,
+                    };
+
+                    if stale {
+                        let vertices = tilemap.build_chunk_mesh(layer, *chunk_position, chunk, transform);
+                        let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
+                            &render_context.device,
+                            &wgpu::util::BufferInitDescriptor {
+                                label: Some("Tilemap Chunk Vertex Buffer"),
+                                contents: bytemuck::cast_slice(&vertices),
+                                usage: wgpu::BufferUsages::VERTEX,
+                            },
+                        );
+
+                        tilemap_pass.chunks.insert(
+                            key,
+                            CachedChunk {
+                                tilemap_id: tilemap.id(),
+                                version: chunk.version(),
+                                opacity: layer.opacity,
+                                transform: *transform,
+
+                                vertex_buffer,
+                                num_vertices: vertices.len() as u32,
+                            },
+                        );
+                    }
+
+                    visible_chunks.push((texture_path.clone(), key, seen));
+                }
+            }
+        }
+
+        //drop the meshes of chunks that are gone or hidden
+        let visible_keys: HashSet<ChunkKey> = visible_chunks.iter().map(|(_, key, _)| *key).collect();
+        tilemap_pass.chunks.retain(|key, _| visible_keys.contains(key));
+
+        tilemap_pass.camera_uniform.set_buffer(render_context.as_ref(), camera.get_matrix());
+
+        let subpass = &mut *subpass;
+        let encoder = subpass.encoder.as_mut().unwrap();
+
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Tilemap Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &subpass.texture,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        camera.set_viewport(&mut render_pass);
+        render_pass.set_pipeline(&tilemap_pass.render_pipeline);
+        render_pass.set_bind_group(0, &tilemap_pass.camera_uniform.bind_group, &[]);
+        //layers are drawn in order, so later layers end up on top
+        for (texture_path, key, seen) in visible_chunks.iter() {
+            let cached = &tilemap_pass.chunks[key];
+            if !seen || cached.num_vertices == 0 {
+                continue;
+            }
+
+            let (_, bind_group) = texture_cache.get(texture_path).expect("Tileset textures are loaded before drawing");
+            render_pass.set_bind_group(1, bind_group, &[]);
+            render_pass.set_vertex_buffer(0, cached.vertex_buffer.slice(..));
+            render_pass.draw(0..cached.num_vertices, 0..1);
+        }
+    }
+}
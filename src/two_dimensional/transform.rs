@@ -0,0 +1,229 @@
+use bevy_ecs::prelude::*;
+use cgmath::{Matrix3, Matrix4, SquareMatrix, Vector3};
+
+use super::hierarchy::{Children, Parent};
+
+//a 2d transform, rotation and scale are applied around the pivot (in local units) before translating
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Transform2d {
+    pub translation: [f32; 2],
+    //rotation in radians, counter clockwise
+    pub rotation: f32,
+    pub scale: [f32; 2],
+    pub pivot: [f32; 2],
+
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl Default for Transform2d {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform2d {
+    pub const IDENTITY: Self = Self {
+        translation: [0f32, 0f32],
+        rotation: 0f32,
+        scale: [1f32, 1f32],
+        pivot: [0f32, 0f32],
+        flip_x: false,
+        flip_y: false,
+    };
+
+    pub fn from_translation(translation: [f32; 2]) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: [f32; 2]) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_pivot(mut self, pivot: [f32; 2]) -> Self {
+        self.pivot = pivot;
+        self
+    }
+
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    //the scale with flipping folded in, this is what actually gets applied to the geometry
+    pub fn signed_scale(&self) -> [f32; 2] {
+        [
+            if self.flip_x { -self.scale[0] } else { self.scale[0] },
+            if self.flip_y { -self.scale[1] } else { self.scale[1] },
+        ]
+    }
+
+    //translate * pivot * rotate * scale * -pivot, as a homogeneous 2d matrix
+    pub fn compute_matrix(&self) -> Matrix3<f32> {
+        let [sx, sy] = self.signed_scale();
+        let (sin, cos) = self.rotation.sin_cos();
+        let [px, py] = self.pivot;
+
+        //rotation * scale, written out so we don't build four matrices for every sprite every frame
+        let (a, b, c, d) = (cos * sx, sin * sx, -sin * sy, cos * sy);
+
+        let tx = self.translation[0] + px - (a * px + c * py);
+        let ty = self.translation[1] + py - (b * px + d * py);
+
+        //cgmath matrices are column major
+        Matrix3::new(a, b, 0f32, c, d, 0f32, tx, ty, 1f32)
+    }
+}
+
+fn transform_point(matrix: &Matrix3<f32>, point: [f32; 2]) -> [f32; 2] {
+    let transformed = matrix * Vector3::new(point[0], point[1], 1f32);
+    [transformed.x, transformed.y]
+}
+
+//the world space transform of an entity, computed from its Transform2d and those of its parents
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct GlobalTransform2d(Matrix3<f32>);
+
+impl Default for GlobalTransform2d {
+    fn default() -> Self {
+        Self(Matrix3::identity())
+    }
+}
+
+impl From<&Transform2d> for GlobalTransform2d {
+    fn from(transform: &Transform2d) -> Self {
+        Self(transform.compute_matrix())
+    }
+}
+
+impl GlobalTransform2d {
+    pub fn matrix(&self) -> &Matrix3<f32> {
+        &self.0
+    }
+
+    pub fn translation(&self) -> [f32; 2] {
+        [self.0.z.x, self.0.z.y]
+    }
+
+    //the same transform lifted into 3d, for pipelines that take a 4x4 matrix
+    #[rustfmt::skip]
+    pub fn matrix4(&self) -> Matrix4<f32> {
+        let m = &self.0;
+        Matrix4::new(
+            m.x.x, m.x.y, 0f32, 0f32,
+            m.y.x, m.y.y, 0f32, 0f32,
+            0f32, 0f32, 1f32, 0f32,
+            m.z.x, m.z.y, 0f32, 1f32,
+        )
+    }
+
+    pub fn transform_point(&self, point: [f32; 2]) -> [f32; 2] {
+        transform_point(&self.0, point)
+    }
+
+    pub fn inverse_transform_point(&self, point: [f32; 2]) -> Option<[f32; 2]> {
+        self.0.invert().map(|inverse| transform_point(&inverse, point))
+    }
+
+    //walks down from every root and recomputes the global transforms of the whole hierarchy
+    pub fn propagate(
+        mut commands: Commands,
+        roots: Query<(Entity, &Transform2d, Option<&Children>), Without<Parent>>,
+        descendants: Query<(Option<&Transform2d>, Option<&Children>), With<Parent>>,
+        mut globals: Query<&mut GlobalTransform2d>,
+    ) {
+        for (entity, transform, children) in roots.iter() {
+            let global = Self::from(transform);
+            Self::set(&mut commands, &mut globals, entity, global);
+
+            if let Some(children) = children {
+                Self::propagate_children(&mut commands, &descendants, &mut globals, children, &global);
+            }
+        }
+    }
+
+    fn propagate_children(
+        commands: &mut Commands,
+        descendants: &Query<(Option<&Transform2d>, Option<&Children>), With<Parent>>,
+        globals: &mut Query<&mut GlobalTransform2d>,
+        children: &Children,
+        parent: &GlobalTransform2d,
+    ) {
+        for child in children.iter() {
+            if let Ok((transform, grand_children)) = descendants.get(*child) {
+                //an entity with no transform of its own just sits at its parent's transform
+                let global = match transform {
+                    Some(transform) => Self(parent.0 * transform.compute_matrix()),
+                    None => *parent,
+                };
+                Self::set(commands, globals, *child, global);
+
+                if let Some(grand_children) = grand_children {
+                    Self::propagate_children(commands, descendants, globals, grand_children, &global);
+                }
+            }
+        }
+    }
+
+    fn set(commands: &mut Commands, globals: &mut Query<&mut GlobalTransform2d>, entity: Entity, global: GlobalTransform2d) {
+        if let Ok(mut current) = globals.get_mut(entity) {
+            if *current != global {
+                *current = global;
+            }
+        } else {
+            commands.entity(entity).insert(global);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f32; 2], expected: [f32; 2]) {
+        assert!(
+            (actual[0] - expected[0]).abs() < 1e-5 && (actual[1] - expected[1]).abs() < 1e-5,
+            "{:?} is not {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn pivot_stays_put_under_rotation_and_scale() {
+        let transform = Transform2d::IDENTITY.with_pivot([1f32, 1f32]).with_rotation(std::f32::consts::FRAC_PI_2).with_scale([2f32, 3f32]);
+        let matrix = transform.compute_matrix();
+
+        assert_close(transform_point(&matrix, [1f32, 1f32]), [1f32, 1f32]);
+        //one unit right of the pivot is scaled to two and turned to point up
+        assert_close(transform_point(&matrix, [2f32, 1f32]), [1f32, 3f32]);
+    }
+
+    #[test]
+    fn flipping_mirrors_around_the_pivot() {
+        let transform = Transform2d::from_translation([10f32, 0f32]).with_pivot([0.5f32, 0.5f32]).with_flip(true, false);
+        let matrix = transform.compute_matrix();
+
+        assert_close(transform_point(&matrix, [0f32, 0f32]), [11f32, 0f32]);
+        assert_close(transform_point(&matrix, [1f32, 1f32]), [10f32, 1f32]);
+        assert_eq!(transform.signed_scale(), [-1f32, 1f32]);
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        let global = GlobalTransform2d::from(&Transform2d::from_translation([3f32, -2f32]).with_rotation(0.7f32).with_pivot([0.2f32, 0.4f32]).with_flip(false, true));
+        let point = global.transform_point([1.5f32, 2.5f32]);
+
+        assert_close(global.inverse_transform_point(point).unwrap(), [1.5f32, 2.5f32]);
+    }
+}
@@ -1,12 +1,20 @@
 use bevy_ecs::prelude::*;
 use imgui::*;
 
-use crate::{graphics::{RenderContext, Subpass}, core::WindowSystem, app::FrameTime};
+use fluent_bundle::FluentArgs;
+
+use winit::event::VirtualKeyCode;
+
+use crate::{graphics::{RenderContext, Subpass}, core::{ActionState, Binding, Gamepads, Input, InputFocus, InputMap, InputOwner, KeyBinding, WindowSystem}, app::{FrameTime, INPUT_MAP}, board::{Board, Highlight, MOODS}, localization::Localization};
 
 pub struct UI {
     pub context: imgui::Context,
     pub platform: imgui_winit_support::WinitPlatform,
     renderer: imgui_wgpu::Renderer,
+    //the action the controls window is waiting to bind the next key or button to
+    listening: Option<String>,
+    //which of the board's lighting moods is showing, daylight to start with
+    mood: usize,
 }
 
 impl UI {
@@ -42,7 +50,9 @@ impl UI {
         Self {
             context: imgui,
             platform,
-            renderer
+            renderer,
+            listening: None,
+            mood: 0,
         }
     }
 
@@ -53,27 +63,97 @@ impl UI {
 
     //probably will end up moving this code out of the render cycle
     pub fn render(&mut self, world: &mut World) {
+        let highlighted_squares = world.query_filtered::<(), With<Highlight>>().iter(world).count();
+
+        //escape gives up on listening instead of being bound
+        let captured = match (&self.listening, world.get_resource::<Input>()) {
+            (Some(_), Some(input)) => Binding::just_pressed_any(input, world.get_non_send_resource::<Gamepads>()),
+            _ => None,
+        };
+        let cancelled = matches!(captured, Some(Binding::Key(KeyBinding { key: VirtualKeyCode::Escape, .. })));
+
         let render_context = world.get_resource::<RenderContext>().expect("UI lost contact with render context");
         let window_system = world.get_resource::<WindowSystem>().expect("UI lost contact with window");
+        let localization = world.get_resource::<Localization>().expect("Localization has not been initialized");
+        let input_map = world.get_resource::<InputMap>().expect("Input map has not been loaded");
 
         self.platform
             .prepare_frame(self.context.io_mut(), window_system.window()).expect("Unable to prepare frame");
         let ui = self.context.frame();
 
+        //the locale can't change while we're reading from it, so a pick is applied once the frame is drawn
+        let mut picked_locale = None;
+        let mut picked_mood = None;
+        //same for the bindings
+        let mut unbound = None;
+        let mut listen_to = None;
+        let mut save = false;
         {
-            let window = imgui::Window::new("Resources");
+            //the ### part keeps the window's id the same in every language
+            let title = format!("{}###resources", localization.text("ui-resources"));
+            let window = imgui::Window::new(title);
             window
                 .size([300.0, 100.0], Condition::FirstUseEver)
                 .build(&ui, || {
                     if let Some(frame_time) = world.get_resource::<FrameTime>() {
-                        ui.text(format!("Frametime: {}", frame_time.0.as_millis()));
+                        let mut args = FluentArgs::new();
+                        args.set("milliseconds", frame_time.0.as_millis() as f64);
+                        ui.text(localization.format("ui-frame-time", Some(&args)));
                     }
                     ui.separator();
                     let mouse_pos = ui.io().mouse_pos;
-                    ui.text(format!(
-                        "Mouse Position: ({:.1},{:.1})",
-                        mouse_pos[0], mouse_pos[1]
-                    ));
+                    let mut args = FluentArgs::new();
+                    args.set("x", format!("{:.1}", mouse_pos[0]));
+                    args.set("y", format!("{:.1}", mouse_pos[1]));
+                    ui.text(localization.format("ui-mouse-position", Some(&args)));
+
+                    let mut args = FluentArgs::new();
+                    args.set("count", highlighted_squares);
+                    ui.text(localization.format("ui-highlighted-squares", Some(&args)));
+
+                    ui.separator();
+                    let locales: Vec<String> = localization.locales().iter().map(|locale| locale.to_string()).collect();
+                    let current_locale = localization.locale().to_string();
+                    let mut selected = locales.iter().position(|locale| *locale == current_locale).unwrap_or(0);
+                    let label = format!("{}###language", localization.text("ui-language"));
+                    if ui.combo_simple_string(label, &mut selected, &locales) {
+                        picked_locale = locales.get(selected).cloned();
+                    }
+
+                    let moods: Vec<String> = MOODS.iter().map(|(key, _)| localization.text(key)).collect();
+                    let mut selected = self.mood;
+                    let label = format!("{}###lighting", localization.text("ui-lighting"));
+                    if ui.combo_simple_string(label, &mut selected, &moods) {
+                        picked_mood = Some(selected);
+                    }
+                });
+
+            //each binding is a button that removes it, and + listens for a new one
+            let title = format!("{}###controls", localization.text("ui-controls"));
+            imgui::Window::new(title)
+                .size([300.0, 200.0], Condition::FirstUseEver)
+                .position([20.0, 140.0], Condition::FirstUseEver)
+                .build(&ui, || {
+                    for action in input_map.actions() {
+                        ui.text(action_name(localization, action));
+                        for (index, binding) in input_map.bindings(action).iter().enumerate() {
+                            ui.same_line();
+                            if ui.small_button(format!("{}##{}-{}", binding, action, index)) {
+                                unbound = Some((String::from(action), *binding));
+                            }
+                        }
+                        ui.same_line();
+                        if self.listening.as_deref() == Some(action) {
+                            ui.text_disabled(localization.text("ui-controls-listening"));
+                        } else if ui.small_button(format!("+##{}", action)) {
+                            listen_to = Some(String::from(action));
+                        }
+                    }
+
+                    ui.separator();
+                    if ui.button(format!("{}###save-controls", localization.text("ui-controls-save"))) {
+                        save = true;
+                    }
                 });
         }
 
@@ -83,7 +163,7 @@ impl UI {
         let mut ui_pass = Subpass::start(surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default()), render_context, wgpu::LoadOp::Load);
 
         {
-            let mut render_pass = (&mut *ui_pass.encoder.as_mut().unwrap()).begin_render_pass(&wgpu::RenderPassDescriptor {
+            let mut render_pass = ui_pass.encoder.as_mut().unwrap().begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &ui_pass.texture,
@@ -100,6 +180,62 @@ impl UI {
         };
 
         render_context.queue.submit(std::iter::once(ui_pass.finish()));
-        
+
+        //imgui works out what it wants while building the frame, it holds for the events before the next one
+        let io = self.context.io();
+        let owner = |wanted: bool| if wanted { InputOwner::Ui } else { InputOwner::Game };
+        let (mouse, keyboard) = (owner(io.want_capture_mouse), owner(io.want_capture_keyboard));
+        let mut focus = world.get_resource_mut::<InputFocus>().expect("Input focus has not been initialized");
+        focus.set_mouse(mouse);
+        focus.set_keyboard(keyboard);
+
+        self.apply_controls(world, captured.filter(|_| !cancelled), cancelled, unbound, listen_to, save);
+
+        if let Some(locale) = picked_locale {
+            let mut localization = world.get_resource_mut::<Localization>().expect("Localization has not been initialized");
+            if let Err(error) = localization.set_locale(&locale) {
+                eprintln!("Couldn't switch language: {}", error);
+            }
+        }
+
+        if let Some(mood) = picked_mood {
+            self.mood = mood;
+            Board::set_mood(world, MOODS[mood].1);
+        }
     }
-}
\ No newline at end of file
+    //rebinds once the frame is drawn, actions are held off while listening so the key being bound doesn't also fire
+    fn apply_controls(&mut self, world: &mut World, captured: Option<Binding>, cancelled: bool, unbound: Option<(String, Binding)>, listen_to: Option<String>, save: bool) {
+        let mut input_map = world.get_resource_mut::<InputMap>().expect("Input map has not been loaded");
+        if let (Some(action), Some(binding)) = (self.listening.as_deref(), captured) {
+            input_map.bind(action, binding);
+        }
+        if let Some((action, binding)) = unbound {
+            input_map.unbind(&action, binding);
+        }
+        if save {
+            if let Err(error) = input_map.save(INPUT_MAP) {
+                eprintln!("Couldn't save the controls: {}", error);
+            }
+        }
+
+        if captured.is_some() || cancelled {
+            self.listening = None;
+        }
+        if listen_to.is_some() {
+            self.listening = listen_to;
+        }
+        let mut actions = world.get_resource_mut::<ActionState>().expect("Action state has not been initialized");
+        actions.suspend(self.listening.is_some());
+    }
+}
+
+//actions are named in the locale files as action- and the name with dashes, pan_left is action-pan-left
+fn action_name(localization: &Localization, action: &str) -> String {
+    let key = format!("action-{}", action.replace('_', "-"));
+    let name = localization.text(&key);
+    if name == key {
+        String::from(action)
+    } else {
+        name
+    }
+}